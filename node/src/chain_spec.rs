@@ -4,10 +4,11 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_consensus_grandpa::AuthorityId as GrandpaId;
 use sp_core::{sr25519, Pair, Public};
 use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::Perbill;
 
 use node_template_runtime::{
-	AccountId, AssetsConfig, AuraConfig, BalancesConfig, Dex, GrandpaConfig, RuntimeGenesisConfig,
-	Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	AccountId, AssetsConfig, AuraConfig, BalancesConfig, Dex, DexConfig, DexProtocolFeeRecipient,
+	GrandpaConfig, RuntimeGenesisConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 
 // The URL for the telemetry server.
@@ -57,6 +58,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				// Pre-funded accounts
 				vec![
 					Dex::dex_account_id(),
+					DexProtocolFeeRecipient::get(),
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
 					get_account_id_from_seed::<sr25519::Public>("Bob"),
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
@@ -98,6 +100,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Pre-funded accounts
 				vec![
+					Dex::dex_account_id(),
+					DexProtocolFeeRecipient::get(),
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
 					get_account_id_from_seed::<sr25519::Public>("Bob"),
 					get_account_id_from_seed::<sr25519::Public>("Charlie"),
@@ -179,5 +183,35 @@ fn testnet_genesis(
 				(3u32, get_account_id_from_seed::<sr25519::Public>("Charlie"), 1_000_000_000u128),
 			],
 		},
+		dex: DexConfig {
+			// Fee tiers of 0.05%, 0.3% and 1%, and pools of AssetX/AssetY and AssetY/AssetZ at
+			// 0.3%, with their reserves minted to the DEX account, which has to be pre-funded, and
+			// their LP tokens to Alice.
+			fee_tiers: vec![
+				Perbill::from_rational(5u32, 10_000u32),
+				Perbill::from_rational(3u32, 1_000u32),
+				Perbill::from_percent(1),
+			],
+			pools: vec![
+				(
+					1u32,
+					2u32,
+					Perbill::from_rational(3u32, 1_000u32),
+					1_000u32,
+					100_000_000u128,
+					100_000_000u128,
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+				),
+				(
+					2u32,
+					3u32,
+					Perbill::from_rational(3u32, 1_000u32),
+					1_001u32,
+					100_000_000u128,
+					200_000_000u128,
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+				),
+			],
+		},
 	}
 }
@@ -0,0 +1,253 @@
+//! RPC interface for the DEX pallet.
+//!
+//! Exposes the [`pallet_dex::DexApi`] runtime API over JSON-RPC under the `dex_*` namespace, so
+//! that frontends and bots can quote swaps and inspect pools without signing anything.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{traits::Block as BlockT, FixedPointNumber, Perbill};
+
+pub use pallet_dex::{DexApi as DexRuntimeApi, PoolInfo};
+
+/// DEX RPC methods.
+#[rpc(client, server)]
+pub trait DexApi<BlockHash, AssetId, Balance> {
+	/// Amount of `asset_out` received for giving exactly `amount_in` of `asset_in`, in the pool of
+	/// the assets at the fee tier taking the most out of it.
+	#[method(name = "dex_quoteExactIn")]
+	fn quote_exact_in(
+		&self,
+		asset_in: AssetId,
+		asset_out: AssetId,
+		amount_in: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NumberOrHex>>;
+
+	/// Amount of `asset_in` required to receive exactly `amount_out` of `asset_out`, in the pool of
+	/// the assets at the fee tier requiring the least to be given to it.
+	#[method(name = "dex_quoteExactOut")]
+	fn quote_exact_out(
+		&self,
+		asset_in: AssetId,
+		asset_out: AssetId,
+		amount_out: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NumberOrHex>>;
+
+	/// Reserves of the pool for the given assets at `fee_tier`, in the order the assets were passed
+	/// in. Fee tiers are given in parts per billion.
+	#[method(name = "dex_getReserves")]
+	fn get_reserves(
+		&self,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		fee_tier: Perbill,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<(NumberOrHex, NumberOrHex)>>;
+
+	/// All existing pools of a pair of assets, priced by a curve of `Curve`. Weighted and
+	/// concentrated liquidity pools aren't listed.
+	#[method(name = "dex_listPools")]
+	fn list_pools(&self, at: Option<BlockHash>) -> RpcResult<Vec<PoolInfo<AssetId, NumberOrHex>>>;
+
+	/// LP token of the pool for the given assets at `fee_tier`, in parts per billion.
+	#[method(name = "dex_lpTokenOf")]
+	fn lp_token_of(
+		&self,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		fee_tier: Perbill,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<AssetId>>;
+
+	/// Time-weighted average price of `asset_id` in its pool with `other_asset_id` at `fee_tier`,
+	/// in parts per billion, between the newest observations at or before `from` and `to`, in
+	/// unix seconds. The price is scaled by `10^18`.
+	#[method(name = "dex_twap")]
+	fn get_twap(
+		&self,
+		asset_id: AssetId,
+		other_asset_id: AssetId,
+		fee_tier: Perbill,
+		from: u64,
+		to: u64,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NumberOrHex>>;
+}
+
+/// Provides RPC methods to query the DEX.
+pub struct Dex<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Dex<C, Block> {
+	/// Creates a new instance of the DEX Rpc helper.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+	/// A balance returned by the runtime doesn't fit the RPC number representation.
+	BalanceOutOfRange,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::BalanceOutOfRange => 2,
+		}
+	}
+}
+
+fn runtime_error(e: impl ToString) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		"Unable to query the DEX runtime api.",
+		Some(e.to_string()),
+	))
+	.into()
+}
+
+fn into_rpc_balance<Balance: TryInto<NumberOrHex>>(
+	balance: Balance,
+) -> Result<NumberOrHex, JsonRpseeError> {
+	balance.try_into().map_err(|_| {
+		CallError::Custom(ErrorObject::owned(
+			Error::BalanceOutOfRange.into(),
+			"Balance doesn't fit in NumberOrHex representation.",
+			None::<()>,
+		))
+		.into()
+	})
+}
+
+impl<C, Block, AssetId, Balance> DexApiServer<<Block as BlockT>::Hash, AssetId, Balance>
+	for Dex<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: DexRuntimeApi<Block, AssetId, Balance>,
+	AssetId: Codec + Serialize + DeserializeOwned + Send + Sync + 'static,
+	Balance: Codec + TryInto<NumberOrHex> + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	fn quote_exact_in(
+		&self,
+		asset_in: AssetId,
+		asset_out: AssetId,
+		amount_in: Balance,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.quote_exact_in(at_hash, asset_in, asset_out, amount_in)
+			.map_err(runtime_error)?
+			.map(into_rpc_balance)
+			.transpose()
+	}
+
+	fn quote_exact_out(
+		&self,
+		asset_in: AssetId,
+		asset_out: AssetId,
+		amount_out: Balance,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.quote_exact_out(at_hash, asset_in, asset_out, amount_out)
+			.map_err(runtime_error)?
+			.map(into_rpc_balance)
+			.transpose()
+	}
+
+	fn get_reserves(
+		&self,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		fee_tier: Perbill,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<(NumberOrHex, NumberOrHex)>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_reserves(at_hash, asset_a, asset_b, fee_tier)
+			.map_err(runtime_error)?
+			.map(|(reserve_a, reserve_b)| {
+				Ok((into_rpc_balance(reserve_a)?, into_rpc_balance(reserve_b)?))
+			})
+			.transpose()
+	}
+
+	fn list_pools(
+		&self,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<PoolInfo<AssetId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.list_pools(at_hash)
+			.map_err(runtime_error)?
+			.into_iter()
+			.map(|pool| {
+				Ok(PoolInfo {
+					asset_x_id: pool.asset_x_id,
+					asset_y_id: pool.asset_y_id,
+					reserve_x: into_rpc_balance(pool.reserve_x)?,
+					reserve_y: into_rpc_balance(pool.reserve_y)?,
+					total_liquidity: into_rpc_balance(pool.total_liquidity)?,
+					lp_token_id: pool.lp_token_id,
+					fee_tier: pool.fee_tier,
+				})
+			})
+			.collect()
+	}
+
+	fn lp_token_of(
+		&self,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		fee_tier: Perbill,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<AssetId>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.lp_token_of(at_hash, asset_a, asset_b, fee_tier).map_err(runtime_error)
+	}
+
+	fn get_twap(
+		&self,
+		asset_id: AssetId,
+		other_asset_id: AssetId,
+		fee_tier: Perbill,
+		from: u64,
+		to: u64,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_twap(at_hash, asset_id, other_asset_id, fee_tier, from, to)
+			.map_err(runtime_error)?
+			.map(|price| into_rpc_balance(price.into_inner()))
+			.transpose()
+	}
+}
@@ -0,0 +1,83 @@
+//! Union of the native currency and the fungible assets, so that pools can pair either of them.
+
+use core::marker::PhantomData;
+
+use frame_support::traits::tokens::Preservation;
+use frame_support::traits::{fungible, fungibles, Get};
+use sp_runtime::DispatchError;
+
+use crate::{AssetBalanceOf, Config};
+
+/// Routes operations on a pool asset to [`Config::NativeBalance`] if the asset id is
+/// [`Config::NativeAssetId`], or to [`Config::Fungibles`] otherwise.
+pub struct NativeOrAssets<T>(PhantomData<T>);
+
+impl<T: Config> NativeOrAssets<T> {
+	/// Returns whether `asset_id` identifies the native currency.
+	pub fn is_native(asset_id: &T::DexAssetId) -> bool {
+		*asset_id == T::NativeAssetId::get()
+	}
+
+	/// Returns whether `asset_id` identifies an existing asset, the native currency always exists.
+	pub fn asset_exists(asset_id: T::DexAssetId) -> bool {
+		Self::is_native(&asset_id)
+			|| <T::Fungibles as fungibles::Inspect<T::AccountId>>::asset_exists(asset_id)
+	}
+
+	/// Returns the least balance of `asset_id` an account can hold, other than none.
+	pub fn minimum_balance(asset_id: T::DexAssetId) -> AssetBalanceOf<T> {
+		if Self::is_native(&asset_id) {
+			<T::NativeBalance as fungible::Inspect<T::AccountId>>::minimum_balance()
+		} else {
+			<T::Fungibles as fungibles::Inspect<T::AccountId>>::minimum_balance(asset_id)
+		}
+	}
+
+	/// Returns the balance of `asset_id` held by `who`.
+	pub fn balance(asset_id: T::DexAssetId, who: &T::AccountId) -> AssetBalanceOf<T> {
+		if Self::is_native(&asset_id) {
+			<T::NativeBalance as fungible::Inspect<T::AccountId>>::balance(who)
+		} else {
+			<T::Fungibles as fungibles::Inspect<T::AccountId>>::balance(asset_id, who)
+		}
+	}
+
+	/// Mints `amount` of `asset_id` into `who`.
+	pub fn mint_into(
+		asset_id: T::DexAssetId,
+		who: &T::AccountId,
+		amount: AssetBalanceOf<T>,
+	) -> Result<AssetBalanceOf<T>, DispatchError> {
+		if Self::is_native(&asset_id) {
+			<T::NativeBalance as fungible::Mutate<T::AccountId>>::mint_into(who, amount)
+		} else {
+			<T::Fungibles as fungibles::Mutate<T::AccountId>>::mint_into(asset_id, who, amount)
+		}
+	}
+
+	/// Transfers `amount` of `asset_id` from `source` to `dest`.
+	pub fn transfer(
+		asset_id: T::DexAssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		amount: AssetBalanceOf<T>,
+		preservation: Preservation,
+	) -> Result<AssetBalanceOf<T>, DispatchError> {
+		if Self::is_native(&asset_id) {
+			<T::NativeBalance as fungible::Mutate<T::AccountId>>::transfer(
+				source,
+				dest,
+				amount,
+				preservation,
+			)
+		} else {
+			<T::Fungibles as fungibles::Mutate<T::AccountId>>::transfer(
+				asset_id,
+				source,
+				dest,
+				amount,
+				preservation,
+			)
+		}
+	}
+}
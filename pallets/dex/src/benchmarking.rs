@@ -0,0 +1,1002 @@
+//! Benchmarking setup for pallet-dex
+#![cfg(feature = "runtime-benchmarks")]
+
+use frame_benchmarking::v2::*;
+use frame_support::traits::fungible;
+use frame_support::traits::fungibles::{Create, Inspect, Mutate};
+use frame_support::traits::UnixTime;
+use frame_support::BoundedVec;
+use frame_system::RawOrigin;
+use sp_runtime::{DispatchError, PerThing, Perbill, Saturating};
+
+use super::*;
+#[allow(unused)]
+use crate::Pallet as Dex;
+
+const SEED: u32 = 0;
+/// Balance of each asset minted to every account taking part in a benchmark.
+const INITIAL_BALANCE: u32 = 1_000_000_000;
+/// Balance of each asset provided to every pool when it is created.
+const RESERVE: u32 = 1_000_000;
+/// Amount swapped or provided by the benchmarked call, small enough not to drain a pool.
+const AMOUNT: u32 = 1_000;
+/// Fee tier every pool is created at.
+const FEE_TIER: Perbill = Perbill::from_percent(1);
+/// Amplification coefficient of every StableSwap pool.
+const AMPLIFICATION: u32 = 100;
+/// Tick spacing of every concentrated liquidity pool.
+const TICK_SPACING: u32 = 10;
+/// Liquidity of every position in a concentrated liquidity pool.
+const POSITION_LIQUIDITY: u128 = 1_000_000;
+/// Amount swapped in a concentrated liquidity pool, enough to cross all of its narrow positions.
+const CONCENTRATED_AMOUNT: u32 = 100_000;
+
+fn asset_id<T: Config>(id: u32) -> T::DexAssetId {
+	T::BenchmarkHelper::asset_id(id)
+}
+
+fn balance<T: Config>(amount: u32) -> AssetBalanceOf<T> {
+	amount.into()
+}
+
+/// Gives `who` enough of the native currency to hold any asset.
+fn fund_native<T: Config>(who: &T::AccountId) {
+	let amount = <T::NativeBalance as fungible::Inspect<T::AccountId>>::minimum_balance()
+		.saturating_mul(INITIAL_BALANCE.into());
+	<T::NativeBalance as fungible::Mutate<T::AccountId>>::set_balance(who, amount);
+}
+
+/// Returns an origin that passes the runtime's `AdminOrigin`.
+fn admin_origin<T: Config>() -> Result<T::RuntimeOrigin, DispatchError> {
+	T::AdminOrigin::try_successful_origin().map_err(|_| DispatchError::BadOrigin)
+}
+
+/// Allows creating pools at `FEE_TIER`, which the runtime may not allow at genesis.
+fn allow_fee_tier<T: Config>() -> Result<(), DispatchError> {
+	Dex::<T>::set_fee_tier(admin_origin::<T>()?, FEE_TIER, true)
+}
+
+/// Creates `count` new assets, skipping ids of assets that already exist, e.g. in genesis, and
+/// mints `INITIAL_BALANCE` of each to every one of `holders`.
+fn create_assets<T: Config>(
+	count: u32,
+	holders: &[&T::AccountId],
+) -> Result<Vec<T::DexAssetId>, DispatchError> {
+	fund_native::<T>(&Dex::<T>::dex_account_id());
+	holders.iter().for_each(|holder| fund_native::<T>(holder));
+
+	let mut assets = Vec::new();
+	let mut id = 0;
+	while assets.len() < count as usize {
+		id += 1;
+		let asset_id = asset_id::<T>(id);
+		if T::Fungibles::asset_exists(asset_id) {
+			continue;
+		}
+		T::Fungibles::create(asset_id, Dex::<T>::dex_account_id(), false, balance::<T>(1))?;
+		for holder in holders {
+			T::Fungibles::mint_into(asset_id, holder, balance::<T>(INITIAL_BALANCE))?;
+		}
+		assets.push(asset_id);
+	}
+	Ok(assets)
+}
+
+/// Creates a pool for `asset_x_id` and `asset_y_id` at `FEE_TIER` and provides `RESERVE` of each
+/// asset to it on behalf of `provider`.
+///
+/// The pool is a StableSwap one, the costlier curve to price swaps and liquidity by, as its
+/// invariant is solved iteratively.
+fn create_pool_with_liquidity<T: Config>(
+	provider: &T::AccountId,
+	asset_x_id: T::DexAssetId,
+	asset_y_id: T::DexAssetId,
+) -> Result<AssetIdPair<T>, DispatchError> {
+	allow_fee_tier::<T>()?;
+	Dex::<T>::create_stable_pool(
+		RawOrigin::Signed(provider.clone()).into(),
+		asset_x_id,
+		asset_y_id,
+		FEE_TIER,
+		AMPLIFICATION,
+		None,
+	)?;
+	let pair = AssetIdPair::<T>::new(asset_x_id, asset_y_id)?;
+	Dex::<T>::provide_liquidity(
+		RawOrigin::Signed(provider.clone()).into(),
+		AssetAmountPair::new(pair.clone(), balance::<T>(RESERVE), balance::<T>(RESERVE)),
+		FEE_TIER,
+		None,
+	)?;
+	Ok(pair)
+}
+
+/// Creates `n` assets and a pool for each consecutive pair of them, with a full book of limit
+/// orders along the path, returning the path through all of them.
+fn create_path<T: Config>(
+	n: u32,
+	caller: &T::AccountId,
+) -> Result<BoundedVec<T::DexAssetId, T::MaxPathLength>, DispatchError> {
+	let provider: T::AccountId = account("provider", 0, SEED);
+	let path = create_assets::<T>(n, &[&provider, caller])?;
+	for hop in path.windows(2) {
+		create_pool_with_liquidity::<T>(&provider, hop[0], hop[1])?;
+		place_orders::<T>(T::MaxOrdersPerBook::get(), hop[1], hop[0])?;
+	}
+	Ok(BoundedVec::truncate_from(path))
+}
+
+/// Returns `assets` weighing the same in a weighted pool, but for the remainder of dividing one
+/// between them, which goes to the first.
+fn equal_weights<T: Config>(
+	assets: &[T::DexAssetId],
+) -> BoundedVec<(T::DexAssetId, Perbill), T::MaxWeightedPoolAssets> {
+	let count = assets.len() as u32;
+	let weight = Perbill::ACCURACY / count;
+	BoundedVec::truncate_from(
+		assets
+			.iter()
+			.enumerate()
+			.map(|(index, asset_id)| {
+				let remainder = if index == 0 { Perbill::ACCURACY % count } else { 0 };
+				(*asset_id, Perbill::from_parts(weight + remainder))
+			})
+			.collect(),
+	)
+}
+
+/// Creates `MaxWeightedPoolAssets` assets and a weighted pool of them at `FEE_TIER`, weighing
+/// them the same, and joins it with `RESERVE` of each asset on behalf of `provider`. Returns the
+/// assets and the LP token of the pool.
+fn create_weighted_pool_with_liquidity<T: Config>(
+	provider: &T::AccountId,
+	holders: &[&T::AccountId],
+) -> Result<(Vec<T::DexAssetId>, T::DexAssetId), DispatchError> {
+	let assets = create_assets::<T>(T::MaxWeightedPoolAssets::get(), holders)?;
+	allow_fee_tier::<T>()?;
+	Dex::<T>::create_weighted_pool(
+		RawOrigin::Signed(provider.clone()).into(),
+		equal_weights::<T>(&assets),
+		FEE_TIER,
+		None,
+	)?;
+	let lp_token_id = WeightedPools::<T>::iter_keys().next().ok_or("pool should exist")?;
+	Dex::<T>::join_weighted_pool(
+		RawOrigin::Signed(provider.clone()).into(),
+		lp_token_id,
+		BoundedVec::truncate_from(vec![balance::<T>(RESERVE); assets.len()]),
+		Zero::zero(),
+		None,
+	)?;
+	Ok((assets, lp_token_id))
+}
+
+/// Creates a concentrated liquidity pool for `asset_x_id` and `asset_y_id` at `FEE_TIER` and a
+/// price of one.
+fn create_concentrated_pool_at_one<T: Config>(
+	creator: &T::AccountId,
+	asset_x_id: T::DexAssetId,
+	asset_y_id: T::DexAssetId,
+) -> Result<AssetIdPair<T>, DispatchError> {
+	allow_fee_tier::<T>()?;
+	Dex::<T>::create_concentrated_pool(
+		RawOrigin::Signed(creator.clone()).into(),
+		asset_x_id,
+		asset_y_id,
+		FEE_TIER,
+		TICK_SPACING,
+		// one in Q64.64
+		1 << 64,
+		None,
+	)?;
+	Ok(AssetIdPair::<T>::new(asset_x_id, asset_y_id)?)
+}
+
+/// Opens a position of `POSITION_LIQUIDITY` from `tick_lower` to `tick_upper` in the pool of
+/// `pair` at `FEE_TIER` on behalf of `owner`, returning its id.
+fn open_range_position<T: Config>(
+	owner: &T::AccountId,
+	pair: &AssetIdPair<T>,
+	tick_lower: i32,
+	tick_upper: i32,
+) -> Result<PositionId, DispatchError> {
+	let position_id = NextPositionId::<T>::get();
+	Dex::<T>::open_position(
+		RawOrigin::Signed(owner.clone()).into(),
+		pair.clone(),
+		FEE_TIER,
+		tick_lower,
+		tick_upper,
+		POSITION_LIQUIDITY,
+		balance::<T>(INITIAL_BALANCE),
+		balance::<T>(INITIAL_BALANCE),
+		None,
+	)?;
+	Ok(position_id)
+}
+
+/// Creates a concentrated liquidity pool for `asset_x_id` and `asset_y_id` with
+/// `MaxConcentratedTicks` ticks, all above its price, on behalf of `provider`: narrow positions
+/// one tick spacing apart, and a last one up to the end of the range of ticks, so that a swap
+/// raising the price crosses every tick.
+fn create_concentrated_pool_with_ticks<T: Config>(
+	provider: &T::AccountId,
+	asset_x_id: T::DexAssetId,
+	asset_y_id: T::DexAssetId,
+) -> Result<AssetIdPair<T>, DispatchError> {
+	let pair = create_concentrated_pool_at_one::<T>(provider, asset_x_id, asset_y_id)?;
+	let spacing = TICK_SPACING as i32;
+	let positions = (T::MaxConcentratedTicks::get() / 2) as i32;
+	for index in 0..positions {
+		let tick_lower = (2 * index + 1) * spacing;
+		let tick_upper = if index + 1 < positions {
+			tick_lower + spacing
+		} else {
+			math::MAX_TICK / spacing * spacing
+		};
+		open_range_position::<T>(provider, &pair, tick_lower, tick_upper)?;
+	}
+	Ok(pair)
+}
+
+/// Returns the amount every limit order offers, a full book of them offering about half of
+/// `AMOUNT`, or the runtime's `MinOrderAmount` if that is more.
+fn order_amount<T: Config>() -> AssetBalanceOf<T> {
+	balance::<T>((AMOUNT / (2 * T::MaxOrdersPerBook::get())).max(1)).max(T::MinOrderAmount::get())
+}
+
+/// Returns the amount swapped by the swaps filling a full book of limit orders, twice what the
+/// book offers, so that they fill all of it and still swap in the pool.
+fn book_swap_amount<T: Config>() -> AssetBalanceOf<T> {
+	order_amount::<T>().saturating_mul(balance::<T>(2 * T::MaxOrdersPerBook::get()))
+}
+
+/// Places `count` limit orders, each by another maker, offering `order_amount` of
+/// `offer_asset_id` for as much of `want_asset_id`, which is cheaper than the pools of
+/// `create_pool_with_liquidity`, as it saves their fee.
+fn place_orders<T: Config>(
+	count: u32,
+	offer_asset_id: T::DexAssetId,
+	want_asset_id: T::DexAssetId,
+) -> Result<(), DispatchError> {
+	for index in 0..count {
+		let maker: T::AccountId = account("maker", index, SEED);
+		fund_native::<T>(&maker);
+		T::Fungibles::mint_into(offer_asset_id, &maker, balance::<T>(INITIAL_BALANCE))?;
+		Dex::<T>::place_order(
+			RawOrigin::Signed(maker).into(),
+			AssetAmount::new(offer_asset_id, order_amount::<T>()),
+			AssetAmount::new(want_asset_id, order_amount::<T>()),
+			None,
+		)?;
+	}
+	Ok(())
+}
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn create_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		allow_fee_tier::<T>()?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), assets[0], assets[1], FEE_TIER, None);
+
+		assert!(Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).is_some());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn provide_liquidity() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		let provision = AssetAmountPair::new(pair, balance::<T>(AMOUNT), balance::<T>(AMOUNT));
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), provision, FEE_TIER, None);
+
+		let lp_token_id =
+			Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).ok_or("pool should exist")?;
+		assert!(!T::Fungibles::balance(lp_token_id, &caller).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn remove_liquidity() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_pool_with_liquidity::<T>(&caller, assets[0], assets[1])?;
+		let lp_token_id =
+			Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).ok_or("pool should exist")?;
+		let lp_tokens = T::Fungibles::balance(lp_token_id, &caller);
+		let half = lp_tokens / balance::<T>(2);
+
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			pair,
+			FEE_TIER,
+			half,
+			Zero::zero(),
+			Zero::zero(),
+			None,
+			None,
+		);
+
+		assert_eq!(T::Fungibles::balance(lp_token_id, &caller), lp_tokens - half);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_limit_take() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		place_orders::<T>(T::MaxOrdersPerBook::get(), assets[1], assets[0])?;
+		let give = AssetAmount::<T>::new(assets[0], book_swap_amount::<T>());
+
+		// worst case fills a full book before swapping in the pool
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), give, Zero::zero(), pair.clone(), FEE_TIER, None);
+
+		assert!(T::Fungibles::balance(assets[1], &caller) > balance::<T>(INITIAL_BALANCE));
+		assert!(OrderBooks::<T>::get(pair, assets[1]).is_empty());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_limit_give() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		place_orders::<T>(T::MaxOrdersPerBook::get(), assets[1], assets[0])?;
+		let take = AssetAmount::<T>::new(assets[1], book_swap_amount::<T>());
+
+		// worst case fills a full book before swapping in the pool
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			take,
+			balance::<T>(INITIAL_BALANCE),
+			pair.clone(),
+			FEE_TIER,
+			None,
+		);
+
+		assert_eq!(
+			T::Fungibles::balance(assets[1], &caller),
+			balance::<T>(INITIAL_BALANCE) + book_swap_amount::<T>()
+		);
+		assert!(OrderBooks::<T>::get(pair, assets[1]).is_empty());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn get_asset_price() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_pool_with_liquidity::<T>(&caller, assets[0], assets[1])?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), pair, FEE_TIER, assets[0]);
+
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_exact_in_along_path(
+		n: Linear<2, { T::MaxPathLength::get() }>,
+	) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let path = create_path::<T>(n, &caller)?;
+		let last = *path.last().ok_or("path should not be empty")?;
+
+		// worst case fills a full book on every hop before swapping in its pool
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), path, book_swap_amount::<T>(), Zero::zero(), None);
+
+		assert!(T::Fungibles::balance(last, &caller) > balance::<T>(INITIAL_BALANCE));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_exact_out_along_path(
+		n: Linear<2, { T::MaxPathLength::get() }>,
+	) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let path = create_path::<T>(n, &caller)?;
+		let last = *path.last().ok_or("path should not be empty")?;
+
+		// worst case fills a full book on every hop before swapping in its pool
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			path,
+			book_swap_amount::<T>(),
+			balance::<T>(INITIAL_BALANCE),
+			None,
+		);
+
+		assert_eq!(
+			T::Fungibles::balance(last, &caller),
+			balance::<T>(INITIAL_BALANCE) + book_swap_amount::<T>()
+		);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_protocol_fee() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_pool_with_liquidity::<T>(&caller, assets[0], assets[1])?;
+		fund_native::<T>(&T::ProtocolFeeRecipient::get());
+
+		// worst case is turning the fee off after swaps grew the pool, which mints the fee
+		let origin = admin_origin::<T>()?;
+		Dex::<T>::set_protocol_fee(origin.clone(), pair.clone(), FEE_TIER, true)?;
+		Dex::<T>::swap_limit_take(
+			RawOrigin::Signed(caller).into(),
+			AssetAmount::<T>::new(assets[0], balance::<T>(AMOUNT)),
+			Zero::zero(),
+			pair.clone(),
+			FEE_TIER,
+			None,
+		)?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, pair, FEE_TIER, false);
+
+		let lp_token_id =
+			Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).ok_or("pool should exist")?;
+		assert!(!T::Fungibles::balance(lp_token_id, &T::ProtocolFeeRecipient::get()).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn add_liquidity() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+
+		// the desired amounts are off the ratio of the reserves, so the optimal one is computed
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			pair,
+			FEE_TIER,
+			balance::<T>(AMOUNT),
+			balance::<T>(AMOUNT * 2),
+			Zero::zero(),
+			Zero::zero(),
+			None,
+		);
+
+		assert_eq!(
+			T::Fungibles::balance(assets[1], &caller),
+			balance::<T>(INITIAL_BALANCE - AMOUNT)
+		);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_fee_tier() -> Result<(), BenchmarkError> {
+		// worst case is allowing the lowest fee tier next to `MaxFeeTiers - 1` others, which
+		// shifts all of them
+		FeeTiers::<T>::kill();
+		for parts in 2..=T::MaxFeeTiers::get() {
+			Dex::<T>::set_fee_tier(admin_origin::<T>()?, Perbill::from_parts(parts), true)?;
+		}
+		let fee_tier = Perbill::from_parts(1);
+		let origin = admin_origin::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, fee_tier, true);
+
+		assert_eq!(FeeTiers::<T>::get().first(), Some(&fee_tier));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_protocol_share() -> Result<(), BenchmarkError> {
+		let share = T::MaxProtocolFeeShare::get();
+		let origin = admin_origin::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, share);
+
+		assert_eq!(DexParameters::<T>::get().protocol_share, share);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_min_liquidity() -> Result<(), BenchmarkError> {
+		let minimum_liquidity =
+			DexParameters::<T>::get().lp_token_dust.saturating_mul(balance::<T>(AMOUNT));
+		let origin = admin_origin::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, minimum_liquidity);
+
+		assert_eq!(DexParameters::<T>::get().minimum_liquidity, minimum_liquidity);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_lp_token_dust() -> Result<(), BenchmarkError> {
+		// worst case is checking the dust against a positive minimum liquidity
+		let lp_token_dust = balance::<T>(AMOUNT);
+		Dex::<T>::set_min_liquidity(
+			admin_origin::<T>()?,
+			lp_token_dust.saturating_mul(balance::<T>(AMOUNT)),
+		)?;
+		let origin = admin_origin::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, lp_token_dust);
+
+		assert_eq!(DexParameters::<T>::get().lp_token_dust, lp_token_dust);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn create_stable_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		allow_fee_tier::<T>()?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), assets[0], assets[1], FEE_TIER, AMPLIFICATION, None);
+
+		assert!(Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).is_some());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn ramp_amplification() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_pool_with_liquidity::<T>(&caller, assets[0], assets[1])?;
+		fund_native::<T>(&T::ProtocolFeeRecipient::get());
+
+		// worst case is ramping after swaps grew a pool with the protocol fee on, which mints it
+		let origin = admin_origin::<T>()?;
+		Dex::<T>::set_protocol_fee(origin.clone(), pair.clone(), FEE_TIER, true)?;
+		Dex::<T>::swap_limit_take(
+			RawOrigin::Signed(caller).into(),
+			AssetAmount::<T>::new(assets[0], balance::<T>(AMOUNT)),
+			Zero::zero(),
+			pair.clone(),
+			FEE_TIER,
+			None,
+		)?;
+		let future_time =
+			T::Time::now().as_secs().saturating_add(T::MinAmplificationRampDuration::get());
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, pair, FEE_TIER, AMPLIFICATION * 2, future_time);
+
+		let lp_token_id =
+			Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).ok_or("pool should exist")?;
+		assert!(!T::Fungibles::balance(lp_token_id, &T::ProtocolFeeRecipient::get()).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn create_weighted_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(T::MaxWeightedPoolAssets::get(), &[&caller])?;
+		allow_fee_tier::<T>()?;
+		let weights = equal_weights::<T>(&assets);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), weights, FEE_TIER, None);
+
+		assert_eq!(WeightedPools::<T>::iter_keys().count(), 1);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn join_weighted_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(T::MaxWeightedPoolAssets::get(), &[&caller])?;
+		allow_fee_tier::<T>()?;
+		Dex::<T>::create_weighted_pool(
+			RawOrigin::Signed(caller.clone()).into(),
+			equal_weights::<T>(&assets),
+			FEE_TIER,
+			None,
+		)?;
+		let lp_token_id = WeightedPools::<T>::iter_keys().next().ok_or("pool should exist")?;
+		let amounts = BoundedVec::truncate_from(vec![balance::<T>(RESERVE); assets.len()]);
+
+		// worst case is the first join, which computes the invariant and locks the minimum
+		// liquidity
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), lp_token_id, amounts, Zero::zero(), None);
+
+		assert!(!T::Fungibles::balance(lp_token_id, &caller).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn join_weighted_pool_single() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let (assets, lp_token_id) =
+			create_weighted_pool_with_liquidity::<T>(&provider, &[&provider, &caller])?;
+		let give = AssetAmount::<T>::new(assets[0], balance::<T>(AMOUNT));
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), lp_token_id, give, Zero::zero(), None);
+
+		assert!(!T::Fungibles::balance(lp_token_id, &caller).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn exit_weighted_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let (assets, lp_token_id) = create_weighted_pool_with_liquidity::<T>(&caller, &[&caller])?;
+		let lp_tokens = T::Fungibles::balance(lp_token_id, &caller);
+		let half = lp_tokens / balance::<T>(2);
+		let min_amounts = BoundedVec::truncate_from(vec![Zero::zero(); assets.len()]);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), lp_token_id, half, min_amounts, None);
+
+		assert_eq!(T::Fungibles::balance(lp_token_id, &caller), lp_tokens - half);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn exit_weighted_pool_single() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let (assets, lp_token_id) = create_weighted_pool_with_liquidity::<T>(&caller, &[&caller])?;
+		let lp_tokens = T::Fungibles::balance(lp_token_id, &caller);
+
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			lp_token_id,
+			balance::<T>(AMOUNT),
+			assets[0],
+			Zero::zero(),
+			None,
+		);
+
+		assert_eq!(T::Fungibles::balance(lp_token_id, &caller), lp_tokens - balance::<T>(AMOUNT));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_weighted_limit_take() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let (assets, lp_token_id) =
+			create_weighted_pool_with_liquidity::<T>(&provider, &[&provider, &caller])?;
+		let give = AssetAmount::<T>::new(assets[0], balance::<T>(AMOUNT));
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), lp_token_id, give, assets[1], Zero::zero(), None);
+
+		assert!(T::Fungibles::balance(assets[1], &caller) > balance::<T>(INITIAL_BALANCE));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_weighted_limit_give() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let (assets, lp_token_id) =
+			create_weighted_pool_with_liquidity::<T>(&provider, &[&provider, &caller])?;
+		let take = AssetAmount::<T>::new(assets[1], balance::<T>(AMOUNT));
+
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			lp_token_id,
+			take,
+			assets[0],
+			balance::<T>(INITIAL_BALANCE),
+			None,
+		);
+
+		assert_eq!(
+			T::Fungibles::balance(assets[1], &caller),
+			balance::<T>(INITIAL_BALANCE + AMOUNT)
+		);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn create_constant_sum_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		allow_fee_tier::<T>()?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), assets[0], assets[1], FEE_TIER, None);
+
+		assert!(Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).is_some());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn create_concentrated_pool() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		allow_fee_tier::<T>()?;
+		let pair = AssetIdPair::<T>::new(assets[0], assets[1])?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), assets[0], assets[1], FEE_TIER, TICK_SPACING, 1 << 64, None);
+
+		assert!(ConcentratedPools::<T>::contains_key(pair, FEE_TIER));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn open_position() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_concentrated_pool_at_one::<T>(&caller, assets[0], assets[1])?;
+		let max_amount = balance::<T>(INITIAL_BALANCE);
+
+		// worst case is a range containing the price, which takes both assets and changes the
+		// liquidity of the pool
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller),
+			pair,
+			FEE_TIER,
+			-1_000,
+			1_000,
+			POSITION_LIQUIDITY,
+			max_amount,
+			max_amount,
+			None,
+		);
+
+		assert_eq!(Positions::<T>::iter_keys().count(), 1);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn decrease_position() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_concentrated_pool_at_one::<T>(&caller, assets[0], assets[1])?;
+		let position_id = open_range_position::<T>(&caller, &pair, -1_000, 1_000)?;
+
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller),
+			position_id,
+			POSITION_LIQUIDITY / 2,
+			Zero::zero(),
+			Zero::zero(),
+			None,
+		);
+
+		let position = Positions::<T>::get(position_id).ok_or("position should exist")?;
+		assert_eq!(position.liquidity, POSITION_LIQUIDITY / 2);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn collect_position_fees() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_concentrated_pool_at_one::<T>(&caller, assets[0], assets[1])?;
+		let position_id = open_range_position::<T>(&caller, &pair, -1_000, 1_000)?;
+		Dex::<T>::swap_concentrated_limit_take(
+			RawOrigin::Signed(caller.clone()).into(),
+			AssetAmount::<T>::new(assets[0], balance::<T>(AMOUNT)),
+			Zero::zero(),
+			pair,
+			FEE_TIER,
+			None,
+		)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), position_id, None);
+
+		let position = Positions::<T>::get(position_id).ok_or("position should exist")?;
+		assert!(position.tokens_owed_x.is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn transfer_position() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let receiver: T::AccountId = account("receiver", 0, SEED);
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_concentrated_pool_at_one::<T>(&caller, assets[0], assets[1])?;
+		let position_id = open_range_position::<T>(&caller, &pair, -1_000, 1_000)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), position_id, receiver.clone(), None);
+
+		let position = Positions::<T>::get(position_id).ok_or("position should exist")?;
+		assert_eq!(position.owner, receiver);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_concentrated_limit_take() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_concentrated_pool_with_ticks::<T>(&provider, assets[0], assets[1])?;
+		let give = AssetAmount::<T>::new(assets[1], balance::<T>(CONCENTRATED_AMOUNT));
+
+		// worst case crosses every tick of the pool
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), give, Zero::zero(), pair.clone(), FEE_TIER, None);
+
+		// all but the upper tick of the last position
+		let pool = ConcentratedPools::<T>::get(pair, FEE_TIER).ok_or("pool should exist")?;
+		assert_eq!(pool.ticks.iter().filter(|info| info.tick > pool.tick).count(), 1);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_concentrated_limit_give() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_concentrated_pool_with_ticks::<T>(&provider, assets[0], assets[1])?;
+		let take = AssetAmount::<T>::new(assets[0], balance::<T>(CONCENTRATED_AMOUNT));
+
+		// worst case crosses every tick of the pool
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller.clone()),
+			take,
+			balance::<T>(INITIAL_BALANCE),
+			pair.clone(),
+			FEE_TIER,
+			None,
+		);
+
+		// all but the upper tick of the last position
+		let pool = ConcentratedPools::<T>::get(pair, FEE_TIER).ok_or("pool should exist")?;
+		assert_eq!(pool.ticks.iter().filter(|info| info.tick > pool.tick).count(), 1);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn place_order() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		place_orders::<T>(T::MaxOrdersPerBook::get() - 1, assets[0], assets[1])?;
+		let offer = AssetAmount::<T>::new(assets[0], order_amount::<T>());
+		let want = AssetAmount::<T>::new(assets[1], order_amount::<T>());
+
+		// worst case compares the order with every other order of the book
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), offer, want, None);
+
+		assert_eq!(OrderBooks::<T>::get(pair, assets[0]).len() as u32, T::MaxOrdersPerBook::get());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn cancel_order() -> Result<(), BenchmarkError> {
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		place_orders::<T>(T::MaxOrdersPerBook::get(), assets[0], assets[1])?;
+		let maker: T::AccountId = account("maker", 0, SEED);
+		let order_id = OrderBooks::<T>::get(&pair, assets[0])[0].id;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(maker), order_id, None);
+
+		assert!(!OrderBookOf::<T>::contains_key(order_id));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn fill_order() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		place_orders::<T>(T::MaxOrdersPerBook::get(), assets[0], assets[1])?;
+		let order_id = OrderBooks::<T>::get(&pair, assets[0])[0].id;
+
+		// worst case fills all of the order, which is removed from the book
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), order_id, order_amount::<T>(), None);
+
+		assert!(!OrderBookOf::<T>::contains_key(order_id));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn flash_swap() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		let take = AssetAmountPair::<T>::new(pair.clone(), Zero::zero(), balance::<T>(AMOUNT));
+		// more than the pool's price of what is taken, flash fee included
+		let repay = AssetAmountPair::<T>::new(pair, balance::<T>(2 * AMOUNT), Zero::zero());
+		let call: <T as Config>::RuntimeCall =
+			frame_system::Call::<T>::remark { remark: Vec::new() }.into();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), take, repay, FEE_TIER, Box::new(call), None);
+
+		assert_eq!(
+			Dex::<T>::get_reserves(assets[0], assets[1], FEE_TIER),
+			Some((balance::<T>(RESERVE + 2 * AMOUNT), balance::<T>(RESERVE - AMOUNT)))
+		);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_flash_fee() -> Result<(), BenchmarkError> {
+		let flash_fee = Perbill::from_percent(1);
+		let origin = admin_origin::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, flash_fee);
+
+		assert_eq!(DexParameters::<T>::get().flash_fee, flash_fee);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn zap_in() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let provider: T::AccountId = account("provider", 0, SEED);
+		let assets = create_assets::<T>(2, &[&provider, &caller])?;
+		let pair = create_pool_with_liquidity::<T>(&provider, assets[0], assets[1])?;
+		let give = AssetAmount::<T>::new(assets[0], balance::<T>(AMOUNT));
+
+		// worst case searches the share to swap by bisection over a StableSwap pool
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), pair, FEE_TIER, give, Zero::zero(), None);
+
+		let lp_token_id =
+			Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).ok_or("pool should exist")?;
+		assert!(!T::Fungibles::balance(lp_token_id, &caller).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn zap_out() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let assets = create_assets::<T>(2, &[&caller])?;
+		let pair = create_pool_with_liquidity::<T>(&caller, assets[0], assets[1])?;
+		let lp_token_id =
+			Dex::<T>::lp_token_of(assets[0], assets[1], FEE_TIER).ok_or("pool should exist")?;
+		let lp_tokens = T::Fungibles::balance(lp_token_id, &caller);
+		let half = lp_tokens / balance::<T>(2);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), pair, FEE_TIER, half, assets[0], Zero::zero(), None);
+
+		assert_eq!(T::Fungibles::balance(lp_token_id, &caller), lp_tokens - half);
+		assert_eq!(
+			T::Fungibles::balance(assets[1], &caller),
+			balance::<T>(INITIAL_BALANCE - RESERVE)
+		);
+		Ok(())
+	}
+
+	impl_benchmark_test_suite!(Dex, crate::mock::new_test_ext(vec![]), crate::mock::Test);
+}
@@ -0,0 +1,284 @@
+//! Pricing curves of the pools of a pair of assets.
+//!
+//! Every pool stores the [`Curve`] it prices swaps by, which the runtime's `Curves` resolves to a
+//! [`PricingCurve`]: one of this module for the built-in curves, unless the runtime overrides
+//! them, and one of the runtime's own for [`Curve::Custom`]. A new curve is added by implementing
+//! the trait, resolving an id of [`Curve::Custom`] to it, and creating pools with that id.
+
+use sp_runtime::traits::{AtLeast32BitUnsigned, One};
+use sp_runtime::{ArithmeticError, FixedU128, Perbill};
+
+use crate::math::{self, Rounding};
+use crate::Curve;
+
+/// Resolves the [`Curve`] of a pool to the [`PricingCurve`] pricing it. Only
+/// [`CurveResolver::with_custom_curve`] has to be implemented, the built-in curves resolve to
+/// those of this module by default. `()` resolves the built-in curves only.
+pub trait CurveResolver<Balance: AtLeast32BitUnsigned + Copy> {
+	/// Calls `f` with the pricing curve of [`Curve::Custom`] `id`, or returns `None` if the
+	/// runtime has no curve of that id.
+	fn with_custom_curve<R>(id: u32, f: impl FnOnce(&dyn PricingCurve<Balance>) -> R) -> Option<R>;
+
+	/// Calls `f` with the pricing curve of `curve` at unix time `now`, in seconds, or returns
+	/// `None` if it can't be resolved.
+	fn with_curve<R>(
+		curve: Curve,
+		now: u64,
+		f: impl FnOnce(&dyn PricingCurve<Balance>) -> R,
+	) -> Option<R> {
+		match curve {
+			Curve::ConstantProduct => Some(f(&ConstantProduct)),
+			Curve::StableSwap(amplification) =>
+				Some(f(&StableSwap { amplification: amplification.at(now) })),
+			Curve::ConstantSum => Some(f(&ConstantSum)),
+			Curve::Custom(id) => Self::with_custom_curve(id, f),
+		}
+	}
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> CurveResolver<Balance> for () {
+	fn with_custom_curve<R>(
+		_id: u32,
+		_f: impl FnOnce(&dyn PricingCurve<Balance>) -> R,
+	) -> Option<R> {
+		None
+	}
+}
+
+/// How a pool of a pair of assets prices swaps, and the LP tokens minted for and burnt from its
+/// liquidity, against its reserves.
+///
+/// Every result is rounded in the direction that favours the pool: amounts paid out of it and LP
+/// tokens minted for it are rounded down, amounts paid into it and fees are rounded up.
+pub trait PricingCurve<Balance: AtLeast32BitUnsigned + Copy> {
+	/// Returns the amount taken from a pool with reserves `reserve_in` of the given asset and
+	/// `reserve_out` of the taken one for giving `amount_in` to it, of which `fee` is kept by the
+	/// pool. The pallet rejects swaps taking all of `reserve_out` or more.
+	fn amount_out(
+		&self,
+		amount_in: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError>;
+
+	/// Returns the amount to give to a pool with reserves `reserve_in` of the given asset and
+	/// `reserve_out` of the taken one to take `amount_out` from it, of which `fee` is kept by the
+	/// pool.
+	///
+	/// `amount_out` must be less than `reserve_out`.
+	fn amount_in(
+		&self,
+		amount_out: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError>;
+
+	/// Returns the liquidity invariant of reserves `x` and `y`. It must grow in proportion to the
+	/// reserves when liquidity is provided or removed, and otherwise only with the fees of swaps,
+	/// as the first provision to a pool mints it and the protocol fee is measured by its growth.
+	fn invariant(&self, x: Balance, y: Balance) -> Result<Balance, ArithmeticError>;
+
+	/// Returns the price of the asset of `reserve` in the other asset of a pool with reserves
+	/// `reserve` and `other_reserve`, as `get_asset_price` reports it, or `None` to price it by
+	/// the ratio of the reserves, which the runtime's `AssetBalancePairToRatioConverter` converts.
+	///
+	/// By default, the ratio of the reserves, as for a constant product pool.
+	fn price(
+		&self,
+		_reserve: Balance,
+		_other_reserve: Balance,
+	) -> Result<Option<FixedU128>, ArithmeticError> {
+		Ok(None)
+	}
+
+	/// Returns the LP tokens to mint for adding `added`, amounts of the first and second asset
+	/// of a pool, to its `reserves` with `total_liquidity` LP tokens: the invariant of `added`
+	/// if the pool has no liquidity, and otherwise the lower share of a reserve added.
+	fn lp_tokens_to_mint(
+		&self,
+		added: (Balance, Balance),
+		reserves: (Balance, Balance),
+		total_liquidity: Balance,
+	) -> Result<Balance, ArithmeticError> {
+		if total_liquidity.is_zero() {
+			return self.invariant(added.0, added.1);
+		}
+		let lp_tokens_x = math::mul_div(added.0, total_liquidity, reserves.0, Rounding::Down)?;
+		let lp_tokens_y = math::mul_div(added.1, total_liquidity, reserves.1, Rounding::Down)?;
+		Ok(lp_tokens_x.min(lp_tokens_y))
+	}
+
+	/// Returns the amounts of the first and second asset of a pool paid out of its `reserves` for
+	/// burning `lp_tokens` of its `total_liquidity`: their share of each reserve.
+	fn amounts_for_lp_tokens(
+		&self,
+		lp_tokens: Balance,
+		reserves: (Balance, Balance),
+		total_liquidity: Balance,
+	) -> Result<(Balance, Balance), ArithmeticError> {
+		Ok((
+			math::mul_div(lp_tokens, reserves.0, total_liquidity, Rounding::Down)?,
+			math::mul_div(lp_tokens, reserves.1, total_liquidity, Rounding::Down)?,
+		))
+	}
+
+	/// Returns the share of `amount_in` to swap for the other asset of a pool with reserves
+	/// `reserve_in` of the given asset and `reserve_out` of the other, of which `fee` is kept by
+	/// the pool, for the rest of `amount_in` and the amount taken for it to be at the ratio of
+	/// the reserves after the swap, so that both can be provided as liquidity.
+	///
+	/// By default, the largest share whose rest is worth no less than the amount taken for it,
+	/// found by bisection over [`PricingCurve::amount_out`].
+	fn zap_swap_amount(
+		&self,
+		amount_in: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		let (mut low, mut high) = (Balance::zero(), amount_in);
+		while low < high {
+			// rounded up, so that `low` always moves when it is kept
+			let share = high - (high - low) / 2u32.into();
+			let amount_out = self.amount_out(share, reserve_in, reserve_out, fee)?;
+			let rest_covers_amount_out = match reserve_out.checked_sub(&amount_out) {
+				Some(reserve_out_left) if !reserve_out_left.is_zero() => {
+					math::product(amount_in - share, reserve_out_left)?
+						>= math::product(amount_out, reserve_in)?
+							.checked_add(math::product(amount_out, share)?)
+							.ok_or(ArithmeticError::Overflow)?
+				},
+				_ => false,
+			};
+			if rest_covers_amount_out {
+				low = share;
+			} else {
+				high = share - 1u32.into();
+			}
+		}
+		Ok(low)
+	}
+}
+
+/// Uniswap V2's `x * y = k`, for assets of unrelated prices. Its invariant is `sqrt(x * y)`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ConstantProduct;
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for ConstantProduct {
+	fn amount_out(
+		&self,
+		amount_in: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::get_amount_out(amount_in, reserve_in, reserve_out, fee)
+	}
+
+	fn amount_in(
+		&self,
+		amount_out: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::get_amount_in(amount_out, reserve_in, reserve_out, fee)
+	}
+
+	fn invariant(&self, x: Balance, y: Balance) -> Result<Balance, ArithmeticError> {
+		math::sqrt_of_product(x, y)
+	}
+
+	fn zap_swap_amount(
+		&self,
+		amount_in: Balance,
+		reserve_in: Balance,
+		_reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::zap_swap_amount(amount_in, reserve_in, fee)
+	}
+}
+
+/// `x + y = k`, for assets of the same price: swaps are one for one, less the fee, whatever the
+/// reserves, until the reserve taken from runs out.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ConstantSum;
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for ConstantSum {
+	fn amount_out(
+		&self,
+		amount_in: Balance,
+		_reserve_in: Balance,
+		_reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::constant_sum_amount_out(amount_in, fee)
+	}
+
+	fn amount_in(
+		&self,
+		amount_out: Balance,
+		_reserve_in: Balance,
+		_reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::constant_sum_amount_in(amount_out, fee)
+	}
+
+	fn invariant(&self, x: Balance, y: Balance) -> Result<Balance, ArithmeticError> {
+		x.checked_add(&y).ok_or(ArithmeticError::Overflow)
+	}
+
+	fn price(
+		&self,
+		_reserve: Balance,
+		_other_reserve: Balance,
+	) -> Result<Option<FixedU128>, ArithmeticError> {
+		Ok(Some(FixedU128::one()))
+	}
+}
+
+/// Curve's StableSwap invariant `D` at the amplification coefficient `amplification`, for
+/// assets pegged to each other.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StableSwap {
+	/// Amplification coefficient `A` of the pool.
+	pub amplification: u32,
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for StableSwap {
+	fn amount_out(
+		&self,
+		amount_in: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::stable_swap_amount_out(amount_in, reserve_in, reserve_out, fee, self.amplification)
+	}
+
+	fn amount_in(
+		&self,
+		amount_out: Balance,
+		reserve_in: Balance,
+		reserve_out: Balance,
+		fee: Perbill,
+	) -> Result<Balance, ArithmeticError> {
+		math::stable_swap_amount_in(amount_out, reserve_in, reserve_out, fee, self.amplification)
+	}
+
+	fn invariant(&self, x: Balance, y: Balance) -> Result<Balance, ArithmeticError> {
+		math::stable_swap_d(x, y, self.amplification)
+	}
+
+	fn price(
+		&self,
+		reserve: Balance,
+		other_reserve: Balance,
+	) -> Result<Option<FixedU128>, ArithmeticError> {
+		math::stable_swap_price(reserve, other_reserve, self.amplification).map(Some)
+	}
+}
@@ -0,0 +1,135 @@
+//! Signed extension rejecting DEX calls past their deadline, so that they never reach a block.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use codec::{Decode, Encode};
+use frame_support::traits::IsSubType;
+use frame_system::pallet_prelude::BlockNumberFor;
+use scale_info::TypeInfo;
+use sp_runtime::traits::{DispatchInfoOf, One, Saturating, SignedExtension, UniqueSaturatedInto};
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+};
+
+use crate::{Call, Config};
+
+impl<T: Config> Call<T> {
+	/// Returns the last block the call can be included in, if it was given one.
+	pub fn deadline(&self) -> Option<BlockNumberFor<T>> {
+		match self {
+			Call::create_pool { deadline, .. }
+			| Call::provide_liquidity { deadline, .. }
+			| Call::remove_liquidity { deadline, .. }
+			| Call::swap_limit_take { deadline, .. }
+			| Call::swap_limit_give { deadline, .. }
+			| Call::swap_exact_in_along_path { deadline, .. }
+			| Call::swap_exact_out_along_path { deadline, .. }
+			| Call::add_liquidity { deadline, .. }
+			| Call::create_stable_pool { deadline, .. }
+			| Call::create_weighted_pool { deadline, .. }
+			| Call::join_weighted_pool { deadline, .. }
+			| Call::join_weighted_pool_single { deadline, .. }
+			| Call::exit_weighted_pool { deadline, .. }
+			| Call::exit_weighted_pool_single { deadline, .. }
+			| Call::swap_weighted_limit_take { deadline, .. }
+			| Call::swap_weighted_limit_give { deadline, .. }
+			| Call::create_constant_sum_pool { deadline, .. }
+			| Call::create_concentrated_pool { deadline, .. }
+			| Call::open_position { deadline, .. }
+			| Call::decrease_position { deadline, .. }
+			| Call::collect_position_fees { deadline, .. }
+			| Call::transfer_position { deadline, .. }
+			| Call::swap_concentrated_limit_take { deadline, .. }
+			| Call::swap_concentrated_limit_give { deadline, .. }
+			| Call::place_order { deadline, .. }
+			| Call::cancel_order { deadline, .. }
+			| Call::fill_order { deadline, .. }
+			| Call::flash_swap { deadline, .. }
+			| Call::zap_in { deadline, .. }
+			| Call::zap_out { deadline, .. } => *deadline,
+			_ => None,
+		}
+	}
+}
+
+/// Rejects DEX calls whose deadline has passed as stale in transaction validation, and limits
+/// the longevity of the valid ones to their deadline.
+///
+/// The pallet checks the deadline again on dispatch, this only keeps expired calls out of the
+/// transaction pool and out of blocks. Calls nested in other calls, e.g. in a batch, are only
+/// checked on dispatch.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckDexDeadline<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckDexDeadline<T> {
+	/// Creates a new `CheckDexDeadline`.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckDexDeadline<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckDexDeadline<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckDexDeadline")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckDexDeadline<T>
+where
+	<T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckDexDeadline";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		let deadline = match call.is_sub_type().and_then(|call| call.deadline()) {
+			Some(deadline) => deadline,
+			None => return Ok(ValidTransaction::default()),
+		};
+
+		let now = frame_system::Pallet::<T>::block_number();
+		if now > deadline {
+			return Err(InvalidTransaction::Stale.into());
+		}
+
+		let longevity = deadline.saturating_sub(now).saturating_add(One::one());
+		Ok(ValidTransaction { longevity: longevity.unique_saturated_into(), ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
@@ -2,11 +2,13 @@
 
 extern crate core;
 
-use codec::{Decode, Encode, MaxEncodedLen};
+use codec::{Codec, Decode, Encode, MaxEncodedLen};
 use frame_support::traits::fungibles;
-use frame_support::{ensure, Blake2_128Concat, DebugNoBound, PalletId};
+use frame_support::{ensure, Blake2_128Concat, BoundedVec, DebugNoBound, PalletId};
 use scale_info::TypeInfo;
-use sp_runtime::traits::{CheckedDiv, CheckedMul, IntegerSquareRoot, Zero};
+use sp_runtime::traits::{CheckedAdd, CheckedSub, One, Zero};
+use sp_runtime::{FixedU128, Perbill};
+use sp_std::{boxed::Box, collections::btree_set::BTreeSet, vec, vec::Vec};
 
 /// Edit this file to define custom logic or remove it if it is not needed.
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
@@ -19,6 +21,29 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+mod adapter;
+pub use adapter::NativeOrAssets;
+
+mod curve;
+pub use curve::{ConstantProduct, ConstantSum, CurveResolver, PricingCurve, StableSwap};
+
+mod extension;
+pub use extension::CheckDexDeadline;
+
+mod math;
+use math::Rounding;
+
+pub mod migration;
+
+mod payment;
+pub use payment::ChargeDexTxPayment;
+
+pub mod weights;
+pub use weights::*;
+
 /// The hasher used by the pallet's storage
 pub type Hasher = Blake2_128Concat;
 
@@ -142,12 +167,35 @@ impl<T: Config> AssetAmountPair<T> {
 ///
 /// A liquidity pool consists of two assets and their respective amounts, total liquidity,
 /// and an identifier for the liquidity provider token.
+///
+/// Each pair of assets can have a pool at every allowed fee tier, `fee_tier` is the share of
+/// every amount swapped into the pool that it keeps as a fee.
+///
+/// `curve` is the invariant the pool prices swaps and liquidity by, see [`PricingCurve`].
+///
+/// While the protocol fee is on, `root_k_last` holds the liquidity invariant of the reserves as
+/// of the last liquidity event, from which the protocol's share of the swap fees accrued since is
+/// derived. It is the square root of the product of the reserves for constant product pools, `D`
+/// for StableSwap ones, and the sum of the reserves for constant sum ones. `amplification_last`
+/// is the amplification coefficient `D` was measured at, as ramping it moves `D` without a swap.
+///
+/// The price cumulatives hold the sum of the price of each asset, as returned by
+/// `get_asset_price`, weighted by the seconds it was in effect until `last_timestamp`. They wrap
+/// around on overflow, only differences between them are meaningful.
 #[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
 #[scale_info(skip_type_params(T))]
 pub struct LiquidityPool<T: Config> {
 	asset_amounts: AssetAmountPair<T>,
 	total_liquidity: AssetBalanceOf<T>,
 	lp_token_id: T::DexAssetId,
+	fee_tier: Perbill,
+	curve: Curve,
+	protocol_fee_on: bool,
+	root_k_last: AssetBalanceOf<T>,
+	amplification_last: u32,
+	price_x_cumulative: u128,
+	price_y_cumulative: u128,
+	last_timestamp: u64,
 }
 
 impl<T: Config> LiquidityPool<T> {
@@ -157,38 +205,504 @@ impl<T: Config> LiquidityPool<T> {
 	///
 	/// * `liquidity_id_pair` - Pair of asset identifiers for the pool.
 	/// * `lp_token_id` - Identifier for the liquidity provider token.
-	fn empty_from_pair(liquidity_id_pair: AssetIdPair<T>, lp_token_id: T::DexAssetId) -> Self {
+	/// * `fee_tier` - Fee tier of the pool.
+	/// * `curve` - Invariant the pool prices swaps by.
+	fn empty_from_pair(
+		liquidity_id_pair: AssetIdPair<T>,
+		lp_token_id: T::DexAssetId,
+		fee_tier: Perbill,
+		curve: Curve,
+	) -> Self {
 		Self {
 			asset_amounts: AssetAmountPair::empty(liquidity_id_pair),
 			total_liquidity: AssetBalanceOf::<T>::zero(),
 			lp_token_id,
+			fee_tier,
+			curve,
+			protocol_fee_on: false,
+			root_k_last: AssetBalanceOf::<T>::zero(),
+			amplification_last: 0,
+			price_x_cumulative: 0,
+			price_y_cumulative: 0,
+			last_timestamp: 0,
+		}
+	}
+}
+
+/// An asset of a weighted pool, with its weight and the pool's reserve of it.
+#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[scale_info(skip_type_params(T))]
+pub struct WeightedAsset<T: Config> {
+	asset_id: T::DexAssetId,
+	weight: Perbill,
+	reserve: AssetBalanceOf<T>,
+}
+
+/// A Balancer-style liquidity pool of 2 to `MaxWeightedPoolAssets` assets, each with a weight
+/// setting the share of the pool's value it holds. The weights add up to one, and swaps keep the
+/// product of the reserves, each to the power of its weight, from decreasing.
+///
+/// The assets are kept in increasing order of their ids, the order the amounts of joins and
+/// exits by all of them are given in. Unlike the pools of a pair of assets, weighted pools are
+/// identified by their LP token, and neither accrue the protocol fee nor keep price cumulatives.
+#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[scale_info(skip_type_params(T))]
+pub struct WeightedPool<T: Config> {
+	assets: BoundedVec<WeightedAsset<T>, T::MaxWeightedPoolAssets>,
+	total_liquidity: AssetBalanceOf<T>,
+	fee_tier: Perbill,
+}
+
+impl<T: Config> WeightedPool<T> {
+	/// Creates an empty weighted pool.
+	///
+	/// # Arguments
+	///
+	/// * `assets` - Assets of the pool with their weights, in increasing order of their ids.
+	/// * `fee_tier` - Fee tier of the pool.
+	fn empty(
+		assets: BoundedVec<(T::DexAssetId, Perbill), T::MaxWeightedPoolAssets>,
+		fee_tier: Perbill,
+	) -> Self {
+		let assets = assets
+			.into_iter()
+			.map(|(asset_id, weight)| WeightedAsset {
+				asset_id,
+				weight,
+				reserve: AssetBalanceOf::<T>::zero(),
+			})
+			.collect();
+		Self {
+			assets: BoundedVec::truncate_from(assets),
+			total_liquidity: AssetBalanceOf::<T>::zero(),
+			fee_tier,
+		}
+	}
+
+	/// Returns the index of `asset_id` among the assets of the pool.
+	///
+	/// # Errors
+	///
+	/// Returns `Error::<T>::AssetNotInPool` if the pool doesn't hold the asset.
+	fn index_of(&self, asset_id: T::DexAssetId) -> Result<usize, Error<T>> {
+		self.assets
+			.binary_search_by_key(&asset_id, |asset| asset.asset_id)
+			.map_err(|_| Error::<T>::AssetNotInPool)
+	}
+
+	/// Pairs each asset of the pool with its amount in `amounts`, given in the same order.
+	fn amounts_of(
+		&self,
+		amounts: &[AssetBalanceOf<T>],
+	) -> BoundedVec<AssetAmount<T>, T::MaxWeightedPoolAssets> {
+		BoundedVec::truncate_from(
+			self.assets
+				.iter()
+				.zip(amounts)
+				.map(|(asset, amount)| AssetAmount::new(asset.asset_id, *amount))
+				.collect(),
+		)
+	}
+}
+
+/// Identifier of a position in a concentrated liquidity pool.
+pub type PositionId = u64;
+
+/// A tick bounding the range of at least one position of a concentrated liquidity pool.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+pub struct TickInfo {
+	tick: i32,
+	/// Total liquidity of the positions bounded by the tick.
+	liquidity_gross: u128,
+	/// Liquidity added to the pool when its price crosses the tick upwards, and removed when it
+	/// crosses it downwards.
+	liquidity_net: i128,
+	/// Growth of the fees per unit of liquidity on the other side of the tick from the pool's
+	/// current tick, in Q64.64, as in Uniswap V3.
+	fee_growth_outside_x: u128,
+	fee_growth_outside_y: u128,
+}
+
+/// A Uniswap V3-style pool of a pair of assets, in which liquidity is provided over a range of
+/// ticks, and only traded against while the price is within it.
+///
+/// The price of the first asset of the pair in the second is kept as its square root in Q64.64,
+/// and the pool's liquidity is that of the positions whose range contains it. The ticks bounding
+/// positions are multiples of the pool's tick spacing, and are kept in the pool in increasing
+/// order, up to `MaxConcentratedTicks`, so that a swap only reads the pool. The fees of swaps are
+/// accrued per unit of liquidity, from which positions work out the fees earned within their
+/// range. Unlike the other pools, liquidity isn't tokenized but held in non-fungible positions.
+#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[scale_info(skip_type_params(T))]
+pub struct ConcentratedPool<T: Config> {
+	sqrt_price: u128,
+	tick: i32,
+	tick_spacing: u32,
+	liquidity: u128,
+	fee_growth_global_x: u128,
+	fee_growth_global_y: u128,
+	ticks: BoundedVec<TickInfo, T::MaxConcentratedTicks>,
+}
+
+impl<T: Config> ConcentratedPool<T> {
+	/// Creates a concentrated liquidity pool without positions.
+	///
+	/// # Arguments
+	///
+	/// * `sqrt_price` - Square root of the initial price of the pool, in Q64.64.
+	/// * `tick` - Tick at `sqrt_price`.
+	/// * `tick_spacing` - Spacing of the ticks positions can be bounded by.
+	fn new(sqrt_price: u128, tick: i32, tick_spacing: u32) -> Self {
+		Self {
+			sqrt_price,
+			tick,
+			tick_spacing,
+			liquidity: 0,
+			fee_growth_global_x: 0,
+			fee_growth_global_y: 0,
+			ticks: BoundedVec::new(),
+		}
+	}
+
+	/// Returns the index of `tick` among the ticks of the pool, or where it would be inserted.
+	fn tick_index(&self, tick: i32) -> Result<usize, usize> {
+		self.ticks.binary_search_by_key(&tick, |info| info.tick)
+	}
+
+	/// Returns the tick of the pool `tick`, with nothing bounded by it if it bounds no position.
+	fn tick_info(&self, tick: i32) -> TickInfo {
+		self.tick_index(tick)
+			.map(|index| self.ticks[index])
+			.unwrap_or(TickInfo { tick, ..Default::default() })
+	}
+
+	/// Adds `liquidity_delta` to the liquidity bounded by `tick`, the upper bound of the range of
+	/// a position if `upper` and its lower bound otherwise. A tick first bounding a position
+	/// starts with all of the fees grown so far outside of it if it is at or below the current
+	/// tick, as in Uniswap V3. Ticks left bounding nothing are only dropped by
+	/// [`Self::clear_ticks`].
+	///
+	/// # Errors
+	///
+	/// Returns `Error::<T>::TooManyTicks` if the pool already has `MaxConcentratedTicks` ticks, or
+	/// `Error::<T>::Arithmetic` if the liquidity of the tick overflows.
+	fn update_tick(
+		&mut self,
+		tick: i32,
+		liquidity_delta: i128,
+		upper: bool,
+	) -> Result<(), Error<T>> {
+		let index = match self.tick_index(tick) {
+			Ok(index) => index,
+			Err(index) => {
+				let (fee_growth_outside_x, fee_growth_outside_y) = if tick <= self.tick {
+					(self.fee_growth_global_x, self.fee_growth_global_y)
+				} else {
+					(0, 0)
+				};
+				let info = TickInfo {
+					tick,
+					fee_growth_outside_x,
+					fee_growth_outside_y,
+					..Default::default()
+				};
+				self.ticks.try_insert(index, info).map_err(|_| Error::<T>::TooManyTicks)?;
+				index
+			},
+		};
+		let info = &mut self.ticks[index];
+		info.liquidity_gross = info
+			.liquidity_gross
+			.checked_add_signed(liquidity_delta)
+			.ok_or(Error::<T>::Arithmetic)?;
+		info.liquidity_net = if upper {
+			info.liquidity_net.checked_sub(liquidity_delta)
+		} else {
+			info.liquidity_net.checked_add(liquidity_delta)
+		}
+		.ok_or(Error::<T>::Arithmetic)?;
+		Ok(())
+	}
+
+	/// Drops the ticks of the pool that no longer bound any position.
+	fn clear_ticks(&mut self) {
+		self.ticks.retain(|info| info.liquidity_gross != 0);
+	}
+
+	/// Returns the growth of the fees per unit of liquidity of each asset between `tick_lower`
+	/// and `tick_upper`, in Q64.64, wrapping around as in Uniswap V3.
+	fn fee_growth_inside(&self, tick_lower: i32, tick_upper: i32) -> (u128, u128) {
+		let (global_x, global_y) = (self.fee_growth_global_x, self.fee_growth_global_y);
+		let (lower, upper) = (self.tick_info(tick_lower), self.tick_info(tick_upper));
+		let (below_x, below_y) = if self.tick >= tick_lower {
+			(lower.fee_growth_outside_x, lower.fee_growth_outside_y)
+		} else {
+			(
+				global_x.wrapping_sub(lower.fee_growth_outside_x),
+				global_y.wrapping_sub(lower.fee_growth_outside_y),
+			)
+		};
+		let (above_x, above_y) = if self.tick < tick_upper {
+			(upper.fee_growth_outside_x, upper.fee_growth_outside_y)
+		} else {
+			(
+				global_x.wrapping_sub(upper.fee_growth_outside_x),
+				global_y.wrapping_sub(upper.fee_growth_outside_y),
+			)
+		};
+		(
+			global_x.wrapping_sub(below_x).wrapping_sub(above_x),
+			global_y.wrapping_sub(below_y).wrapping_sub(above_y),
+		)
+	}
+}
+
+/// A position of liquidity in a concentrated liquidity pool over the range of ticks from
+/// `tick_lower` to `tick_upper`, owned by an account that can transfer it to another.
+///
+/// The fees earned by the position are accrued to the amounts owed to it whenever its liquidity
+/// changes, from the growth of the fees within its range since it last did.
+#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[scale_info(skip_type_params(T))]
+pub struct Position<T: Config> {
+	owner: T::AccountId,
+	pool_id: AssetIdPair<T>,
+	fee_tier: Perbill,
+	tick_lower: i32,
+	tick_upper: i32,
+	liquidity: u128,
+	fee_growth_inside_last_x: u128,
+	fee_growth_inside_last_y: u128,
+	tokens_owed_x: AssetBalanceOf<T>,
+	tokens_owed_y: AssetBalanceOf<T>,
+}
+
+/// Identifier of a limit order.
+pub type OrderId = u64;
+
+/// A resting limit order of an account, offering `offer` of an asset of a pair with a pool for
+/// `want` of the other, which takers fill in part or in full at that price.
+///
+/// What is left of the offered asset is held in escrow in the DEX account until the order is
+/// filled or cancelled.
+#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[scale_info(skip_type_params(T))]
+pub struct Order<T: Config> {
+	id: OrderId,
+	owner: T::AccountId,
+	offer: AssetBalanceOf<T>,
+	want: AssetBalanceOf<T>,
+	/// Amount of the offered asset left to fill.
+	remaining: AssetBalanceOf<T>,
+}
+
+impl<T: Config> Order<T> {
+	/// Returns the amount of the wanted asset to pay for `amount` of the offered one, rounded up.
+	fn cost_of(&self, amount: AssetBalanceOf<T>) -> Result<AssetBalanceOf<T>, Error<T>> {
+		math::mul_div(amount, self.want, self.offer, Rounding::Up).map_err(Error::<T>::from)
+	}
+
+	/// Returns the amount of the offered asset that `cost` of the wanted one pays for, rounded
+	/// down.
+	fn amount_for(&self, cost: AssetBalanceOf<T>) -> Result<AssetBalanceOf<T>, Error<T>> {
+		math::mul_div(cost, self.offer, self.want, Rounding::Down).map_err(Error::<T>::from)
+	}
+
+	/// Returns whether the order asks less of the wanted asset per unit offered than `other`.
+	fn is_cheaper_than(&self, other: &Self) -> Result<bool, Error<T>> {
+		Ok(math::product(self.want, other.offer)? < math::product(other.want, self.offer)?)
+	}
+}
+
+/// Borrows the output of flash swaps for other pallets, see [`Pallet::flash_swap_with`].
+pub trait FlashBorrower<T: Config> {
+	/// Called once `taken` is transferred to `who`, before what `who` repays is taken from them.
+	fn on_flash_swap(who: &T::AccountId, taken: &AssetAmountPair<T>) -> sp_runtime::DispatchResult;
+}
+
+/// A read-only snapshot of a liquidity pool, as exposed through the [`DexApi`] runtime API.
+///
+/// Unlike [`LiquidityPool`], this type is not tied to the pallet's `Config`, so it can be
+/// decoded and serialized by clients that only know the concrete asset and balance types.
+#[derive(Clone, PartialEq, Eq, Debug, TypeInfo, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolInfo<AssetId, Balance> {
+	/// Identifier of the first asset of the pair.
+	pub asset_x_id: AssetId,
+	/// Identifier of the second asset of the pair.
+	pub asset_y_id: AssetId,
+	/// Reserve of the first asset held by the pool.
+	pub reserve_x: Balance,
+	/// Reserve of the second asset held by the pool.
+	pub reserve_y: Balance,
+	/// Total amount of LP tokens issued for the pool.
+	pub total_liquidity: Balance,
+	/// Identifier of the pool's LP token.
+	pub lp_token_id: AssetId,
+	/// Share of every amount swapped into the pool kept as a fee.
+	pub fee_tier: Perbill,
+	/// Invariant the pool prices swaps by.
+	pub curve: Curve,
+}
+
+impl<T: Config> From<LiquidityPool<T>> for PoolInfo<T::DexAssetId, AssetBalanceOf<T>> {
+	fn from(pool: LiquidityPool<T>) -> Self {
+		Self {
+			asset_x_id: pool.asset_amounts.amount_x.asset_id,
+			asset_y_id: pool.asset_amounts.amount_y.asset_id,
+			reserve_x: pool.asset_amounts.amount_x.balance,
+			reserve_y: pool.asset_amounts.amount_y.balance,
+			total_liquidity: pool.total_liquidity,
+			lp_token_id: pool.lp_token_id,
+			fee_tier: pool.fee_tier,
+			curve: pool.curve,
+		}
+	}
+}
+
+/// A snapshot of the price cumulatives of a pool at a point in time, from which time-weighted
+/// average prices are derived.
+#[derive(Clone, PartialEq, Eq, Debug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+pub struct Observation {
+	/// Unix time of the observation, in seconds.
+	pub timestamp: u64,
+	/// Price cumulative of the first asset of the pair at `timestamp`.
+	pub price_x_cumulative: u128,
+	/// Price cumulative of the second asset of the pair at `timestamp`.
+	pub price_y_cumulative: u128,
+}
+
+/// The invariant a pool prices swaps by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Curve {
+	/// Uniswap V2's `x * y = k`, for assets of unrelated prices.
+	ConstantProduct,
+	/// Curve's StableSwap invariant, for assets pegged to each other. Near the peg it prices
+	/// swaps closer to one to one than the constant product does, the closer the higher its
+	/// amplification coefficient.
+	StableSwap(Amplification),
+	/// `x + y = k`, for assets of the same price. Swaps are one for one, less the fee, until the
+	/// reserve taken from runs out.
+	ConstantSum,
+	/// A curve of the runtime's, by the id its `Curves` resolves it with.
+	Custom(u32),
+}
+
+/// The amplification coefficient `A` of a StableSwap pool, ramping linearly from `initial` at
+/// `initial_time` to `future` at `future_time`, both in unix seconds, and constant after.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct Amplification {
+	/// Coefficient at the start of the ramp.
+	pub initial: u32,
+	/// Coefficient at the end of the ramp.
+	pub future: u32,
+	/// Start of the ramp.
+	pub initial_time: u64,
+	/// End of the ramp.
+	pub future_time: u64,
+}
+
+impl Amplification {
+	/// Creates an amplification coefficient that doesn't ramp.
+	pub fn constant(value: u32) -> Self {
+		Self { initial: value, future: value, initial_time: 0, future_time: 0 }
+	}
+
+	/// Returns the coefficient at unix time `now`, in seconds.
+	pub fn at(&self, now: u64) -> u32 {
+		if now >= self.future_time {
+			return self.future;
 		}
+		let elapsed = u128::from(now.saturating_sub(self.initial_time));
+		let duration = u128::from(self.future_time.saturating_sub(self.initial_time));
+		let (initial, future) = (u128::from(self.initial), u128::from(self.future));
+		// `elapsed` is less than `duration`, so the coefficient stays between the two ends
+		let value = if future > initial {
+			initial + (future - initial) * elapsed / duration
+		} else {
+			initial - (initial - future) * elapsed / duration
+		};
+		value as u32
 	}
 }
 
+/// Parameters of the DEX that `AdminOrigin` can change without a runtime upgrade. Until first
+/// set, they default to the runtime's `ProtocolFeeShare`, `MinimumLiquidity`, `LpTokenDust` and
+/// `FlashFee`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+pub struct Parameters<Balance> {
+	/// Share of the swap fees minted as LP tokens to the protocol fee recipient, for pools with
+	/// the protocol fee turned on.
+	pub protocol_share: Perbill,
+	/// Amount of LP tokens locked forever on the first provision to a pool, zero or at least
+	/// `lp_token_dust`.
+	pub minimum_liquidity: Balance,
+	/// Minimum balance of the LP tokens of pools created from now on, positive.
+	pub lp_token_dust: Balance,
+	/// Share of what is repaid to a pool after a flash swap that the pool keeps as a fee, below
+	/// 100%.
+	pub flash_fee: Perbill,
+}
+
 const PALLET_ID: PalletId = PalletId(*b"__Dex__!");
 
+/// The most a single ramp can multiply or divide the amplification coefficient of a StableSwap
+/// pool by, as in Curve's contracts.
+const MAX_AMPLIFICATION_CHANGE: u32 = 10;
+
+/// The lowest weight of an asset in a weighted pool, as in Balancer's, which keeps the exponents
+/// of its pricing between 1/99 and 99.
+const MIN_WEIGHT: Perbill = Perbill::from_percent(1);
+
+/// The largest share of a weighted pool's reserve of an asset that a swap, or a join or exit by
+/// that asset alone, can add or take, as in Balancer's, so that a single call can't move the
+/// price of the pool by orders of magnitude.
+const MAX_WEIGHTED_RATIO: Perbill = Perbill::from_percent(30);
+
+/// The largest tick spacing of a concentrated liquidity pool, as in Uniswap V3.
+const MAX_TICK_SPACING: u32 = 16_384;
+
+/// Helper for benchmarks to create asset ids, as `DexAssetId` can't be constructed generically.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<AssetId> {
+	/// Returns the asset id for a given integer id.
+	fn asset_id(id: u32) -> AssetId;
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl<AssetId: From<u32>> BenchmarkHelper<AssetId> for () {
+	fn asset_id(id: u32) -> AssetId {
+		id.into()
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use core::fmt::Debug;
 
 	use codec::EncodeLike;
+	use frame_support::dispatch::{GetDispatchInfo, PostDispatchInfo};
 	use frame_support::traits::fungibles::{Create, Inspect, Mutate};
 	use frame_support::traits::tokens::Fortitude::Force;
 	use frame_support::traits::tokens::{Precision, Preservation};
+	use frame_support::traits::UnixTime;
 	use frame_support::{
 		pallet_prelude::*,
 		traits::fungible::{self},
 	};
 	use frame_system::pallet_prelude::*;
-	use sp_runtime::traits::{
-		AccountIdConversion, Convert, EnsureAdd, EnsureDiv, EnsureMul, EnsureSub,
-	};
-	use sp_runtime::{ArithmeticError, FixedU128, Perbill, Saturating};
+	use sp_runtime::traits::{AccountIdConversion, Convert, Dispatchable, EnsureAdd, EnsureSub};
+	use sp_runtime::{ArithmeticError, FixedPointNumber, FixedU128, PerThing, Perbill, Saturating};
 
 	use crate::*;
 
+	/// The current storage version, 1 since pools are keyed by their fee tier.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -197,20 +711,24 @@ pub mod pallet {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-		/// The Native Balance Type
-		type NativeBalance: fungible::Inspect<Self::AccountId>
-			+ fungible::Mutate<Self::AccountId>
-			+ fungible::hold::Mutate<Self::AccountId>
-			+ fungible::hold::Inspect<Self::AccountId>
-			+ fungible::freeze::Inspect<Self::AccountId>
-			+ fungible::freeze::Mutate<Self::AccountId>;
+		/// The overarching call type, of the calls dispatched by flash swaps
+		type RuntimeCall: Parameter
+			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+			+ GetDispatchInfo
+			+ From<frame_system::Call<Self>>;
+
+		/// The Native Balance Type, pooled like any other asset under `NativeAssetId`
+		type NativeBalance: fungible::Inspect<Self::AccountId, Balance = AssetBalanceOf<Self>>
+			+ fungible::Mutate<Self::AccountId>;
 
 		/// The Assets Balance Type
 		type Fungibles: Inspect<Self::AccountId, AssetId = Self::DexAssetId>
 			+ Mutate<Self::AccountId>
 			+ Create<Self::AccountId>;
 
-		/// Type to use for asset IDs, needs to implement `Ord` to prevent duplicate asset liquidity pool ids
+		/// Type to use for asset IDs, needs to implement `Ord` to prevent duplicate asset liquidity pool ids,
+		/// `One` and `CheckedAdd` to allocate LP token ids, and `MaybeSerializeDeserialize` to list
+		/// pools in the genesis config
 		type DexAssetId: Ord
 			+ Clone
 			+ Copy
@@ -220,29 +738,294 @@ pub mod pallet {
 			+ EncodeLike
 			+ Decode
 			+ MaxEncodedLen
-			+ Debug;
+			+ Debug
+			+ One
+			+ CheckedAdd
+			+ MaybeSerializeDeserialize;
+
+		/// The asset id identifying the native currency in pools. Assets of `Fungibles` with this
+		/// id can't be pooled
+		type NativeAssetId: Get<Self::DexAssetId>;
 
-		/// The minimum balance for LP tokens
+		/// The first LP token id to allocate. LP token ids are allocated in increasing order from it,
+		/// skipping ids of assets that already exist
+		type LpTokenIdStart: Get<Self::DexAssetId>;
+
+		/// The origin allowed to change the parameters of the DEX and the fee tiers pools can be
+		/// created at, and to switch the protocol fee of pools
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The minimum balance for LP tokens, until changed by `AdminOrigin`
 		type LpTokenDust: Get<AssetBalanceOf<Self>>;
 
-		/// The swap fee percentage
-		type FeePct: Get<Perbill>;
+		/// The amount of LP tokens locked forever in the DEX account on the first provision to a
+		/// pool, so that the value of a single LP token can't be inflated by the first provider to
+		/// steal the provisions rounded down after it, until changed by `AdminOrigin`. Must be zero
+		/// or at least `LpTokenDust`
+		type MinimumLiquidity: Get<AssetBalanceOf<Self>>;
+
+		/// The share of what is repaid to a pool after a flash swap that the pool keeps as a fee,
+		/// until changed by `AdminOrigin`
+		type FlashFee: Get<Perbill>;
+
+		/// The maximum number of fee tiers pools can be created at, the fee tiers themselves are
+		/// set by governance
+		#[pallet::constant]
+		type MaxFeeTiers: Get<u32>;
+
+		/// The share of the swap fees minted as LP tokens to `ProtocolFeeRecipient`, for pools with
+		/// the protocol fee turned on, until changed by `AdminOrigin`
+		type ProtocolFeeShare: Get<Perbill>;
+
+		/// The highest share of the swap fees `AdminOrigin` can set as the protocol's
+		#[pallet::constant]
+		type MaxProtocolFeeShare: Get<Perbill>;
+
+		/// The account receiving the protocol's share of the swap fees
+		type ProtocolFeeRecipient: Get<Self::AccountId>;
 
 		/// Type to convert two asset balances to a ratio
 		type AssetBalancePairToRatioConverter: Convert<
 			(AssetBalanceOf<Self>, AssetBalanceOf<Self>),
 			FixedU128,
 		>;
+
+		/// The maximum number of assets in a swap path, i.e. one more than the maximum number of
+		/// pools a single swap can go through
+		type MaxPathLength: Get<u32>;
+
+		/// The source of time for the price cumulatives
+		type Time: UnixTime;
+
+		/// The maximum number of price observations kept per pool, the oldest are dropped first
+		type MaxObservations: Get<u32>;
+
+		/// The highest amplification coefficient of StableSwap pools
+		#[pallet::constant]
+		type MaxAmplification: Get<u32>;
+
+		/// The shortest time, in seconds, `AdminOrigin` can ramp the amplification coefficient of
+		/// a StableSwap pool over
+		#[pallet::constant]
+		type MinAmplificationRampDuration: Get<u64>;
+
+		/// Resolves the curve of every pool to the pricing curve it prices by, `()` for the
+		/// built-in curves only
+		type Curves: CurveResolver<AssetBalanceOf<Self>>;
+
+		/// The most assets a weighted pool can hold, at least 2
+		#[pallet::constant]
+		type MaxWeightedPoolAssets: Get<u32>;
+
+		/// The most ticks bounding positions a concentrated liquidity pool can have, which bounds
+		/// the ticks a swap can cross
+		#[pallet::constant]
+		type MaxConcentratedTicks: Get<u32>;
+
+		/// The most resting limit orders offering an asset of a pair, which bounds the orders a
+		/// swap can fill
+		#[pallet::constant]
+		type MaxOrdersPerBook: Get<u32>;
+
+		/// The least a limit order can offer, in the smallest unit of the asset offered, so that
+		/// filling a book with orders that are never filled isn't free
+		#[pallet::constant]
+		type MinOrderAmount: Get<AssetBalanceOf<Self>>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+
+		/// Helper for creating asset ids in benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper<Self::DexAssetId>;
 	}
 
 	#[pallet::storage]
 	pub type Assets<T: Config> = StorageMap<_, Hasher, T::DexAssetId, AssetBalanceOf<T>>;
 
+	/// Price observations of each pool, by its pair of assets and fee tier, newest first.
+	#[pallet::storage]
+	pub type Observations<T: Config> = StorageDoubleMap<
+		_,
+		Hasher,
+		AssetIdPair<T>,
+		Hasher,
+		Perbill,
+		BoundedVec<Observation, T::MaxObservations>,
+		ValueQuery,
+	>;
+
+	/// Pools by their pair of assets and fee tier.
 	#[pallet::storage]
 	pub type Pools<T>
 	where
 		T: Config + TypeInfo,
-	= StorageMap<_, Hasher, AssetIdPair<T>, LiquidityPool<T>>;
+	= StorageDoubleMap<_, Hasher, AssetIdPair<T>, Hasher, Perbill, LiquidityPool<T>>;
+
+	/// Weighted pools by their LP token id.
+	#[pallet::storage]
+	pub type WeightedPools<T: Config> = StorageMap<_, Hasher, T::DexAssetId, WeightedPool<T>>;
+
+	/// Concentrated liquidity pools by their pair of assets and fee tier.
+	#[pallet::storage]
+	pub type ConcentratedPools<T: Config> =
+		StorageDoubleMap<_, Hasher, AssetIdPair<T>, Hasher, Perbill, ConcentratedPool<T>>;
+
+	/// Positions in concentrated liquidity pools by their id.
+	#[pallet::storage]
+	pub type Positions<T: Config> = StorageMap<_, Hasher, PositionId, Position<T>>;
+
+	/// The id of the next position to open.
+	#[pallet::storage]
+	pub type NextPositionId<T: Config> = StorageValue<_, PositionId, ValueQuery>;
+
+	/// Resting limit orders by their pair of assets and the asset they offer, cheapest first and,
+	/// at the same price, oldest first.
+	#[pallet::storage]
+	pub type OrderBooks<T: Config> = StorageDoubleMap<
+		_,
+		Hasher,
+		AssetIdPair<T>,
+		Hasher,
+		T::DexAssetId,
+		BoundedVec<Order<T>, T::MaxOrdersPerBook>,
+		ValueQuery,
+	>;
+
+	/// The pair of assets and offered asset of the book of each resting limit order, by its id.
+	#[pallet::storage]
+	pub type OrderBookOf<T: Config> =
+		StorageMap<_, Hasher, OrderId, (AssetIdPair<T>, T::DexAssetId)>;
+
+	/// The id of the next limit order to place.
+	#[pallet::storage]
+	pub type NextOrderId<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+	/// The fee tiers new pools can be created at, in increasing order. Swaps along a path choose
+	/// among the pools of each pair of assets at these fee tiers.
+	#[pallet::storage]
+	pub type FeeTiers<T: Config> = StorageValue<_, BoundedVec<Perbill, T::MaxFeeTiers>, ValueQuery>;
+
+	/// The pair of assets and fee tier of the pool each LP token was allocated for, other than
+	/// those of weighted pools, which are identified by their LP token.
+	#[pallet::storage]
+	pub type LpTokenToPair<T: Config> =
+		StorageMap<_, Hasher, T::DexAssetId, (AssetIdPair<T>, Perbill)>;
+
+	/// The runtime's configuration of the parameters of the DEX, until they are first set.
+	#[pallet::type_value]
+	pub fn DefaultParameters<T: Config>() -> Parameters<AssetBalanceOf<T>> {
+		Parameters {
+			protocol_share: T::ProtocolFeeShare::get(),
+			minimum_liquidity: T::MinimumLiquidity::get(),
+			lp_token_dust: T::LpTokenDust::get(),
+			flash_fee: T::FlashFee::get(),
+		}
+	}
+
+	/// The parameters of the DEX, changed by `AdminOrigin`.
+	#[pallet::storage]
+	pub type DexParameters<T: Config> =
+		StorageValue<_, Parameters<AssetBalanceOf<T>>, ValueQuery, DefaultParameters<T>>;
+
+	/// The next LP token id to try to allocate, `LpTokenIdStart` if none was allocated yet.
+	#[pallet::storage]
+	pub type NextLpTokenId<T: Config> = StorageValue<_, T::DexAssetId>;
+
+	/// The fee tiers to allow at genesis, and the pools to create, each given as its two assets,
+	/// its fee tier, its LP token id, the initial reserves of each of its assets, in the same
+	/// order, and the account to mint the LP tokens for the initial reserves to.
+	///
+	/// The reserves are minted to the DEX account, which must already exist to hold them, e.g. by
+	/// being endowed in the balances genesis.
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		pub fee_tiers: Vec<Perbill>,
+		pub pools: Vec<(
+			T::DexAssetId,
+			T::DexAssetId,
+			Perbill,
+			T::DexAssetId,
+			AssetBalanceOf<T>,
+			AssetBalanceOf<T>,
+			T::AccountId,
+		)>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			for fee_tier in &self.fee_tiers {
+				Pallet::<T>::set_fee_tier_allowed(*fee_tier, true)
+					.expect("genesis fee tiers must be below 100% and at most `MaxFeeTiers`");
+			}
+
+			for (asset_a, asset_b, fee_tier, lp_token_id, reserve_a, reserve_b, lp_recipient) in
+				&self.pools
+			{
+				let pair = AssetIdPair::<T>::new(*asset_a, *asset_b)
+					.expect("genesis pools must be of two different assets");
+				assert!(
+					FeeTiers::<T>::get().contains(fee_tier),
+					"fee tiers of genesis pools must be allowed"
+				);
+				assert!(!Pools::<T>::contains_key(&pair, fee_tier), "genesis pools must be unique");
+				assert!(
+					NativeOrAssets::<T>::asset_exists(*asset_a)
+						&& NativeOrAssets::<T>::asset_exists(*asset_b),
+					"assets of genesis pools must exist"
+				);
+				assert!(
+					!NativeOrAssets::<T>::asset_exists(*lp_token_id),
+					"LP token ids of genesis pools must not be in use"
+				);
+
+				let (reserve_x, reserve_y) = if pair.asset_x_id == *asset_a {
+					(*reserve_a, *reserve_b)
+				} else {
+					(*reserve_b, *reserve_a)
+				};
+				let reserves = AssetAmountPair::new(pair.clone(), reserve_x, reserve_y);
+
+				let mut pool = Pallet::<T>::new_empty_pool(
+					pair.clone(),
+					lp_token_id,
+					*fee_tier,
+					Curve::ConstantProduct,
+				)
+				.expect("LP token of a genesis pool can be created");
+				let lp_tokens = Pallet::<T>::calculate_tokens_to_mint(&reserves, &pool)
+					.expect("reserves of genesis pools must be above the minimum liquidity");
+				Pallet::<T>::lock_minimum_liquidity(*lp_token_id, &mut pool.total_liquidity)
+					.expect("minimum liquidity of genesis pools can be locked");
+				for reserve in [&reserves.amount_x, &reserves.amount_y] {
+					NativeOrAssets::<T>::mint_into(
+						reserve.asset_id,
+						&Pallet::<T>::dex_account_id(),
+						reserve.balance,
+					)
+					.expect("reserves of genesis pools can be minted to the DEX account");
+				}
+				T::Fungibles::mint_into(*lp_token_id, lp_recipient, lp_tokens)
+					.expect("LP tokens of genesis pools can be minted to their recipient");
+
+				pool.asset_amounts = reserves;
+				pool.total_liquidity += lp_tokens;
+				Pools::<T>::insert(&pair, fee_tier, pool);
+				LpTokenToPair::<T>::insert(lp_token_id, (pair, *fee_tier));
+
+				let next_lp_token_id =
+					NextLpTokenId::<T>::get().unwrap_or_else(T::LpTokenIdStart::get);
+				if *lp_token_id >= next_lp_token_id {
+					NextLpTokenId::<T>::put(
+						Pallet::<T>::increment_lp_token_id(lp_token_id)
+							.expect("LP token ids of genesis pools must not be the last one"),
+					);
+				}
+			}
+		}
+	}
 
 	// todo remove the comment below
 
@@ -252,108 +1035,400 @@ pub mod pallet {
 	#[pallet::generate_deposit(pub (super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// Liquidity token created for a new pool
-		LpTokenCreated { lp_token_id: T::DexAssetId },
+		LpTokenCreated { lp_token_id: T::DexAssetId, fee_tier: Perbill },
 
 		/// Liquidity tokens issues to account.
 		LiquidityProvided {
 			who: T::AccountId,
 			provided: AssetAmountPair<T>,
 			lp_tokens: AssetBalanceOf<T>,
+			fee_tier: Perbill,
 		},
 
-		/// Liquidity tokens issues to account
+		/// Liquidity tokens burned by account, `reserves` are the amounts left in the pool.
 		LiquidityRemoved {
 			who: T::AccountId,
 			removed: AssetAmountPair<T>,
 			lp_tokens: AssetBalanceOf<T>,
+			reserves: AssetAmountPair<T>,
+			fee_tier: Perbill,
+		},
+
+		/// Token swapped by account in the pool of the assets at `fee_tier`.
+		TokenSwapped {
+			who: T::AccountId,
+			give: AssetAmount<T>,
+			take: AssetAmount<T>,
+			fee_tier: Perbill,
 		},
 
-		/// Token swapped by account.
-		TokenSwapped { who: T::AccountId, give: AssetAmount<T>, take: AssetAmount<T> },
+		/// Token swapped by account along a path of pools, emitted after a `TokenSwapped` event
+		/// for each of the pools in the path.
+		PathSwapped {
+			who: T::AccountId,
+			path: BoundedVec<T::DexAssetId, T::MaxPathLength>,
+			give: AssetAmount<T>,
+			take: AssetAmount<T>,
+		},
 
 		/// Asset price
 		AssetPrice { price: FixedU128 },
-	}
 
-	// Errors inform users that something went wrong.
-	#[pallet::error]
-	pub enum Error<T> {
-		/// An arithmetic error has occurred
-		Arithmetic,
+		/// The protocol fee was turned on or off for a pool
+		ProtocolFeeSwitched { pair: AssetIdPair<T>, enabled: bool, fee_tier: Perbill },
 
-		/// The provided liquidity pair is invalid
-		InvalidPair,
+		/// The protocol's share of the swap fees was minted as LP tokens
+		ProtocolFeeMinted {
+			recipient: T::AccountId,
+			lp_token_id: T::DexAssetId,
+			lp_tokens: AssetBalanceOf<T>,
+		},
 
-		/// Liquidity pool does not exist
-		PoolDoesntExists,
+		/// A fee tier was allowed or disallowed for new pools
+		FeeTierSet { fee_tier: Perbill, allowed: bool },
 
-		/// Liquidity pool already exists
-		PoolAlreadyExists,
+		/// The protocol's share of the swap fees was set
+		ProtocolShareSet { share: Perbill },
 
-		/// Insufficient liquidity provided
-		InsufficientLiquidityProvided,
+		/// The amount of LP tokens locked on the first provision to a pool was set
+		MinimumLiquiditySet { minimum_liquidity: AssetBalanceOf<T> },
 
-		/// Zero swap amount requested, amount must be positive
-		ZeroSwapAmountRequested,
+		/// The minimum balance of the LP tokens of new pools was set
+		LpTokenDustSet { lp_token_dust: AssetBalanceOf<T> },
 
-		/// There aren't enough asset to satisfy the swap
-		SwapCannotBeSatisfied,
+		/// The fee kept by pools out of what is repaid to them after flash swaps was set
+		FlashFeeSet { flash_fee: Perbill },
 
-		/// The minimum expected swap output wasn't reached
-		MinimumOutputNotReached,
+		/// The amplification coefficient of a StableSwap pool started ramping to `future`, which
+		/// it reaches at `future_time`, in unix seconds
+		AmplificationRamped {
+			pair: AssetIdPair<T>,
+			fee_tier: Perbill,
+			initial: u32,
+			future: u32,
+			future_time: u64,
+		},
 
-		/// The maximum expected swap input was exceeded
-		MaximumInputExceeded,
+		/// A weighted pool of `assets`, with their weights, was created at `fee_tier`, identified
+		/// by its LP token
+		WeightedPoolCreated {
+			lp_token_id: T::DexAssetId,
+			assets: BoundedVec<(T::DexAssetId, Perbill), T::MaxWeightedPoolAssets>,
+			fee_tier: Perbill,
+		},
 
-		/// The provided liquidity amount can lead to immediate arbitrage,
-		/// provisions should conform to:
-		///
-		/// `x / y != dx / dy`
-		///
-		/// where `x` and `y` are the asset balances
-		/// and `dx` and `dy` are the provision amounts
-		ImmediateArbitrage,
-	}
+		/// Assets were given to a weighted pool for LP tokens
+		WeightedPoolJoined {
+			who: T::AccountId,
+			lp_token_id: T::DexAssetId,
+			amounts: BoundedVec<AssetAmount<T>, T::MaxWeightedPoolAssets>,
+			lp_tokens: AssetBalanceOf<T>,
+		},
 
-	impl<T: Config> From<ArithmeticError> for Error<T> {
-		fn from(_: ArithmeticError) -> Self {
-			Self::Arithmetic
-		}
-	}
+		/// LP tokens of a weighted pool were burned for its assets
+		WeightedPoolExited {
+			who: T::AccountId,
+			lp_token_id: T::DexAssetId,
+			amounts: BoundedVec<AssetAmount<T>, T::MaxWeightedPoolAssets>,
+			lp_tokens: AssetBalanceOf<T>,
+		},
 
-	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
-	// These functions materialize as "extrinsics", which are often compared to transactions.
+		/// Token swapped by account in a weighted pool
+		WeightedTokenSwapped {
+			who: T::AccountId,
+			lp_token_id: T::DexAssetId,
+			give: AssetAmount<T>,
+			take: AssetAmount<T>,
+		},
+
+		/// A concentrated liquidity pool was created at `fee_tier`, at the square root of the
+		/// price `sqrt_price`, in Q64.64
+		ConcentratedPoolCreated {
+			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			tick_spacing: u32,
+			sqrt_price: u128,
+		},
+
+		/// A position was opened in a concentrated liquidity pool for `provided`
+		PositionOpened {
+			who: T::AccountId,
+			position_id: PositionId,
+			fee_tier: Perbill,
+			tick_lower: i32,
+			tick_upper: i32,
+			liquidity: u128,
+			provided: AssetAmountPair<T>,
+		},
+
+		/// Liquidity of a position was removed for `removed`
+		PositionDecreased {
+			who: T::AccountId,
+			position_id: PositionId,
+			liquidity: u128,
+			removed: AssetAmountPair<T>,
+		},
+
+		/// The fees earned by a position were paid out
+		PositionFeesCollected {
+			who: T::AccountId,
+			position_id: PositionId,
+			collected: AssetAmountPair<T>,
+		},
+
+		/// A position was transferred to another account
+		PositionTransferred { position_id: PositionId, from: T::AccountId, to: T::AccountId },
+
+		/// Token swapped by account in the concentrated liquidity pool of the assets at `fee_tier`
+		ConcentratedTokenSwapped {
+			who: T::AccountId,
+			give: AssetAmount<T>,
+			take: AssetAmount<T>,
+			fee_tier: Perbill,
+		},
+
+		/// A limit order offering `offer` for `want` was placed, and `offer` escrowed
+		OrderPlaced {
+			who: T::AccountId,
+			order_id: OrderId,
+			offer: AssetAmount<T>,
+			want: AssetAmount<T>,
+		},
+
+		/// A limit order was cancelled, and what was left of its offer refunded
+		OrderCancelled { who: T::AccountId, order_id: OrderId, refunded: AssetAmount<T> },
+
+		/// `filled` of the offer of a limit order was taken by `taker`, who paid `paid` for it to
+		/// `maker`
+		OrderFilled {
+			order_id: OrderId,
+			maker: T::AccountId,
+			taker: T::AccountId,
+			filled: AssetAmount<T>,
+			paid: AssetAmount<T>,
+		},
+
+		/// `taken` was flash swapped by account out of the pool of the assets at `fee_tier`, and
+		/// `repaid` to it
+		FlashSwapped {
+			who: T::AccountId,
+			taken: AssetAmountPair<T>,
+			repaid: AssetAmountPair<T>,
+			fee_tier: Perbill,
+		},
+	}
+
+	// Errors inform users that something went wrong.
+	#[pallet::error]
+	pub enum Error<T> {
+		/// An arithmetic error has occurred
+		Arithmetic,
+
+		/// The provided liquidity pair is invalid
+		InvalidPair,
+
+		/// An asset of the pool doesn't exist
+		AssetDoesNotExist,
+
+		/// Liquidity pool does not exist
+		PoolDoesntExists,
+
+		/// Liquidity pool already exists
+		PoolAlreadyExists,
+
+		/// Insufficient liquidity provided
+		InsufficientLiquidityProvided,
+
+		/// Zero swap amount requested, amount must be positive
+		ZeroSwapAmountRequested,
+
+		/// There aren't enough asset to satisfy the swap
+		SwapCannotBeSatisfied,
+
+		/// The minimum expected swap output wasn't reached
+		MinimumOutputNotReached,
+
+		/// The maximum expected swap input was exceeded
+		MaximumInputExceeded,
+
+		/// The provided liquidity amount can lead to immediate arbitrage,
+		/// provisions should conform to:
+		///
+		/// `x / y != dx / dy`
+		///
+		/// where `x` and `y` are the asset balances
+		/// and `dx` and `dy` are the provision amounts
+		ImmediateArbitrage,
+
+		/// The swap path is invalid, it must contain at least two assets and no asset more than
+		/// once
+		InvalidPath,
+
+		/// There are no LP token ids left to allocate
+		LpTokenIdsExhausted,
+
+		/// The amount of an asset to provide, in proportion to the other asset, is below the
+		/// requested minimum
+		MinimumProvisionNotReached,
+
+		/// The amount of an asset to withdraw is below the requested minimum
+		MinimumWithdrawalNotReached,
+
+		/// The deadline given for the call has passed
+		DeadlineExpired,
+
+		/// Pools can't be created at the given fee tier
+		FeeTierNotAllowed,
+
+		/// Fee tiers must be below 100%
+		InvalidFeeTier,
+
+		/// There are already `MaxFeeTiers` fee tiers allowed
+		TooManyFeeTiers,
+
+		/// The protocol's share of the swap fees can't be above `MaxProtocolFeeShare`
+		ProtocolShareTooHigh,
+
+		/// The minimum liquidity must be zero or at least the LP token dust
+		InvalidMinimumLiquidity,
+
+		/// The LP token dust must be positive and, unless the minimum liquidity is zero, at most
+		/// the minimum liquidity
+		InvalidLpTokenDust,
+
+		/// Amplification coefficients must be positive and at most `MaxAmplification`
+		InvalidAmplification,
+
+		/// A ramp can't multiply or divide the amplification coefficient by more than 10
+		AmplificationChangeTooLarge,
+
+		/// A ramp of the amplification coefficient must last at least
+		/// `MinAmplificationRampDuration`
+		AmplificationRampTooShort,
+
+		/// The pool doesn't price swaps by the StableSwap invariant
+		NotStableSwapPool,
+
+		/// The runtime's `Curves` doesn't resolve the curve
+		UnknownCurve,
+
+		/// A weighted pool must have at least two assets, none of them more than once
+		InvalidWeightedPoolAssets,
+
+		/// The weights of a weighted pool must each be at least 1%, and add up to 100%
+		InvalidWeights,
+
+		/// The asset isn't one of the pool's
+		AssetNotInPool,
+
+		/// An amount must be given for every asset of the weighted pool, in increasing order of
+		/// their ids
+		AmountsDontMatchPoolAssets,
+
+		/// A swap, or a join or exit by a single asset, can't add or take more than 30% of a
+		/// weighted pool's reserve of an asset
+		WeightedAmountTooLarge,
+
+		/// The amount of LP tokens to mint is below the requested minimum
+		MinimumLpTokensNotReached,
+
+		/// The tick spacing of a concentrated liquidity pool must be from 1 to 16384
+		InvalidTickSpacing,
+
+		/// The square root of the price is outside of the range of ticks
+		InvalidSqrtPrice,
+
+		/// The lower tick of a position must be below its upper tick, both within the range of
+		/// ticks and multiples of the pool's tick spacing
+		InvalidTickRange,
+
+		/// The concentrated liquidity pool already has `MaxConcentratedTicks` ticks
+		TooManyTicks,
+
+		/// The liquidity of a position must be positive, and at most `i128::MAX`
+		ZeroLiquidity,
+
+		/// The position doesn't exist
+		PositionNotFound,
+
+		/// The position is owned by another account
+		NotPositionOwner,
+
+		/// The position has less liquidity than requested
+		InsufficientPositionLiquidity,
+
+		/// A limit order must offer and want positive amounts
+		ZeroOrderAmount,
+
+		/// A limit order must offer at least `MinOrderAmount`
+		OrderBelowMinimum,
+
+		/// The book of the pair already has `MaxOrdersPerBook` orders offering the asset
+		OrderBookFull,
+
+		/// The limit order doesn't exist
+		OrderNotFound,
+
+		/// The limit order was placed by another account
+		NotOrderOwner,
+
+		/// The limit order has less left to fill than requested
+		InsufficientOrderAmount,
+
+		/// The flash fee must be below 100%
+		InvalidFlashFee,
+
+		/// The call dispatched by a flash swap changed the pool swapped in
+		PoolChangedDuringFlashSwap,
+
+		/// What was repaid after a flash swap, less the flash fee, doesn't keep the invariant of
+		/// the pool
+		FlashSwapNotRepaid,
+	}
+
+	impl<T: Config> From<ArithmeticError> for Error<T> {
+		fn from(_: ArithmeticError) -> Self {
+			Self::Arithmetic
+		}
+	}
+
+	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
+	// These functions materialize as "extrinsics", which are often compared to transactions.
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Create a new liquidity pool with specified asset pairs and LP token.
+		/// Create a new liquidity pool with specified asset pairs at one of the allowed fee tiers,
+		/// and an LP token with an id allocated by the pallet. The same pair of assets can have a
+		/// pool at every allowed fee tier.
 		///
 		/// # Arguments
 		///
 		/// * `origin` - Origin of the transaction.
 		/// * `asset_x_id` - Identifier of the first asset.
 		/// * `asset_y_id` - Identifier of the second asset.
-		/// * `lp_token_id` - Identifier for the LP token.
+		/// * `fee_tier` - Fee tier of the pool, one of `FeeTiers`.
+		/// * `deadline` - Last block the call can be included in, if any.
 		///
 		/// # Errors
 		///
-		/// Returns `PoolAlreadyExists` if the pool for the given asset pair already exists.
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `PoolAlreadyExists` if the pool for the given asset pair and fee tier already
+		/// exists.
+		/// Returns `LpTokenIdsExhausted` if there are no LP token ids left to allocate.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
 		#[pallet::call_index(0)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::create_pool())]
 		pub fn create_pool(
 			origin: OriginFor<T>,
 			asset_x_id: T::DexAssetId,
 			asset_y_id: T::DexAssetId,
-			lp_token_id: T::DexAssetId,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let _ = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
 
-			let pair: AssetIdPair<T> = AssetIdPair::new(asset_x_id, asset_y_id)?;
-			ensure!(!Pools::contains_key(&pair), Error::<T>::PoolAlreadyExists);
-
-			Pools::<T>::insert(pair.clone(), Self::new_empty_pool(pair, &lp_token_id)?);
-			Self::deposit_event(Event::LpTokenCreated { lp_token_id });
-			Ok(())
+			Self::do_create_pool(asset_x_id, asset_y_id, fee_tier, Curve::ConstantProduct)
 		}
 
 		/// Provide liquidity to a pool and receive LP tokens in return.
@@ -362,20 +1437,27 @@ pub mod pallet {
 		///
 		/// * `origin` - Origin of the transaction.
 		/// * `provision` - Asset amounts to provide as liquidity.
-		/// * `lp_token_id` - Identifier for the LP token.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// The LP tokens are always minted in the pool's own LP token.
 		///
 		/// # Errors
 		///
 		/// Returns `InsufficientLiquidityProvided` if the provided liquidity is zero for either asset.
+		/// Returns `PoolDoesntExists` if there is no pool for the provided assets at `fee_tier`.
 		/// Returns `ImmediateArbitrage` if the provided liquidity can lead to immediate arbitrage.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
 		#[pallet::call_index(2)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::provide_liquidity())]
 		pub fn provide_liquidity(
 			origin: OriginFor<T>,
 			provision: AssetAmountPair<T>,
-			lp_token_id: T::DexAssetId,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
 
 			ensure!(
 				!provision.amount_x.balance.is_zero() && !provision.amount_y.balance.is_zero(),
@@ -383,57 +1465,25 @@ pub mod pallet {
 			);
 
 			let mut pool =
-				Pools::<T>::get(&provision.id()?).ok_or(Error::<T>::PoolAlreadyExists)?;
+				Pools::<T>::get(&provision.id()?, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			Self::update_price_cumulatives(&mut pool)?;
 
 			if !pool.asset_amounts.amount_x.balance.is_zero()
 				&& !pool.asset_amounts.amount_y.balance.is_zero()
 			{
 				ensure!(
-					pool.asset_amounts
-						.amount_x
-						.balance
-						.checked_mul(&provision.amount_x.balance)
-						.ok_or(Error::<T>::Arithmetic)?
-						== pool
-							.asset_amounts
-							.amount_y
-							.balance
-							.checked_mul(&provision.amount_y.balance)
-							.ok_or(Error::<T>::Arithmetic)?,
+					math::product(pool.asset_amounts.amount_x.balance, provision.amount_x.balance)
+						.map_err(Error::<T>::from)?
+						== math::product(
+							pool.asset_amounts.amount_y.balance,
+							provision.amount_y.balance
+						)
+						.map_err(Error::<T>::from)?,
 					Error::<T>::ImmediateArbitrage
 				);
 			}
 
-			// Transfer assets to the DEX account.
-			T::Fungibles::transfer(
-				provision.amount_x.asset_id.clone(),
-				&who,
-				&Self::dex_account_id(),
-				provision.amount_x.balance,
-				Preservation::Preserve,
-			)?;
-			T::Fungibles::transfer(
-				provision.amount_y.asset_id.clone(),
-				&who,
-				&Self::dex_account_id(),
-				provision.amount_y.balance,
-				Preservation::Preserve,
-			)?;
-
-			let lp_tokens = Self::calculate_tokens_to_mint(&provision, &pool)?;
-
-			T::Fungibles::mint_into(lp_token_id, &who, lp_tokens)?;
-			Self::deposit_event(Event::LiquidityProvided {
-				who,
-				provided: provision.clone(),
-				lp_tokens,
-			});
-
-			pool.asset_amounts.amount_x.balance += provision.amount_x.balance;
-			pool.asset_amounts.amount_y.balance += provision.amount_y.balance;
-			pool.total_liquidity += lp_tokens;
-			Pools::<T>::insert(provision.id()?, pool);
-
+			Self::do_provide_liquidity(who, provision, pool)?;
 			Ok(())
 		}
 
@@ -443,77 +1493,92 @@ pub mod pallet {
 		///
 		/// * `origin` - Origin of the transaction.
 		/// * `pair_id` - Identifier of the asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
 		/// * `lp_tokens` - Amount of LP tokens to burn in exchange for the assets.
+		/// * `min_amount_x` - Minimum amount of the first asset to receive.
+		/// * `min_amount_y` - Minimum amount of the second asset to receive.
+		/// * `withdraw_to` - Account to receive the assets, the caller if `None`.
+		/// * `deadline` - Last block the call can be included in, if any.
 		///
 		/// # Errors
 		///
 		/// Returns `PoolDoesntExists` if the specified pool does not exist.
 		/// Returns `InsufficientLiquidityProvided` if the liquidity removal results in zero assets.
+		/// Returns `MinimumWithdrawalNotReached` if the amount of either asset to receive is below
+		/// its minimum.
+		/// Returns the fungibles error if the caller holds fewer than `lp_tokens` LP tokens.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
 		#[pallet::call_index(3)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::remove_liquidity())]
 		pub fn remove_liquidity(
 			origin: OriginFor<T>,
 			pair_id: AssetIdPair<T>,
+			fee_tier: Perbill,
 			lp_tokens: AssetBalanceOf<T>,
+			min_amount_x: AssetBalanceOf<T>,
+			min_amount_y: AssetBalanceOf<T>,
+			withdraw_to: Option<T::AccountId>,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			let recipient = withdraw_to.unwrap_or_else(|| who.clone());
 
-			let pool = Pools::<T>::get(&pair_id).ok_or(Error::<T>::PoolDoesntExists)?;
-			let total_liquidity = pool.total_liquidity;
+			let mut pool =
+				Pools::<T>::get(&pair_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			Self::update_price_cumulatives(&mut pool)?;
+			Self::mint_protocol_fee(&mut pool)?;
 
-			let amount_x = lp_tokens
-				.ensure_mul(pool.asset_amounts.amount_x.balance)?
-				.ensure_div(total_liquidity)?;
-			let amount_y = lp_tokens
-				.ensure_mul(pool.asset_amounts.amount_y.balance)?
-				.ensure_div(total_liquidity)?;
+			let (amount_x, amount_y) = Self::with_pricing_curve(&pool, |curve| {
+				curve.amounts_for_lp_tokens(
+					lp_tokens,
+					(pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance),
+					pool.total_liquidity,
+				)
+			})?;
 
 			ensure!(
 				amount_x > Zero::zero() && amount_y > Zero::zero(),
 				Error::<T>::InsufficientLiquidityProvided
 			);
+			ensure!(
+				amount_x >= min_amount_x && amount_y >= min_amount_y,
+				Error::<T>::MinimumWithdrawalNotReached
+			);
+
+			T::Fungibles::burn_from(pool.lp_token_id, &who, lp_tokens, Precision::Exact, Force)?;
 
-			// Transfer the assets back to the user.
-			T::Fungibles::transfer(
+			// Transfer the assets to the recipient.
+			NativeOrAssets::<T>::transfer(
 				pool.asset_amounts.amount_x.asset_id.clone(),
 				&Self::dex_account_id(),
-				&who,
+				&recipient,
 				amount_x,
 				Preservation::Preserve,
 			)?;
-			T::Fungibles::transfer(
+			NativeOrAssets::<T>::transfer(
 				pool.asset_amounts.amount_y.asset_id.clone(),
 				&Self::dex_account_id(),
-				&who,
+				&recipient,
 				amount_y,
 				Preservation::Preserve,
 			)?;
 
-			T::Fungibles::burn_from(
-				pool.lp_token_id,
-				&who,
-				lp_tokens,
-				Precision::BestEffort,
-				Force,
-			)?;
-
-			Pools::<T>::try_mutate(&pair_id, |pool| {
-				if let Some(pool) = pool {
-					pool.asset_amounts.amount_x.balance =
-						pool.asset_amounts.amount_x.balance.saturating_sub(amount_x);
-					pool.asset_amounts.amount_y.balance =
-						pool.asset_amounts.amount_y.balance.saturating_sub(amount_y);
-					pool.total_liquidity = pool.total_liquidity.saturating_sub(lp_tokens);
-					Ok(())
-				} else {
-					Err(Error::<T>::PoolDoesntExists)
-				}
-			})?;
+			pool.asset_amounts.amount_x.balance =
+				pool.asset_amounts.amount_x.balance.saturating_sub(amount_x);
+			pool.asset_amounts.amount_y.balance =
+				pool.asset_amounts.amount_y.balance.saturating_sub(amount_y);
+			pool.total_liquidity = pool.total_liquidity.saturating_sub(lp_tokens);
+			Self::update_root_k_last(&mut pool)?;
+			let reserves = pool.asset_amounts.clone();
+			Pools::<T>::insert(&pair_id, fee_tier, pool);
 
 			Self::deposit_event(Event::<T>::LiquidityRemoved {
 				who,
 				removed: AssetAmountPair::<T>::new(pair_id, amount_x, amount_y),
 				lp_tokens,
+				reserves,
+				fee_tier,
 			});
 
 			Ok(())
@@ -522,135 +1587,208 @@ pub mod pallet {
 		/// Perform an asset swap in a specified pool with an expected minimum take amount. if the take amount is
 		/// calculated to be less than the expected minimum, the swap will fail with `MinimumOutputNotReached`.
 		///
+		/// The limit orders of the pair offering the asset taken are filled first, cheapest first,
+		/// for as long as they give more than the pool would, each emitting `OrderFilled`, and
+		/// the rest is swapped in the pool, emitting `TokenSwapped` for its part only.
+		///
 		/// # Arguments
 		///
 		/// * `origin` - Origin of the transaction.
 		/// * `give` - Asset and amount to give in the swap.
 		/// * `expect_min_take` - Minimum expected amount to receive from the swap.
 		/// * `pool_id` - Identifier of the asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `deadline` - Last block the call can be included in, if any.
 		///
 		/// # Errors
 		///
 		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `AssetNotInPool` if the asset given isn't either asset of the pair.
 		/// Returns `SwapCannotBeSatisfied` if the swap cannot be satisfied with the pool's liquidity.
 		/// Returns `MinimumOutputNotReached` if the output is less than the expected minimum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
 		#[pallet::call_index(4)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::swap_limit_take())]
 		pub fn swap_limit_take(
 			origin: OriginFor<T>,
 			give: AssetAmount<T>,
 			expect_min_take: AssetBalanceOf<T>,
 			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
 			ensure!(!give.balance.is_zero(), Error::<T>::ZeroSwapAmountRequested);
 
-			let mut pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolDoesntExists)?;
-
-			let (give_to, take_from) = Self::get_swap_assets(&mut pool, give.asset_id);
-
-			let give_amount = give.balance;
-			let take_amount =
-				Self::calculate_swap_amounts(give_amount, give_to.balance, take_from.balance)?;
-
-			ensure!(take_amount >= expect_min_take, Error::<T>::MinimumOutputNotReached);
-			ensure!(take_amount < take_from.balance, Error::<T>::SwapCannotBeSatisfied);
+			let mut pool =
+				Pools::<T>::get(&pool_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			Self::update_price_cumulatives(&mut pool)?;
 
-			// Give to dex from user
-			T::Fungibles::transfer(
-				give_to.asset_id,
+			let (_, take_asset_id) = Self::swap_direction(&pool_id, give.asset_id)?;
+			let (book_give, book_take) = Self::fill_from_book(
 				&who,
-				&Self::dex_account_id(),
-				give_amount,
-				Preservation::Preserve,
+				&pool_id,
+				&pool,
+				give.asset_id,
+				take_asset_id,
+				give.balance,
+				true,
 			)?;
 
-			// Take from dex to user
-			T::Fungibles::transfer(
-				take_from.asset_id,
-				&Self::dex_account_id(),
-				&who,
-				take_amount,
-				Preservation::Preserve,
-			)?;
+			let give_amount = give.balance.ensure_sub(book_give)?;
+			let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, give.asset_id);
+			let take_amount = if give_amount.is_zero() {
+				Zero::zero()
+			} else {
+				Self::calculate_amount_out(&pool, give_amount, reserve_in, reserve_out)?
+			};
 
-			// Update pool reserves based on what was transferred
-			give_to.balance = give_to.balance.ensure_add(give.balance)?;
-			take_from.balance = take_from.balance.ensure_sub(take_amount)?;
+			ensure!(
+				take_amount.ensure_add(book_take)? >= expect_min_take,
+				Error::<T>::MinimumOutputNotReached
+			);
+			ensure!(take_amount < reserve_out, Error::<T>::SwapCannotBeSatisfied);
 
+			let (give_to, take_from) = Self::get_swap_assets(&mut pool, give.asset_id);
+			let give = AssetAmount::<T>::new(give_to.asset_id, give_amount);
 			let take = AssetAmount::<T>::new(take_from.asset_id, take_amount);
+
+			if !give_amount.is_zero() {
+				// Give to dex from user
+				NativeOrAssets::<T>::transfer(
+					give_to.asset_id,
+					&who,
+					&Self::dex_account_id(),
+					give_amount,
+					Preservation::Preserve,
+				)?;
+
+				// Take from dex to user
+				NativeOrAssets::<T>::transfer(
+					take_from.asset_id,
+					&Self::dex_account_id(),
+					&who,
+					take_amount,
+					Preservation::Preserve,
+				)?;
+
+				// Update pool reserves based on what was transferred
+				give_to.balance = give_to.balance.ensure_add(give_amount)?;
+				take_from.balance = take_from.balance.ensure_sub(take_amount)?;
+			}
+
 			// Store updated pool
-			Pools::<T>::insert(&pool_id, pool.clone());
+			Pools::<T>::insert(&pool_id, fee_tier, pool.clone());
 
 			// Emit swap event
-			Self::deposit_event(Event::<T>::TokenSwapped { who, give, take });
+			if !give_amount.is_zero() {
+				Self::deposit_event(Event::<T>::TokenSwapped { who, give, take, fee_tier });
+			}
 
 			Ok(())
 		}
 
 		/// Perform an asset swap in a specified pool with an maximum give amount. if the give amount is
-		/// calculated to be more than the expected maximum, the swap will fail with `MinimumOutputNotReached`.
+		/// calculated to be more than the expected maximum, the swap will fail with `MaximumInputExceeded`.
+		///
+		/// The limit orders of the pair offering the asset taken are filled first, cheapest first,
+		/// for as long as they ask less than the pool would, each emitting `OrderFilled`, and the
+		/// rest is swapped in the pool, emitting `TokenSwapped` for its part only.
 		///
 		/// # Arguments
 		///
 		/// * `origin` - Origin of the transaction.
 		/// * `take` - Asset and amount to take in the swap.
-		/// * `expect_max_give` - Maximum expected amount to receive from the swap.
+		/// * `expect_max_give` - Maximum expected amount to give for the swap.
 		/// * `pool_id` - Identifier of the asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `deadline` - Last block the call can be included in, if any.
 		///
 		/// # Errors
 		///
 		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `AssetNotInPool` if the asset taken isn't either asset of the pair.
 		/// Returns `SwapCannotBeSatisfied` if the swap cannot be satisfied with the pool's liquidity.
-		/// Returns `MaximumInputExceeded` if the output is less than the expected minimum.
+		/// Returns `MaximumInputExceeded` if the input is more than the expected maximum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
 		#[pallet::call_index(5)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::swap_limit_give())]
 		pub fn swap_limit_give(
 			origin: OriginFor<T>,
 			take: AssetAmount<T>,
 			expect_max_give: AssetBalanceOf<T>,
 			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
 			ensure!(!take.balance.is_zero(), Error::<T>::ZeroSwapAmountRequested);
 
-			let mut pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolDoesntExists)?;
-
-			let (take_from, give_to) = Self::get_swap_assets(&mut pool, take.asset_id);
-
-			let take_amount = take.balance;
-			let give_amount =
-				Self::calculate_swap_amounts(take_amount, take_from.balance, give_to.balance)?;
-
-			ensure!(give_amount <= expect_max_give, Error::<T>::MaximumInputExceeded);
+			let mut pool =
+				Pools::<T>::get(&pool_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			Self::update_price_cumulatives(&mut pool)?;
 
-			// Give to dex from user
-			T::Fungibles::transfer(
-				give_to.asset_id,
+			let (_, give_asset_id) = Self::swap_direction(&pool_id, take.asset_id)?;
+			let (book_give, book_take) = Self::fill_from_book(
 				&who,
-				&Self::dex_account_id(),
-				give_amount,
-				Preservation::Preserve,
+				&pool_id,
+				&pool,
+				give_asset_id,
+				take.asset_id,
+				take.balance,
+				false,
 			)?;
 
-			// Take from dex to user
-			T::Fungibles::transfer(
-				take_from.asset_id,
-				&Self::dex_account_id(),
-				&who,
-				take_amount,
-				Preservation::Preserve,
-			)?;
+			let take_amount = take.balance.ensure_sub(book_take)?;
+			let (reserve_out, reserve_in) = Self::reserves_in_order(&pool, take.asset_id);
+			ensure!(take_amount < reserve_out, Error::<T>::SwapCannotBeSatisfied);
+			let give_amount = if take_amount.is_zero() {
+				Zero::zero()
+			} else {
+				Self::calculate_amount_in(&pool, take_amount, reserve_in, reserve_out)?
+			};
 
-			// Update pool reserves based on what was transferred.
-			give_to.balance = give_to.balance.ensure_add(give_amount)?;
-			take_from.balance = take_from.balance.ensure_sub(take_amount)?;
+			ensure!(
+				give_amount.ensure_add(book_give)? <= expect_max_give,
+				Error::<T>::MaximumInputExceeded
+			);
 
+			let (take_from, give_to) = Self::get_swap_assets(&mut pool, take.asset_id);
 			let give = AssetAmount::<T>::new(give_to.asset_id, give_amount);
-			Pools::<T>::insert(&pool_id, pool.clone());
+			let take = AssetAmount::<T>::new(take_from.asset_id, take_amount);
+
+			if !take_amount.is_zero() {
+				// Give to dex from user
+				NativeOrAssets::<T>::transfer(
+					give_to.asset_id,
+					&who,
+					&Self::dex_account_id(),
+					give_amount,
+					Preservation::Preserve,
+				)?;
+
+				// Take from dex to user
+				NativeOrAssets::<T>::transfer(
+					take_from.asset_id,
+					&Self::dex_account_id(),
+					&who,
+					take_amount,
+					Preservation::Preserve,
+				)?;
+
+				// Update pool reserves based on what was transferred.
+				give_to.balance = give_to.balance.ensure_add(give_amount)?;
+				take_from.balance = take_from.balance.ensure_sub(take_amount)?;
+			}
+
+			Pools::<T>::insert(&pool_id, fee_tier, pool.clone());
 
 			// Emit swap event
-			Self::deposit_event(Event::<T>::TokenSwapped { who, give, take });
+			if !take_amount.is_zero() {
+				Self::deposit_event(Event::<T>::TokenSwapped { who, give, take, fee_tier });
+			}
 
 			Ok(())
 		}
@@ -661,75 +1799,2823 @@ pub mod pallet {
 		///
 		/// * `origin` - Origin of the transaction.
 		/// * `pair` - Asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
 		/// * `asset_id` - Identifier of the asset for which the price is requested.
 		///
 		/// # Errors
 		///
 		/// Returns `PoolDoesntExists` if the specified pool does not exist.
 		#[pallet::call_index(6)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::get_asset_price())]
 		pub fn get_asset_price(
 			origin: OriginFor<T>,
 			pair: AssetIdPair<T>,
+			fee_tier: Perbill,
 			asset_id: T::DexAssetId,
 		) -> DispatchResult {
 			let _ = ensure_signed(origin)?; // we don't care who the signer is
 
-			let pool = Pools::<T>::get(&pair).ok_or(Error::<T>::PoolDoesntExists)?;
+			let pool = Pools::<T>::get(&pair, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
 
 			let price = Self::get_price_of_asset_in_pool(asset_id, &pool)?;
 			Self::deposit_event(Event::<T>::AssetPrice { price });
 
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
-		/// Calculates the number of liquidity provider tokens to mint for a given provision.
+		/// Swap an exact amount of the first asset in `path` for as much as possible of the last
+		/// asset, going through a pool of each consecutive pair of assets in the path, the one at
+		/// the fee tier taking the most out of it. If the final take amount is calculated to be
+		/// less than the expected minimum, the swap will fail with `MinimumOutputNotReached`.
+		///
+		/// Each hop fills the limit orders of its pair cheaper than its pool first, as
+		/// `swap_limit_take` does, with the DEX account as their taker, each emitting
+		/// `OrderFilled`, and swaps the rest in the pool, emitting `TokenSwapped` for its part
+		/// only.
 		///
 		/// # Arguments
 		///
-		/// * `added` - The provision of assets being added to the pool.
-		/// * `pool` - The current state of the liquidity pool.
+		/// * `origin` - Origin of the transaction.
+		/// * `path` - Assets to swap through, starting with the given asset and ending with the
+		///   taken asset.
+		/// * `give` - Amount of the first asset in the path to give.
+		/// * `expect_min_take` - Minimum expected amount of the last asset in the path to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
 		///
-		/// # Returns
+		/// # Errors
 		///
-		/// Returns the number of LP tokens to mint as `AssetBalanceOf<T>`.
+		/// Returns `InvalidPath` if the path is shorter than two assets or repeats an asset.
+		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `PoolDoesntExists` if there is no pool for a pair of consecutive assets.
+		/// Returns `SwapCannotBeSatisfied` if a hop cannot be satisfied with the pool's liquidity.
+		/// Returns `MinimumOutputNotReached` if the output is less than the expected minimum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::swap_exact_in_along_path(path.len() as u32))]
+		pub fn swap_exact_in_along_path(
+			origin: OriginFor<T>,
+			path: BoundedVec<T::DexAssetId, T::MaxPathLength>,
+			give: AssetBalanceOf<T>,
+			expect_min_take: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!give.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+			Self::validate_path(&path)?;
+
+			Self::swap_along_path(who, path, give, expect_min_take, true)?;
+			Ok(())
+		}
+
+		/// Swap as little as possible of the first asset in `path` for an exact amount of the
+		/// last asset, going through a pool of each consecutive pair of assets in the path, the
+		/// one at the fee tier requiring the least to be given to it. If the initial give amount is
+		/// calculated to be more than the expected maximum, the swap will fail with
+		/// `MaximumInputExceeded`.
 		///
-		/// # Errors
+		/// Each hop fills the limit orders of its pair cheaper than its pool first, as
+		/// `swap_limit_give` does, with the DEX account as their taker, and the hops are swapped
+		/// from the last one, so their events are emitted in reverse.
 		///
-		/// Returns `Error::<T>::Arithmetic` on overflow or underflow during calculations.
-		fn calculate_tokens_to_mint(
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `path` - Assets to swap through, starting with the given asset and ending with the
+		///   taken asset.
+		/// * `take` - Amount of the last asset in the path to take.
+		/// * `expect_max_give` - Maximum expected amount of the first asset in the path to give.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidPath` if the path is shorter than two assets or repeats an asset.
+		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `PoolDoesntExists` if there is no pool for a pair of consecutive assets.
+		/// Returns `SwapCannotBeSatisfied` if a hop cannot be satisfied with the pool's liquidity.
+		/// Returns `MaximumInputExceeded` if the input is more than the expected maximum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::swap_exact_out_along_path(path.len() as u32))]
+		pub fn swap_exact_out_along_path(
+			origin: OriginFor<T>,
+			path: BoundedVec<T::DexAssetId, T::MaxPathLength>,
+			take: AssetBalanceOf<T>,
+			expect_max_give: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!take.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+			Self::validate_path(&path)?;
+
+			Self::swap_along_path(who, path, take, expect_max_give, false)?;
+			Ok(())
+		}
+
+		/// Turn the protocol fee on or off for a pool. While on, the protocol's share of the swap
+		/// fees accrued by the pool is minted as LP tokens to `ProtocolFeeRecipient` whenever
+		/// liquidity is provided or removed. Fees accrued while the switch was on are minted when
+		/// it is turned off.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `pair` - Asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `enabled` - Whether the protocol fee should be on.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if the specified pool does not exist.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::set_protocol_fee())]
+		pub fn set_protocol_fee(
+			origin: OriginFor<T>,
+			pair: AssetIdPair<T>,
+			fee_tier: Perbill,
+			enabled: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Pools::<T>::try_mutate(&pair, fee_tier, |pool| -> DispatchResult {
+				let pool = pool.as_mut().ok_or(Error::<T>::PoolDoesntExists)?;
+				Self::mint_protocol_fee(pool)?;
+				pool.protocol_fee_on = enabled;
+				Self::update_root_k_last(pool)
+			})?;
+
+			Self::deposit_event(Event::<T>::ProtocolFeeSwitched { pair, enabled, fee_tier });
+
+			Ok(())
+		}
+
+		/// Provide liquidity to a pool in proportion to its reserves and receive LP tokens in
+		/// return.
+		///
+		/// Unlike `provide_liquidity`, the provision doesn't have to match the ratio of the
+		/// reserves exactly. The largest amounts at that ratio not exceeding the desired amounts
+		/// are provided, and only those are transferred from the caller. If the pool is empty, the
+		/// desired amounts are provided as is and set its price.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `pair` - Asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `amount_x_desired` - Maximum amount of the first asset to provide.
+		/// * `amount_y_desired` - Maximum amount of the second asset to provide.
+		/// * `amount_x_min` - Minimum amount of the first asset to provide.
+		/// * `amount_y_min` - Minimum amount of the second asset to provide.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if the specified pool does not exist.
+		/// Returns `MinimumProvisionNotReached` if the amount of either asset to provide is below
+		/// its minimum.
+		/// Returns `InsufficientLiquidityProvided` if the amount of either asset to provide is zero.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::add_liquidity())]
+		pub fn add_liquidity(
+			origin: OriginFor<T>,
+			pair: AssetIdPair<T>,
+			fee_tier: Perbill,
+			amount_x_desired: AssetBalanceOf<T>,
+			amount_y_desired: AssetBalanceOf<T>,
+			amount_x_min: AssetBalanceOf<T>,
+			amount_y_min: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool = Pools::<T>::get(&pair, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			Self::update_price_cumulatives(&mut pool)?;
+
+			let (reserve_x, reserve_y) =
+				(pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance);
+			let (amount_x, amount_y) = if reserve_x.is_zero() || reserve_y.is_zero() {
+				(amount_x_desired, amount_y_desired)
+			} else {
+				let amount_y_optimal = Self::quote(amount_x_desired, reserve_x, reserve_y)?;
+				if amount_y_optimal <= amount_y_desired {
+					(amount_x_desired, amount_y_optimal)
+				} else {
+					// at the ratio of the reserves, `amount_y_desired` is worth less than
+					// `amount_x_desired`
+					(Self::quote(amount_y_desired, reserve_y, reserve_x)?, amount_y_desired)
+				}
+			};
+
+			ensure!(
+				amount_x >= amount_x_min && amount_y >= amount_y_min,
+				Error::<T>::MinimumProvisionNotReached
+			);
+			ensure!(
+				!amount_x.is_zero() && !amount_y.is_zero(),
+				Error::<T>::InsufficientLiquidityProvided
+			);
+
+			Self::do_provide_liquidity(who, AssetAmountPair::new(pair, amount_x, amount_y), pool)?;
+			Ok(())
+		}
+
+		/// Allow or disallow creating new pools at a fee tier. Existing pools at a disallowed fee
+		/// tier keep working.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `fee_tier` - The fee tier to allow or disallow.
+		/// * `allowed` - Whether new pools can be created at the fee tier.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidFeeTier` if `fee_tier` is 100%.
+		/// Returns `TooManyFeeTiers` if `MaxFeeTiers` fee tiers are already allowed.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::set_fee_tier())]
+		pub fn set_fee_tier(
+			origin: OriginFor<T>,
+			fee_tier: Perbill,
+			allowed: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			Self::set_fee_tier_allowed(fee_tier, allowed)?;
+			Self::deposit_event(Event::<T>::FeeTierSet { fee_tier, allowed });
+
+			Ok(())
+		}
+
+		/// Set the protocol's share of the swap fees of pools with the protocol fee turned on.
+		/// Fees accrued since the last liquidity event of a pool are minted at the share set when
+		/// they are minted.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `share` - The protocol's share of the swap fees.
+		///
+		/// # Errors
+		///
+		/// Returns `ProtocolShareTooHigh` if `share` is above `MaxProtocolFeeShare`.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::set_protocol_share())]
+		pub fn set_protocol_share(origin: OriginFor<T>, share: Perbill) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(share <= T::MaxProtocolFeeShare::get(), Error::<T>::ProtocolShareTooHigh);
+
+			DexParameters::<T>::mutate(|parameters| parameters.protocol_share = share);
+			Self::deposit_event(Event::<T>::ProtocolShareSet { share });
+
+			Ok(())
+		}
+
+		/// Set the amount of LP tokens locked forever on the first provision to a pool, see
+		/// `MinimumLiquidity`. Only affects pools without liquidity.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `minimum_liquidity` - The amount of LP tokens to lock.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidMinimumLiquidity` if `minimum_liquidity` is positive but less than the
+		/// LP token dust.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::set_min_liquidity())]
+		pub fn set_min_liquidity(
+			origin: OriginFor<T>,
+			minimum_liquidity: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			DexParameters::<T>::try_mutate(|parameters| -> DispatchResult {
+				ensure!(
+					minimum_liquidity.is_zero() || minimum_liquidity >= parameters.lp_token_dust,
+					Error::<T>::InvalidMinimumLiquidity
+				);
+				parameters.minimum_liquidity = minimum_liquidity;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::<T>::MinimumLiquiditySet { minimum_liquidity });
+
+			Ok(())
+		}
+
+		/// Set the minimum balance of the LP tokens of pools created from now on. The LP tokens
+		/// of existing pools keep theirs.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `lp_token_dust` - The minimum balance of LP tokens.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidLpTokenDust` if `lp_token_dust` is zero, or more than a positive
+		/// minimum liquidity.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::set_lp_token_dust())]
+		pub fn set_lp_token_dust(
+			origin: OriginFor<T>,
+			lp_token_dust: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			DexParameters::<T>::try_mutate(|parameters| -> DispatchResult {
+				ensure!(
+					!lp_token_dust.is_zero()
+						&& (parameters.minimum_liquidity.is_zero()
+							|| lp_token_dust <= parameters.minimum_liquidity),
+					Error::<T>::InvalidLpTokenDust
+				);
+				parameters.lp_token_dust = lp_token_dust;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::<T>::LpTokenDustSet { lp_token_dust });
+
+			Ok(())
+		}
+
+		/// Create a new liquidity pool like `create_pool`, pricing swaps by the StableSwap
+		/// invariant rather than the constant product, for assets pegged to each other. Liquidity
+		/// is provided to and removed from it, and swapped through it, with the same calls.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `asset_x_id` - Identifier of the first asset.
+		/// * `asset_y_id` - Identifier of the second asset.
+		/// * `fee_tier` - Fee tier of the pool, one of `FeeTiers`.
+		/// * `amplification` - Amplification coefficient `A` of the pool, which `AdminOrigin` can
+		///   ramp later.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidAmplification` if `amplification` is zero or above
+		/// `MaxAmplification`.
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `PoolAlreadyExists` if the pool for the given asset pair and fee tier already
+		/// exists.
+		/// Returns `LpTokenIdsExhausted` if there are no LP token ids left to allocate.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::create_stable_pool())]
+		pub fn create_stable_pool(
+			origin: OriginFor<T>,
+			asset_x_id: T::DexAssetId,
+			asset_y_id: T::DexAssetId,
+			fee_tier: Perbill,
+			amplification: u32,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			Self::ensure_amplification(amplification)?;
+
+			Self::do_create_pool(
+				asset_x_id,
+				asset_y_id,
+				fee_tier,
+				Curve::StableSwap(Amplification::constant(amplification)),
+			)
+		}
+
+		/// Ramp the amplification coefficient of a StableSwap pool linearly from its current
+		/// value to `future`, reached at `future_time`. A ramp in progress is replaced by the new
+		/// one, starting from where it got to.
+		///
+		/// The protocol's share of the swap fees accrued so far is minted first, as `D` moves
+		/// with the coefficient.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `pair` - Asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `future` - The amplification coefficient to ramp to.
+		/// * `future_time` - Unix time, in seconds, the ramp ends at.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if the specified pool does not exist.
+		/// Returns `NotStableSwapPool` if the pool doesn't price swaps by the StableSwap invariant.
+		/// Returns `InvalidAmplification` if `future` is zero or above `MaxAmplification`.
+		/// Returns `AmplificationChangeTooLarge` if `future` is more than 10 times the current
+		/// coefficient or less than a tenth of it.
+		/// Returns `AmplificationRampTooShort` if `future_time` is less than
+		/// `MinAmplificationRampDuration` from now.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::ramp_amplification())]
+		pub fn ramp_amplification(
+			origin: OriginFor<T>,
+			pair: AssetIdPair<T>,
+			fee_tier: Perbill,
+			future: u32,
+			future_time: u64,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_amplification(future)?;
+
+			let now = T::Time::now().as_secs();
+			ensure!(
+				future_time >= now.saturating_add(T::MinAmplificationRampDuration::get()),
+				Error::<T>::AmplificationRampTooShort
+			);
+
+			let initial =
+				Pools::<T>::try_mutate(&pair, fee_tier, |pool| -> Result<u32, DispatchError> {
+					let pool = pool.as_mut().ok_or(Error::<T>::PoolDoesntExists)?;
+					let amplification = match pool.curve {
+						Curve::StableSwap(amplification) => amplification,
+						_ => return Err(Error::<T>::NotStableSwapPool.into()),
+					};
+					let initial = amplification.at(now);
+					ensure!(
+						future <= initial.saturating_mul(MAX_AMPLIFICATION_CHANGE)
+							&& future.saturating_mul(MAX_AMPLIFICATION_CHANGE) >= initial,
+						Error::<T>::AmplificationChangeTooLarge
+					);
+
+					Self::update_price_cumulatives(pool)?;
+					Self::mint_protocol_fee(pool)?;
+					pool.curve = Curve::StableSwap(Amplification {
+						initial,
+						future,
+						initial_time: now,
+						future_time,
+					});
+					Self::update_root_k_last(pool)?;
+					Ok(initial)
+				})?;
+
+			Self::deposit_event(Event::<T>::AmplificationRamped {
+				pair,
+				fee_tier,
+				initial,
+				future,
+				future_time,
+			});
+
+			Ok(())
+		}
+
+		/// Create a Balancer-style weighted pool of 2 to `MaxWeightedPoolAssets` assets at one of
+		/// the allowed fee tiers, and its LP token, by which the pool is identified. Swaps keep the
+		/// product of the reserves, each to the power of its weight, from decreasing.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `assets` - Assets of the pool with their weights, in any order.
+		/// * `fee_tier` - Fee tier of the pool, one of `FeeTiers`.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidWeightedPoolAssets` if there are less than two assets, or an asset is
+		/// given more than once.
+		/// Returns `InvalidWeights` if a weight is below 1%, or the weights don't add up to 100%.
+		/// Returns `AssetDoesNotExist` if an asset doesn't exist.
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `LpTokenIdsExhausted` if there are no LP token ids left to allocate.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(17)]
+		#[pallet::weight(T::WeightInfo::create_weighted_pool())]
+		pub fn create_weighted_pool(
+			origin: OriginFor<T>,
+			assets: BoundedVec<(T::DexAssetId, Perbill), T::MaxWeightedPoolAssets>,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut assets = assets.into_inner();
+			assets.sort_by_key(|(asset_id, _)| *asset_id);
+			ensure!(
+				assets.len() >= 2 && assets.windows(2).all(|pair| pair[0].0 != pair[1].0),
+				Error::<T>::InvalidWeightedPoolAssets
+			);
+			ensure!(
+				assets.iter().all(|(_, weight)| *weight >= MIN_WEIGHT)
+					&& assets
+						.iter()
+						.map(|(_, weight)| u64::from(weight.deconstruct()))
+						.sum::<u64>() == u64::from(Perbill::ACCURACY),
+				Error::<T>::InvalidWeights
+			);
+			ensure!(
+				assets.iter().all(|(asset_id, _)| NativeOrAssets::<T>::asset_exists(*asset_id)),
+				Error::<T>::AssetDoesNotExist
+			);
+			ensure!(FeeTiers::<T>::get().contains(&fee_tier), Error::<T>::FeeTierNotAllowed);
+
+			let assets = BoundedVec::truncate_from(assets);
+			let lp_token_id = Self::allocate_lp_token_id()?;
+			Self::create_lp_token(&lp_token_id)?;
+			WeightedPools::<T>::insert(lp_token_id, WeightedPool::empty(assets.clone(), fee_tier));
+			Self::deposit_event(Event::WeightedPoolCreated { lp_token_id, assets, fee_tier });
+			Ok(())
+		}
+
+		/// Join a weighted pool by giving it all of its assets, at the ratio of its reserves, for
+		/// LP tokens. Only as much of each asset is taken as matches the ratio of the reserves
+		/// given the others, rounded up, as for [`Pallet::add_liquidity`].
+		///
+		/// The first join of a pool sets the ratio, taking all of `max_amounts`, and mints the
+		/// invariant of the pool less the minimum liquidity of `DexParameters`, which is locked.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `lp_token_id` - LP token of the pool.
+		/// * `max_amounts` - Most of each asset to give, in increasing order of their ids.
+		/// * `min_lp_tokens` - Minimum amount of LP tokens to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no weighted pool with the given LP token.
+		/// Returns `AmountsDontMatchPoolAssets` if there isn't an amount for every asset.
+		/// Returns `InsufficientLiquidityProvided` if no LP tokens would be minted, or an amount of
+		/// the first join is zero or its invariant isn't above the minimum liquidity.
+		/// Returns `MinimumLpTokensNotReached` if the LP tokens to mint are below `min_lp_tokens`.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(18)]
+		#[pallet::weight(T::WeightInfo::join_weighted_pool())]
+		pub fn join_weighted_pool(
+			origin: OriginFor<T>,
+			lp_token_id: T::DexAssetId,
+			max_amounts: BoundedVec<AssetBalanceOf<T>, T::MaxWeightedPoolAssets>,
+			min_lp_tokens: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool =
+				WeightedPools::<T>::get(lp_token_id).ok_or(Error::<T>::PoolDoesntExists)?;
+			ensure!(max_amounts.len() == pool.assets.len(), Error::<T>::AmountsDontMatchPoolAssets);
+
+			let (amounts, lp_tokens) = if pool.total_liquidity.is_zero() {
+				ensure!(
+					max_amounts.iter().all(|amount| !amount.is_zero()),
+					Error::<T>::InsufficientLiquidityProvided
+				);
+				let invariant = math::weighted_invariant(
+					max_amounts
+						.iter()
+						.zip(&pool.assets)
+						.map(|(amount, asset)| (*amount, asset.weight)),
+				)
+				.map_err(Error::<T>::from)?;
+				let minimum = DexParameters::<T>::get().minimum_liquidity;
+				ensure!(invariant > minimum, Error::<T>::InsufficientLiquidityProvided);
+				Self::lock_minimum_liquidity(lp_token_id, &mut pool.total_liquidity)?;
+				(max_amounts.into_inner(), invariant - minimum)
+			} else {
+				let lp_tokens = max_amounts
+					.iter()
+					.zip(&pool.assets)
+					.map(|(amount, asset)| {
+						math::mul_div(*amount, pool.total_liquidity, asset.reserve, Rounding::Down)
+					})
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(Error::<T>::from)?
+					.into_iter()
+					.min()
+					.unwrap_or_default();
+				let amounts = pool
+					.assets
+					.iter()
+					.map(|asset| {
+						math::mul_div(asset.reserve, lp_tokens, pool.total_liquidity, Rounding::Up)
+					})
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(Error::<T>::from)?;
+				(amounts, lp_tokens)
+			};
+			ensure!(!lp_tokens.is_zero(), Error::<T>::InsufficientLiquidityProvided);
+			ensure!(lp_tokens >= min_lp_tokens, Error::<T>::MinimumLpTokensNotReached);
+
+			for (asset, amount) in pool.assets.iter_mut().zip(&amounts) {
+				NativeOrAssets::<T>::transfer(
+					asset.asset_id,
+					&who,
+					&Self::dex_account_id(),
+					*amount,
+					Preservation::Preserve,
+				)?;
+				asset.reserve = asset.reserve.ensure_add(*amount)?;
+			}
+			T::Fungibles::mint_into(lp_token_id, &who, lp_tokens)?;
+			pool.total_liquidity = pool.total_liquidity.ensure_add(lp_tokens)?;
+
+			let amounts = pool.amounts_of(&amounts);
+			WeightedPools::<T>::insert(lp_token_id, pool);
+			Self::deposit_event(Event::WeightedPoolJoined { who, lp_token_id, amounts, lp_tokens });
+			Ok(())
+		}
+
+		/// Join a weighted pool with liquidity by giving it a single one of its assets for LP
+		/// tokens, as if the share of it not matching the ratio of the reserves was first swapped
+		/// for the others, and paying the pool's fee on that share only, see
+		/// [`math::weighted_lp_tokens_for_amount_in`].
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `lp_token_id` - LP token of the pool.
+		/// * `give` - Asset and amount to give.
+		/// * `min_lp_tokens` - Minimum amount of LP tokens to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no weighted pool with the given LP token.
+		/// Returns `AssetNotInPool` if the pool doesn't hold the asset given.
+		/// Returns `InsufficientLiquidityProvided` if the pool has no liquidity yet, or no LP
+		/// tokens would be minted.
+		/// Returns `WeightedAmountTooLarge` if the amount is more than 30% of the pool's reserve.
+		/// Returns `MinimumLpTokensNotReached` if the LP tokens to mint are below `min_lp_tokens`.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::WeightInfo::join_weighted_pool_single())]
+		pub fn join_weighted_pool_single(
+			origin: OriginFor<T>,
+			lp_token_id: T::DexAssetId,
+			give: AssetAmount<T>,
+			min_lp_tokens: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool =
+				WeightedPools::<T>::get(lp_token_id).ok_or(Error::<T>::PoolDoesntExists)?;
+			let index = pool.index_of(give.asset_id)?;
+			ensure!(!pool.total_liquidity.is_zero(), Error::<T>::InsufficientLiquidityProvided);
+			let asset = &pool.assets[index];
+			Self::ensure_weighted_ratio(give.balance, asset.reserve)?;
+
+			let lp_tokens = math::weighted_lp_tokens_for_amount_in(
+				give.balance,
+				asset.reserve,
+				asset.weight,
+				pool.total_liquidity,
+				pool.fee_tier,
+			)
+			.map_err(Error::<T>::from)?;
+			ensure!(!lp_tokens.is_zero(), Error::<T>::InsufficientLiquidityProvided);
+			ensure!(lp_tokens >= min_lp_tokens, Error::<T>::MinimumLpTokensNotReached);
+
+			NativeOrAssets::<T>::transfer(
+				give.asset_id,
+				&who,
+				&Self::dex_account_id(),
+				give.balance,
+				Preservation::Preserve,
+			)?;
+			pool.assets[index].reserve = pool.assets[index].reserve.ensure_add(give.balance)?;
+			T::Fungibles::mint_into(lp_token_id, &who, lp_tokens)?;
+			pool.total_liquidity = pool.total_liquidity.ensure_add(lp_tokens)?;
+
+			WeightedPools::<T>::insert(lp_token_id, pool);
+			Self::deposit_event(Event::WeightedPoolJoined {
+				who,
+				lp_token_id,
+				amounts: BoundedVec::truncate_from(vec![give]),
+				lp_tokens,
+			});
+			Ok(())
+		}
+
+		/// Exit a weighted pool by burning LP tokens for a proportional share of each of its
+		/// assets, rounded down.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `lp_token_id` - LP token of the pool.
+		/// * `lp_tokens` - Amount of LP tokens to burn.
+		/// * `min_amounts` - Minimum amount of each asset to receive, in increasing order of their
+		///   ids.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no weighted pool with the given LP token.
+		/// Returns `AmountsDontMatchPoolAssets` if there isn't a minimum for every asset.
+		/// Returns `InsufficientLiquidityProvided` if the exit results in zero of any asset.
+		/// Returns `MinimumWithdrawalNotReached` if the amount of any asset to receive is below
+		/// its minimum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(20)]
+		#[pallet::weight(T::WeightInfo::exit_weighted_pool())]
+		pub fn exit_weighted_pool(
+			origin: OriginFor<T>,
+			lp_token_id: T::DexAssetId,
+			lp_tokens: AssetBalanceOf<T>,
+			min_amounts: BoundedVec<AssetBalanceOf<T>, T::MaxWeightedPoolAssets>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool =
+				WeightedPools::<T>::get(lp_token_id).ok_or(Error::<T>::PoolDoesntExists)?;
+			ensure!(min_amounts.len() == pool.assets.len(), Error::<T>::AmountsDontMatchPoolAssets);
+
+			let amounts = pool
+				.assets
+				.iter()
+				.map(|asset| {
+					math::mul_div(lp_tokens, asset.reserve, pool.total_liquidity, Rounding::Down)
+				})
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(Error::<T>::from)?;
+			ensure!(
+				amounts.iter().all(|amount| !amount.is_zero()),
+				Error::<T>::InsufficientLiquidityProvided
+			);
+			ensure!(
+				amounts
+					.iter()
+					.zip(&min_amounts)
+					.all(|(amount, min_amount)| amount >= min_amount),
+				Error::<T>::MinimumWithdrawalNotReached
+			);
+
+			T::Fungibles::burn_from(lp_token_id, &who, lp_tokens, Precision::Exact, Force)?;
+			for (asset, amount) in pool.assets.iter_mut().zip(&amounts) {
+				NativeOrAssets::<T>::transfer(
+					asset.asset_id,
+					&Self::dex_account_id(),
+					&who,
+					*amount,
+					Preservation::Preserve,
+				)?;
+				asset.reserve = asset.reserve.ensure_sub(*amount)?;
+			}
+			pool.total_liquidity = pool.total_liquidity.ensure_sub(lp_tokens)?;
+
+			let amounts = pool.amounts_of(&amounts);
+			WeightedPools::<T>::insert(lp_token_id, pool);
+			Self::deposit_event(Event::WeightedPoolExited { who, lp_token_id, amounts, lp_tokens });
+			Ok(())
+		}
+
+		/// Exit a weighted pool by burning LP tokens for a single one of its assets, as if the
+		/// share of the other assets was swapped for it, and paying the pool's fee on that share
+		/// only, see [`math::weighted_amount_out_for_lp_tokens`].
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `lp_token_id` - LP token of the pool.
+		/// * `lp_tokens` - Amount of LP tokens to burn.
+		/// * `asset_id` - Asset to receive.
+		/// * `min_amount` - Minimum amount of the asset to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no weighted pool with the given LP token.
+		/// Returns `AssetNotInPool` if the pool doesn't hold the asset.
+		/// Returns `InsufficientLiquidityProvided` if the exit results in zero of the asset.
+		/// Returns `WeightedAmountTooLarge` if the amount is more than 30% of the pool's reserve.
+		/// Returns `MinimumWithdrawalNotReached` if the amount to receive is below `min_amount`.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::WeightInfo::exit_weighted_pool_single())]
+		pub fn exit_weighted_pool_single(
+			origin: OriginFor<T>,
+			lp_token_id: T::DexAssetId,
+			lp_tokens: AssetBalanceOf<T>,
+			asset_id: T::DexAssetId,
+			min_amount: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool =
+				WeightedPools::<T>::get(lp_token_id).ok_or(Error::<T>::PoolDoesntExists)?;
+			let index = pool.index_of(asset_id)?;
+			let asset = &pool.assets[index];
+
+			let amount = math::weighted_amount_out_for_lp_tokens(
+				lp_tokens,
+				asset.reserve,
+				asset.weight,
+				pool.total_liquidity,
+				pool.fee_tier,
+			)
+			.map_err(Error::<T>::from)?;
+			Self::ensure_weighted_ratio(amount, asset.reserve)?;
+			ensure!(!amount.is_zero(), Error::<T>::InsufficientLiquidityProvided);
+			ensure!(amount >= min_amount, Error::<T>::MinimumWithdrawalNotReached);
+
+			T::Fungibles::burn_from(lp_token_id, &who, lp_tokens, Precision::Exact, Force)?;
+			NativeOrAssets::<T>::transfer(
+				asset_id,
+				&Self::dex_account_id(),
+				&who,
+				amount,
+				Preservation::Preserve,
+			)?;
+			pool.assets[index].reserve = pool.assets[index].reserve.ensure_sub(amount)?;
+			pool.total_liquidity = pool.total_liquidity.ensure_sub(lp_tokens)?;
+
+			WeightedPools::<T>::insert(lp_token_id, pool);
+			Self::deposit_event(Event::WeightedPoolExited {
+				who,
+				lp_token_id,
+				amounts: BoundedVec::truncate_from(vec![AssetAmount::new(asset_id, amount)]),
+				lp_tokens,
+			});
+			Ok(())
+		}
+
+		/// Swap exactly `give` for another asset of a weighted pool, by the weighted constant
+		/// product, see [`math::weighted_amount_out`].
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `lp_token_id` - LP token of the pool.
+		/// * `give` - Asset and amount to give in the swap.
+		/// * `take_asset_id` - Asset to take in the swap.
+		/// * `expect_min_take` - Minimum expected amount to receive from the swap.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `PoolDoesntExists` if there is no weighted pool with the given LP token.
+		/// Returns `AssetNotInPool` if the pool doesn't hold either asset.
+		/// Returns `InvalidPair` if both assets are the same.
+		/// Returns `WeightedAmountTooLarge` if `give` is more than 30% of the pool's reserve.
+		/// Returns `MinimumOutputNotReached` if the output is less than the expected minimum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(22)]
+		#[pallet::weight(T::WeightInfo::swap_weighted_limit_take())]
+		pub fn swap_weighted_limit_take(
+			origin: OriginFor<T>,
+			lp_token_id: T::DexAssetId,
+			give: AssetAmount<T>,
+			take_asset_id: T::DexAssetId,
+			expect_min_take: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!give.balance.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+
+			let pool = WeightedPools::<T>::get(lp_token_id).ok_or(Error::<T>::PoolDoesntExists)?;
+			let (index_in, index_out) =
+				Self::weighted_swap_indices(&pool, give.asset_id, take_asset_id)?;
+			let (asset_in, asset_out) = (&pool.assets[index_in], &pool.assets[index_out]);
+			Self::ensure_weighted_ratio(give.balance, asset_in.reserve)?;
+
+			let take_amount = math::weighted_amount_out(
+				give.balance,
+				asset_in.reserve,
+				asset_in.weight,
+				asset_out.reserve,
+				asset_out.weight,
+				pool.fee_tier,
+			)
+			.map_err(Error::<T>::from)?;
+			ensure!(take_amount >= expect_min_take, Error::<T>::MinimumOutputNotReached);
+
+			Self::do_weighted_swap(
+				who,
+				lp_token_id,
+				pool,
+				(index_in, give.balance),
+				(index_out, take_amount),
+			)
+		}
+
+		/// Swap another asset of a weighted pool for exactly `take`, by the weighted constant
+		/// product, see [`math::weighted_amount_in`].
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `lp_token_id` - LP token of the pool.
+		/// * `take` - Asset and amount to take in the swap.
+		/// * `give_asset_id` - Asset to give in the swap.
+		/// * `expect_max_give` - Maximum expected amount to give for the swap.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `PoolDoesntExists` if there is no weighted pool with the given LP token.
+		/// Returns `AssetNotInPool` if the pool doesn't hold either asset.
+		/// Returns `InvalidPair` if both assets are the same.
+		/// Returns `WeightedAmountTooLarge` if `take` is more than 30% of the pool's reserve.
+		/// Returns `MaximumInputExceeded` if the input is more than the expected maximum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(23)]
+		#[pallet::weight(T::WeightInfo::swap_weighted_limit_give())]
+		pub fn swap_weighted_limit_give(
+			origin: OriginFor<T>,
+			lp_token_id: T::DexAssetId,
+			take: AssetAmount<T>,
+			give_asset_id: T::DexAssetId,
+			expect_max_give: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!take.balance.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+
+			let pool = WeightedPools::<T>::get(lp_token_id).ok_or(Error::<T>::PoolDoesntExists)?;
+			let (index_in, index_out) =
+				Self::weighted_swap_indices(&pool, give_asset_id, take.asset_id)?;
+			let (asset_in, asset_out) = (&pool.assets[index_in], &pool.assets[index_out]);
+			Self::ensure_weighted_ratio(take.balance, asset_out.reserve)?;
+
+			let give_amount = math::weighted_amount_in(
+				take.balance,
+				asset_in.reserve,
+				asset_in.weight,
+				asset_out.reserve,
+				asset_out.weight,
+				pool.fee_tier,
+			)
+			.map_err(Error::<T>::from)?;
+			ensure!(give_amount <= expect_max_give, Error::<T>::MaximumInputExceeded);
+
+			Self::do_weighted_swap(
+				who,
+				lp_token_id,
+				pool,
+				(index_in, give_amount),
+				(index_out, take.balance),
+			)
+		}
+
+		/// Create a new liquidity pool for a pair of assets of the same price at a given fee tier,
+		/// pricing swaps by the constant sum `x + y = k`: one for one, less the fee, until the
+		/// reserve taken from runs out.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `asset_x_id` - Identifier of the first asset.
+		/// * `asset_y_id` - Identifier of the second asset.
+		/// * `fee_tier` - Fee tier of the pool, one of `FeeTiers`.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `PoolAlreadyExists` if the pool for the given asset pair and fee tier already
+		/// exists.
+		/// Returns `LpTokenIdsExhausted` if there are no LP token ids left to allocate.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(24)]
+		#[pallet::weight(T::WeightInfo::create_constant_sum_pool())]
+		pub fn create_constant_sum_pool(
+			origin: OriginFor<T>,
+			asset_x_id: T::DexAssetId,
+			asset_y_id: T::DexAssetId,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			Self::do_create_pool(asset_x_id, asset_y_id, fee_tier, Curve::ConstantSum)
+		}
+
+		/// Create a Uniswap V3-style concentrated liquidity pool for a pair of assets at one of the
+		/// allowed fee tiers, in which liquidity is provided over ranges of ticks by opening
+		/// positions.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `asset_x_id` - Identifier of the first asset.
+		/// * `asset_y_id` - Identifier of the second asset.
+		/// * `fee_tier` - Fee tier of the pool, one of `FeeTiers`.
+		/// * `tick_spacing` - Spacing of the ticks positions can be bounded by, from 1 to 16384.
+		/// * `sqrt_price` - Square root of the initial price, in Q64.64, of the asset of the pair
+		///   with the lower id in the other.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidPair` if both assets are the same.
+		/// Returns `AssetDoesNotExist` if either asset doesn't exist.
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `InvalidTickSpacing` if `tick_spacing` is zero or above 16384.
+		/// Returns `InvalidSqrtPrice` if `sqrt_price` is outside of the range of ticks.
+		/// Returns `PoolAlreadyExists` if the pool for the given asset pair and fee tier already
+		/// exists.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(25)]
+		#[pallet::weight(T::WeightInfo::create_concentrated_pool())]
+		pub fn create_concentrated_pool(
+			origin: OriginFor<T>,
+			asset_x_id: T::DexAssetId,
+			asset_y_id: T::DexAssetId,
+			fee_tier: Perbill,
+			tick_spacing: u32,
+			sqrt_price: u128,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let pool_id = AssetIdPair::<T>::new(asset_x_id, asset_y_id)?;
+			ensure!(
+				NativeOrAssets::<T>::asset_exists(asset_x_id)
+					&& NativeOrAssets::<T>::asset_exists(asset_y_id),
+				Error::<T>::AssetDoesNotExist
+			);
+			ensure!(FeeTiers::<T>::get().contains(&fee_tier), Error::<T>::FeeTierNotAllowed);
+			ensure!((1..=MAX_TICK_SPACING).contains(&tick_spacing), Error::<T>::InvalidTickSpacing);
+			let tick =
+				math::tick_at_sqrt_price(sqrt_price).map_err(|_| Error::<T>::InvalidSqrtPrice)?;
+			ensure!(
+				!ConcentratedPools::<T>::contains_key(&pool_id, fee_tier),
+				Error::<T>::PoolAlreadyExists
+			);
+
+			ConcentratedPools::<T>::insert(
+				&pool_id,
+				fee_tier,
+				ConcentratedPool::new(sqrt_price, tick, tick_spacing),
+			);
+			Self::deposit_event(Event::ConcentratedPoolCreated {
+				pool_id,
+				fee_tier,
+				tick_spacing,
+				sqrt_price,
+			});
+			Ok(())
+		}
+
+		/// Open a position of `liquidity` in a concentrated liquidity pool over the range of
+		/// ticks from `tick_lower` to `tick_upper`, giving the amounts of the assets it is worth
+		/// at the pool's price, rounded up: only the first asset if the price is below the range,
+		/// only the second if it is above it, and both otherwise.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `pool_id` - Identifier of the asset pair for the pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `tick_lower` - Lower tick of the range, a multiple of the pool's tick spacing.
+		/// * `tick_upper` - Upper tick of the range, a multiple of the pool's tick spacing.
+		/// * `liquidity` - Liquidity of the position.
+		/// * `max_amount_x` - Maximum amount of the first asset to give.
+		/// * `max_amount_y` - Maximum amount of the second asset to give.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroLiquidity` if `liquidity` is zero or above `i128::MAX`.
+		/// Returns `PoolDoesntExists` if there is no concentrated liquidity pool for the pair at
+		/// `fee_tier`.
+		/// Returns `InvalidTickRange` if the range of ticks is empty, out of bounds or not aligned
+		/// to the pool's tick spacing.
+		/// Returns `TooManyTicks` if the pool already has `MaxConcentratedTicks` ticks.
+		/// Returns `MaximumInputExceeded` if either amount to give is above its maximum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(26)]
+		#[pallet::weight(T::WeightInfo::open_position())]
+		pub fn open_position(
+			origin: OriginFor<T>,
+			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			tick_lower: i32,
+			tick_upper: i32,
+			liquidity: u128,
+			max_amount_x: AssetBalanceOf<T>,
+			max_amount_y: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			let liquidity_delta = i128::try_from(liquidity)
+				.ok()
+				.filter(|liquidity| *liquidity > 0)
+				.ok_or(Error::<T>::ZeroLiquidity)?;
+
+			let mut pool = ConcentratedPools::<T>::get(&pool_id, fee_tier)
+				.ok_or(Error::<T>::PoolDoesntExists)?;
+			ensure!(
+				tick_lower < tick_upper
+					&& tick_lower >= math::MIN_TICK
+					&& tick_upper <= math::MAX_TICK
+					&& tick_lower % pool.tick_spacing as i32 == 0
+					&& tick_upper % pool.tick_spacing as i32 == 0,
+				Error::<T>::InvalidTickRange
+			);
+
+			let mut position = Position {
+				owner: who.clone(),
+				pool_id: pool_id.clone(),
+				fee_tier,
+				tick_lower,
+				tick_upper,
+				liquidity: 0,
+				fee_growth_inside_last_x: 0,
+				fee_growth_inside_last_y: 0,
+				tokens_owed_x: AssetBalanceOf::<T>::zero(),
+				tokens_owed_y: AssetBalanceOf::<T>::zero(),
+			};
+			let (amount_x, amount_y) =
+				Self::modify_position(&mut pool, &mut position, liquidity_delta)?;
+			ensure!(
+				amount_x <= max_amount_x && amount_y <= max_amount_y,
+				Error::<T>::MaximumInputExceeded
+			);
+
+			let provided = AssetAmountPair::new(pool_id.clone(), amount_x, amount_y);
+			Self::transfer_amounts(&provided, &who, &Self::dex_account_id())?;
+			let position_id = NextPositionId::<T>::get();
+			NextPositionId::<T>::put(position_id.checked_add(1).ok_or(Error::<T>::Arithmetic)?);
+			Positions::<T>::insert(position_id, position);
+			ConcentratedPools::<T>::insert(&pool_id, fee_tier, pool);
+
+			Self::deposit_event(Event::PositionOpened {
+				who,
+				position_id,
+				fee_tier,
+				tick_lower,
+				tick_upper,
+				liquidity,
+				provided,
+			});
+			Ok(())
+		}
+
+		/// Remove `liquidity` from a position for the amounts of the assets it is worth at the
+		/// pool's price, rounded down. The fees earned by the position are accrued to it, to be
+		/// paid out by [`Pallet::collect_position_fees`], and the position is closed once it has
+		/// neither liquidity nor fees left.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, the owner of the position.
+		/// * `position_id` - Identifier of the position.
+		/// * `liquidity` - Liquidity to remove from the position.
+		/// * `min_amount_x` - Minimum amount of the first asset to receive.
+		/// * `min_amount_y` - Minimum amount of the second asset to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroLiquidity` if `liquidity` is zero.
+		/// Returns `PositionNotFound` if the position doesn't exist.
+		/// Returns `NotPositionOwner` if the caller doesn't own the position.
+		/// Returns `InsufficientPositionLiquidity` if the position has less than `liquidity`.
+		/// Returns `MinimumWithdrawalNotReached` if the amount of either asset to receive is below
+		/// its minimum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(27)]
+		#[pallet::weight(T::WeightInfo::decrease_position())]
+		pub fn decrease_position(
+			origin: OriginFor<T>,
+			position_id: PositionId,
+			liquidity: u128,
+			min_amount_x: AssetBalanceOf<T>,
+			min_amount_y: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(liquidity > 0, Error::<T>::ZeroLiquidity);
+
+			let mut position = Self::owned_position(position_id, &who)?;
+			ensure!(liquidity <= position.liquidity, Error::<T>::InsufficientPositionLiquidity);
+			let (pool_id, fee_tier) = (position.pool_id.clone(), position.fee_tier);
+			let mut pool = ConcentratedPools::<T>::get(&pool_id, fee_tier)
+				.ok_or(Error::<T>::PoolDoesntExists)?;
+
+			// at most the position's liquidity, which fits `i128`
+			let liquidity_delta = -(liquidity as i128);
+			let (amount_x, amount_y) =
+				Self::modify_position(&mut pool, &mut position, liquidity_delta)?;
+			ensure!(
+				amount_x >= min_amount_x && amount_y >= min_amount_y,
+				Error::<T>::MinimumWithdrawalNotReached
+			);
+
+			let removed = AssetAmountPair::new(pool_id.clone(), amount_x, amount_y);
+			Self::transfer_amounts(&removed, &Self::dex_account_id(), &who)?;
+			Self::store_position(position_id, position);
+			ConcentratedPools::<T>::insert(&pool_id, fee_tier, pool);
+
+			Self::deposit_event(Event::PositionDecreased { who, position_id, liquidity, removed });
+			Ok(())
+		}
+
+		/// Collect the fees earned by a position so far. The position is closed once it has
+		/// neither liquidity nor fees left.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, the owner of the position.
+		/// * `position_id` - Identifier of the position.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PositionNotFound` if the position doesn't exist.
+		/// Returns `NotPositionOwner` if the caller doesn't own the position.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(28)]
+		#[pallet::weight(T::WeightInfo::collect_position_fees())]
+		pub fn collect_position_fees(
+			origin: OriginFor<T>,
+			position_id: PositionId,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut position = Self::owned_position(position_id, &who)?;
+			if position.liquidity > 0 {
+				let pool = ConcentratedPools::<T>::get(&position.pool_id, position.fee_tier)
+					.ok_or(Error::<T>::PoolDoesntExists)?;
+				Self::accrue_position_fees(&pool, &mut position)?;
+			}
+
+			let collected = AssetAmountPair::new(
+				position.pool_id.clone(),
+				position.tokens_owed_x,
+				position.tokens_owed_y,
+			);
+			Self::transfer_amounts(&collected, &Self::dex_account_id(), &who)?;
+			position.tokens_owed_x = AssetBalanceOf::<T>::zero();
+			position.tokens_owed_y = AssetBalanceOf::<T>::zero();
+			Self::store_position(position_id, position);
+
+			Self::deposit_event(Event::PositionFeesCollected { who, position_id, collected });
+			Ok(())
+		}
+
+		/// Transfer a position, with the fees it earned so far, to another account.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, the owner of the position.
+		/// * `position_id` - Identifier of the position.
+		/// * `to` - Account to transfer the position to.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PositionNotFound` if the position doesn't exist.
+		/// Returns `NotPositionOwner` if the caller doesn't own the position.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(29)]
+		#[pallet::weight(T::WeightInfo::transfer_position())]
+		pub fn transfer_position(
+			origin: OriginFor<T>,
+			position_id: PositionId,
+			to: T::AccountId,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut position = Self::owned_position(position_id, &who)?;
+			position.owner = to.clone();
+			Positions::<T>::insert(position_id, position);
+
+			Self::deposit_event(Event::PositionTransferred { position_id, from: who, to });
+			Ok(())
+		}
+
+		/// Swap exactly `give` in a concentrated liquidity pool, crossing the ticks of the pool
+		/// on the way, with an expected minimum take amount.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `give` - Asset and amount to give in the swap.
+		/// * `expect_min_take` - Minimum expected amount to receive from the swap.
+		/// * `pool_id` - Identifier of the asset pair for the pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `PoolDoesntExists` if there is no concentrated liquidity pool for the pair at
+		/// `fee_tier`.
+		/// Returns `AssetNotInPool` if the pool doesn't hold the asset given.
+		/// Returns `SwapCannotBeSatisfied` if the pool runs out of liquidity before `give` is
+		/// swapped.
+		/// Returns `MinimumOutputNotReached` if the output is less than the expected minimum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(30)]
+		#[pallet::weight(T::WeightInfo::swap_concentrated_limit_take())]
+		pub fn swap_concentrated_limit_take(
+			origin: OriginFor<T>,
+			give: AssetAmount<T>,
+			expect_min_take: AssetBalanceOf<T>,
+			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!give.balance.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+
+			let mut pool = ConcentratedPools::<T>::get(&pool_id, fee_tier)
+				.ok_or(Error::<T>::PoolDoesntExists)?;
+			let (x_for_y, take_asset_id) = Self::swap_direction(&pool_id, give.asset_id)?;
+			let (_, take_amount) = Self::concentrated_swap(
+				&mut pool,
+				fee_tier,
+				x_for_y,
+				Self::to_u128(give.balance)?,
+				true,
+			)?;
+			let take = AssetAmount::new(take_asset_id, Self::to_balance(take_amount)?);
+			ensure!(take.balance >= expect_min_take, Error::<T>::MinimumOutputNotReached);
+
+			Self::do_concentrated_swap(who, pool_id, fee_tier, pool, give, take)
+		}
+
+		/// Swap a concentrated liquidity pool's other asset for exactly `take`, crossing the ticks
+		/// of the pool on the way, with an expected maximum give amount.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `take` - Asset and amount to take in the swap.
+		/// * `expect_max_give` - Maximum expected amount to give for the swap.
+		/// * `pool_id` - Identifier of the asset pair for the pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroSwapAmountRequested` if the swap amount is zero.
+		/// Returns `PoolDoesntExists` if there is no concentrated liquidity pool for the pair at
+		/// `fee_tier`.
+		/// Returns `AssetNotInPool` if the pool doesn't hold the asset taken.
+		/// Returns `SwapCannotBeSatisfied` if the pool runs out of liquidity before `take` is
+		/// swapped.
+		/// Returns `MaximumInputExceeded` if the input is more than the expected maximum.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(31)]
+		#[pallet::weight(T::WeightInfo::swap_concentrated_limit_give())]
+		pub fn swap_concentrated_limit_give(
+			origin: OriginFor<T>,
+			take: AssetAmount<T>,
+			expect_max_give: AssetBalanceOf<T>,
+			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!take.balance.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+
+			let mut pool = ConcentratedPools::<T>::get(&pool_id, fee_tier)
+				.ok_or(Error::<T>::PoolDoesntExists)?;
+			let (take_x, give_asset_id) = Self::swap_direction(&pool_id, take.asset_id)?;
+			let (give_amount, _) = Self::concentrated_swap(
+				&mut pool,
+				fee_tier,
+				!take_x,
+				Self::to_u128(take.balance)?,
+				false,
+			)?;
+			let give = AssetAmount::new(give_asset_id, Self::to_balance(give_amount)?);
+			ensure!(give.balance <= expect_max_give, Error::<T>::MaximumInputExceeded);
+
+			Self::do_concentrated_swap(who, pool_id, fee_tier, pool, give, take)
+		}
+
+		/// Place a limit order offering `offer` for `want`, on a pair of assets with a pool at any
+		/// fee tier. `offer` is escrowed in the DEX account until the order is filled or
+		/// cancelled, and the order rests in the book of the pair offering its asset, behind the
+		/// orders asking as little or less for it.
+		///
+		/// Orders are filled by `fill_order`, and by `swap_limit_take` and `swap_limit_give`
+		/// whenever they are cheaper than the pool swapped in.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `offer` - Asset and amount to offer.
+		/// * `want` - Asset and amount wanted for all of `offer`.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroOrderAmount` if either amount is zero.
+		/// Returns `OrderBelowMinimum` if less than `MinOrderAmount` is offered.
+		/// Returns `InvalidPair` if both amounts are of the same asset.
+		/// Returns `PoolDoesntExists` if there is no pool for the pair at any fee tier.
+		/// Returns `OrderBookFull` if the book of the pair already has `MaxOrdersPerBook` orders
+		/// offering the asset.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(32)]
+		#[pallet::weight(T::WeightInfo::place_order())]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			offer: AssetAmount<T>,
+			want: AssetAmount<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(
+				!offer.balance.is_zero() && !want.balance.is_zero(),
+				Error::<T>::ZeroOrderAmount
+			);
+			ensure!(offer.balance >= T::MinOrderAmount::get(), Error::<T>::OrderBelowMinimum);
+			let pair = AssetIdPair::<T>::new(offer.asset_id, want.asset_id)?;
+			ensure!(Pools::<T>::iter_prefix(&pair).next().is_some(), Error::<T>::PoolDoesntExists);
+
+			let order_id = NextOrderId::<T>::get();
+			NextOrderId::<T>::put(order_id.checked_add(1).ok_or(Error::<T>::Arithmetic)?);
+			let order = Order {
+				id: order_id,
+				owner: who.clone(),
+				offer: offer.balance,
+				want: want.balance,
+				remaining: offer.balance,
+			};
+			OrderBooks::<T>::try_mutate(&pair, offer.asset_id, |book| -> DispatchResult {
+				let mut index = book.len();
+				for (resting_index, resting) in book.iter().enumerate() {
+					if order.is_cheaper_than(resting)? {
+						index = resting_index;
+						break;
+					}
+				}
+				book.try_insert(index, order).map_err(|_| Error::<T>::OrderBookFull.into())
+			})?;
+			OrderBookOf::<T>::insert(order_id, (pair, offer.asset_id));
+
+			NativeOrAssets::<T>::transfer(
+				offer.asset_id,
+				&who,
+				&Self::dex_account_id(),
+				offer.balance,
+				Preservation::Preserve,
+			)?;
+
+			Self::deposit_event(Event::<T>::OrderPlaced { who, order_id, offer, want });
+			Ok(())
+		}
+
+		/// Cancel a limit order, refunding what is left of its offer.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, the owner of the order.
+		/// * `order_id` - Identifier of the order.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `OrderNotFound` if the order doesn't exist.
+		/// Returns `NotOrderOwner` if the order was placed by another account.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(33)]
+		#[pallet::weight(T::WeightInfo::cancel_order())]
+		pub fn cancel_order(
+			origin: OriginFor<T>,
+			order_id: OrderId,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			let (pair, offer_asset_id) =
+				OrderBookOf::<T>::get(order_id).ok_or(Error::<T>::OrderNotFound)?;
+			let mut book = OrderBooks::<T>::get(&pair, offer_asset_id);
+			let index = Self::order_index(&book, order_id)?;
+			ensure!(book[index].owner == who, Error::<T>::NotOrderOwner);
+
+			let order = book.remove(index);
+			OrderBookOf::<T>::remove(order_id);
+			Self::store_order_book(&pair, offer_asset_id, book);
+
+			let refunded = AssetAmount::new(offer_asset_id, order.remaining);
+			NativeOrAssets::<T>::transfer(
+				offer_asset_id,
+				&Self::dex_account_id(),
+				&who,
+				refunded.balance,
+				Preservation::Preserve,
+			)?;
+
+			Self::deposit_event(Event::<T>::OrderCancelled { who, order_id, refunded });
+			Ok(())
+		}
+
+		/// Fill `amount` of the offer of a limit order, paying the maker for it at the price of
+		/// the order, rounded up.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `order_id` - Identifier of the order.
+		/// * `amount` - Amount of the offered asset to take.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroSwapAmountRequested` if `amount` is zero.
+		/// Returns `OrderNotFound` if the order doesn't exist.
+		/// Returns `InsufficientOrderAmount` if less than `amount` is left to fill of the order.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(34)]
+		#[pallet::weight(T::WeightInfo::fill_order())]
+		pub fn fill_order(
+			origin: OriginFor<T>,
+			order_id: OrderId,
+			amount: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+
+			let (pair, offer_asset_id) =
+				OrderBookOf::<T>::get(order_id).ok_or(Error::<T>::OrderNotFound)?;
+			let (_, want_asset_id) = Self::swap_direction(&pair, offer_asset_id)?;
+			let mut book = OrderBooks::<T>::get(&pair, offer_asset_id);
+			let index = Self::order_index(&book, order_id)?;
+			let order = &mut book[index];
+			ensure!(amount <= order.remaining, Error::<T>::InsufficientOrderAmount);
+
+			let cost = order.cost_of(amount)?;
+			Self::do_fill_order(&who, order, offer_asset_id, want_asset_id, amount, cost)?;
+			Self::store_order_book(&pair, offer_asset_id, book);
+			Ok(())
+		}
+
+		/// Take `take` from a pool before paying for it: `take` is transferred to the caller,
+		/// `call` is dispatched on their behalf, and `repay` is then taken from them.
+		///
+		/// Less the flash fee, which the pool keeps, what is repaid must be worth what is taken
+		/// by the curve of the pool, without its swap fee, so that its invariant doesn't decrease.
+		/// `take` can be repaid in either asset of the pool, or both. The swap is reverted as a
+		/// whole if `call` fails or the pool isn't repaid, and `call` can't change the pool.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `take` - Amounts of the assets of the pool to take.
+		/// * `repay` - Amounts of the assets of the pool to repay after `call`.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `call` - Call dispatched with the caller as its origin once `take` is transferred.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `ZeroSwapAmountRequested` if nothing is taken.
+		/// Returns `InvalidPair` if `take` and `repay` aren't amounts of the same pair of assets.
+		/// Returns `PoolDoesntExists` if the specified pool does not exist.
+		/// Returns `SwapCannotBeSatisfied` if `take` is a whole reserve of the pool or more.
+		/// Returns the error of `call` if it fails.
+		/// Returns `PoolChangedDuringFlashSwap` if `call` changed the pool.
+		/// Returns `FlashSwapNotRepaid` if `repay`, less the flash fee, decreases the invariant of
+		/// the pool.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(35)]
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(T::WeightInfo::flash_swap().saturating_add(dispatch_info.weight), dispatch_info.class)
+		})]
+		pub fn flash_swap(
+			origin: OriginFor<T>,
+			take: AssetAmountPair<T>,
+			repay: AssetAmountPair<T>,
+			fee_tier: Perbill,
+			call: Box<<T as Config>::RuntimeCall>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let dispatch_info = call.get_dispatch_info();
+			let mut call_weight = dispatch_info.weight;
+			Self::do_flash_swap(who.clone(), take, repay, fee_tier, || {
+				let post_info = call
+					.dispatch(frame_system::RawOrigin::Signed(who).into())
+					.map_err(|error| error.error)?;
+				call_weight = post_info.calc_actual_weight(&dispatch_info);
+				Ok(())
+			})?;
+
+			Ok(Some(T::WeightInfo::flash_swap().saturating_add(call_weight)).into())
+		}
+
+		/// Set the share of what is repaid to pools after flash swaps that they keep as a fee.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction, must be `AdminOrigin`.
+		/// * `flash_fee` - The flash fee.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidFlashFee` if `flash_fee` is 100%.
+		#[pallet::call_index(36)]
+		#[pallet::weight(T::WeightInfo::set_flash_fee())]
+		pub fn set_flash_fee(origin: OriginFor<T>, flash_fee: Perbill) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(flash_fee < Perbill::one(), Error::<T>::InvalidFlashFee);
+
+			DexParameters::<T>::mutate(|parameters| parameters.flash_fee = flash_fee);
+			Self::deposit_event(Event::<T>::FlashFeeSet { flash_fee });
+
+			Ok(())
+		}
+
+		/// Provide liquidity to a pool with a single one of its assets, by swapping the share of
+		/// it for which the rest and the amount taken are at the ratio of the reserves after the
+		/// swap, see [`PricingCurve::zap_swap_amount`], and providing both.
+		///
+		/// The swap only goes through the pool, not the limit orders of the pair, and emits
+		/// `TokenSwapped`. The provision is rounded down to the ratio of the reserves, and what is
+		/// left of either asset stays with the caller.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `pool_id` - Identifier of the asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `give` - Asset and amount to provide.
+		/// * `min_lp_tokens` - Minimum amount of LP tokens to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no pool for the pair at `fee_tier`.
+		/// Returns `AssetNotInPool` if the asset given isn't either asset of the pair.
+		/// Returns `InsufficientLiquidityProvided` if the pool has no liquidity yet, or `give` is
+		/// too little to provide some of both assets.
+		/// Returns `SwapCannotBeSatisfied` if the swap would take all of the other reserve.
+		/// Returns `MinimumLpTokensNotReached` if the LP tokens minted are below `min_lp_tokens`.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(37)]
+		#[pallet::weight(T::WeightInfo::zap_in())]
+		pub fn zap_in(
+			origin: OriginFor<T>,
+			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			give: AssetAmount<T>,
+			min_lp_tokens: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool =
+				Pools::<T>::get(&pool_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			let (give_is_x, take_asset_id) = Self::swap_direction(&pool_id, give.asset_id)?;
+			ensure!(!pool.total_liquidity.is_zero(), Error::<T>::InsufficientLiquidityProvided);
+			Self::update_price_cumulatives(&mut pool)?;
+
+			let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, give.asset_id);
+			let swap_amount = Self::with_pricing_curve(&pool, |curve| {
+				curve.zap_swap_amount(give.balance, reserve_in, reserve_out, fee_tier)
+			})?;
+			let take_amount =
+				Self::calculate_amount_out(&pool, swap_amount, reserve_in, reserve_out)?;
+			ensure!(!take_amount.is_zero(), Error::<T>::InsufficientLiquidityProvided);
+			ensure!(take_amount < reserve_out, Error::<T>::SwapCannotBeSatisfied);
+
+			NativeOrAssets::<T>::transfer(
+				give.asset_id,
+				&who,
+				&Self::dex_account_id(),
+				swap_amount,
+				Preservation::Preserve,
+			)?;
+			NativeOrAssets::<T>::transfer(
+				take_asset_id,
+				&Self::dex_account_id(),
+				&who,
+				take_amount,
+				Preservation::Preserve,
+			)?;
+			let (give_to, take_from) = Self::get_swap_assets(&mut pool, give.asset_id);
+			give_to.balance = give_to.balance.ensure_add(swap_amount)?;
+			take_from.balance = take_from.balance.ensure_sub(take_amount)?;
+			Self::deposit_event(Event::<T>::TokenSwapped {
+				who: who.clone(),
+				give: AssetAmount::new(give.asset_id, swap_amount),
+				take: AssetAmount::new(take_asset_id, take_amount),
+				fee_tier,
+			});
+
+			// provide as much of the rest and the amount taken as is at the ratio of the reserves
+			let rest = give.balance.ensure_sub(swap_amount)?;
+			let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, give.asset_id);
+			let take_amount_optimal = Self::quote(rest, reserve_in, reserve_out)?;
+			let (give_amount, take_amount) = if take_amount_optimal <= take_amount {
+				(rest, take_amount_optimal)
+			} else {
+				(Self::quote(take_amount, reserve_out, reserve_in)?, take_amount)
+			};
+			ensure!(
+				!give_amount.is_zero() && !take_amount.is_zero(),
+				Error::<T>::InsufficientLiquidityProvided
+			);
+
+			let provision = if give_is_x {
+				AssetAmountPair::new(pool_id, give_amount, take_amount)
+			} else {
+				AssetAmountPair::new(pool_id, take_amount, give_amount)
+			};
+			let lp_tokens = Self::do_provide_liquidity(who, provision, pool)?;
+			ensure!(lp_tokens >= min_lp_tokens, Error::<T>::MinimumLpTokensNotReached);
+
+			Ok(())
+		}
+
+		/// Remove liquidity from a pool for a single one of its assets, by swapping the share of
+		/// the other asset the LP tokens are worth for it in the pool.
+		///
+		/// The swap only goes through the pool, not the limit orders of the pair, and emits
+		/// `TokenSwapped` after `LiquidityRemoved`.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `pair_id` - Identifier of the asset pair for the liquidity pool.
+		/// * `fee_tier` - Fee tier of the pool.
+		/// * `lp_tokens` - Amount of LP tokens to burn in exchange for the asset.
+		/// * `asset_id` - Asset to receive.
+		/// * `min_amount` - Minimum amount of the asset to receive.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if the specified pool does not exist.
+		/// Returns `AssetNotInPool` if the asset isn't either asset of the pair.
+		/// Returns `InsufficientLiquidityProvided` if the liquidity removal results in zero of
+		/// either asset.
+		/// Returns `SwapCannotBeSatisfied` if the swap would take all of the reserve of the asset.
+		/// Returns `MinimumWithdrawalNotReached` if the amount to receive is below `min_amount`.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(38)]
+		#[pallet::weight(T::WeightInfo::zap_out())]
+		pub fn zap_out(
+			origin: OriginFor<T>,
+			pair_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			lp_tokens: AssetBalanceOf<T>,
+			asset_id: T::DexAssetId,
+			min_amount: AssetBalanceOf<T>,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+
+			let mut pool =
+				Pools::<T>::get(&pair_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+			let (take_is_x, give_asset_id) = Self::swap_direction(&pair_id, asset_id)?;
+			Self::update_price_cumulatives(&mut pool)?;
+			Self::mint_protocol_fee(&mut pool)?;
+
+			let (amount_x, amount_y) = Self::with_pricing_curve(&pool, |curve| {
+				curve.amounts_for_lp_tokens(
+					lp_tokens,
+					(pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance),
+					pool.total_liquidity,
+				)
+			})?;
+			ensure!(
+				!amount_x.is_zero() && !amount_y.is_zero(),
+				Error::<T>::InsufficientLiquidityProvided
+			);
+
+			T::Fungibles::burn_from(pool.lp_token_id, &who, lp_tokens, Precision::Exact, Force)?;
+			pool.asset_amounts.amount_x.balance =
+				pool.asset_amounts.amount_x.balance.ensure_sub(amount_x)?;
+			pool.asset_amounts.amount_y.balance =
+				pool.asset_amounts.amount_y.balance.ensure_sub(amount_y)?;
+			pool.total_liquidity = pool.total_liquidity.ensure_sub(lp_tokens)?;
+			Self::update_root_k_last(&mut pool)?;
+			Self::deposit_event(Event::<T>::LiquidityRemoved {
+				who: who.clone(),
+				removed: AssetAmountPair::<T>::new(pair_id.clone(), amount_x, amount_y),
+				lp_tokens,
+				reserves: pool.asset_amounts.clone(),
+				fee_tier,
+			});
+
+			// the share of the other asset is swapped without leaving the DEX account
+			let (amount, give_amount) =
+				if take_is_x { (amount_x, amount_y) } else { (amount_y, amount_x) };
+			let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, give_asset_id);
+			let take_amount =
+				Self::calculate_amount_out(&pool, give_amount, reserve_in, reserve_out)?;
+			ensure!(take_amount < reserve_out, Error::<T>::SwapCannotBeSatisfied);
+			let amount = amount.ensure_add(take_amount)?;
+			ensure!(amount >= min_amount, Error::<T>::MinimumWithdrawalNotReached);
+
+			let (give_to, take_from) = Self::get_swap_assets(&mut pool, give_asset_id);
+			give_to.balance = give_to.balance.ensure_add(give_amount)?;
+			take_from.balance = take_from.balance.ensure_sub(take_amount)?;
+			NativeOrAssets::<T>::transfer(
+				asset_id,
+				&Self::dex_account_id(),
+				&who,
+				amount,
+				Preservation::Preserve,
+			)?;
+			Pools::<T>::insert(&pair_id, fee_tier, pool);
+
+			Self::deposit_event(Event::<T>::TokenSwapped {
+				who,
+				give: AssetAmount::new(give_asset_id, give_amount),
+				take: AssetAmount::new(asset_id, take_amount),
+				fee_tier,
+			});
+
+			Ok(())
+		}
+
+		/// Create a new liquidity pool for a pair of assets at a given fee tier, pricing swaps by
+		/// the curve of the runtime's own that its `Curves` resolves `curve_id` to.
+		///
+		/// # Arguments
+		///
+		/// * `origin` - Origin of the transaction.
+		/// * `asset_x_id` - Identifier of the first asset.
+		/// * `asset_y_id` - Identifier of the second asset.
+		/// * `fee_tier` - Fee tier of the pool, one of `FeeTiers`.
+		/// * `curve_id` - Identifier of the curve, as the runtime's `Curves` resolves it.
+		/// * `deadline` - Last block the call can be included in, if any.
+		///
+		/// # Errors
+		///
+		/// Returns `UnknownCurve` if the runtime's `Curves` doesn't resolve `curve_id`.
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `PoolAlreadyExists` if the pool for the given asset pair and fee tier already
+		/// exists.
+		/// Returns `LpTokenIdsExhausted` if there are no LP token ids left to allocate.
+		/// Returns `DeadlineExpired` if the current block is past `deadline`.
+		#[pallet::call_index(39)]
+		// Does the same work as creating a constant sum pool, past resolving the curve.
+		#[pallet::weight(T::WeightInfo::create_constant_sum_pool())]
+		pub fn create_custom_pool(
+			origin: OriginFor<T>,
+			asset_x_id: T::DexAssetId,
+			asset_y_id: T::DexAssetId,
+			fee_tier: Perbill,
+			curve_id: u32,
+			deadline: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			Self::ensure_deadline(deadline)?;
+			ensure!(
+				T::Curves::with_custom_curve(curve_id, |_| ()).is_some(),
+				Error::<T>::UnknownCurve
+			);
+
+			Self::do_create_pool(asset_x_id, asset_y_id, fee_tier, Curve::Custom(curve_id))
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Creates a pool of the given assets at `fee_tier` pricing swaps by `curve`, and its LP
+		/// token.
+		///
+		/// # Errors
+		///
+		/// Returns `FeeTierNotAllowed` if `fee_tier` is not one of `FeeTiers`.
+		/// Returns `PoolAlreadyExists` if the pool for the given asset pair and fee tier already
+		/// exists.
+		/// Returns `LpTokenIdsExhausted` if there are no LP token ids left to allocate.
+		fn do_create_pool(
+			asset_x_id: T::DexAssetId,
+			asset_y_id: T::DexAssetId,
+			fee_tier: Perbill,
+			curve: Curve,
+		) -> DispatchResult {
+			let pair: AssetIdPair<T> = AssetIdPair::new(asset_x_id, asset_y_id)?;
+			ensure!(FeeTiers::<T>::get().contains(&fee_tier), Error::<T>::FeeTierNotAllowed);
+			ensure!(!Pools::<T>::contains_key(&pair, fee_tier), Error::<T>::PoolAlreadyExists);
+
+			let lp_token_id = Self::allocate_lp_token_id()?;
+			Pools::<T>::insert(
+				pair.clone(),
+				fee_tier,
+				Self::new_empty_pool(pair.clone(), &lp_token_id, fee_tier, curve)?,
+			);
+			LpTokenToPair::<T>::insert(lp_token_id, (pair, fee_tier));
+			Self::deposit_event(Event::LpTokenCreated { lp_token_id, fee_tier });
+			Ok(())
+		}
+
+		/// Ensures `amplification` is a valid amplification coefficient for StableSwap pools.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidAmplification` if it is zero or above `MaxAmplification`.
+		fn ensure_amplification(amplification: u32) -> DispatchResult {
+			ensure!(
+				amplification > 0 && amplification <= T::MaxAmplification::get(),
+				Error::<T>::InvalidAmplification
+			);
+			Ok(())
+		}
+
+		/// Ensures `amount` is at most 30% of `reserve`, the most a swap, or a join or exit by a
+		/// single asset, can add to or take from a weighted pool's reserve of an asset.
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::WeightedAmountTooLarge` if it is more.
+		fn ensure_weighted_ratio(
+			amount: AssetBalanceOf<T>,
+			reserve: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			ensure!(
+				amount <= MAX_WEIGHTED_RATIO.mul_floor(reserve),
+				Error::<T>::WeightedAmountTooLarge
+			);
+			Ok(())
+		}
+
+		/// Returns the indices of the assets given to and taken from `pool` in a swap.
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::AssetNotInPool` if the pool doesn't hold either asset.
+		/// Returns `Error::<T>::InvalidPair` if both assets are the same.
+		fn weighted_swap_indices(
+			pool: &WeightedPool<T>,
+			give_asset_id: T::DexAssetId,
+			take_asset_id: T::DexAssetId,
+		) -> Result<(usize, usize), DispatchError> {
+			ensure!(give_asset_id != take_asset_id, Error::<T>::InvalidPair);
+			Ok((pool.index_of(give_asset_id)?, pool.index_of(take_asset_id)?))
+		}
+
+		/// Transfers the amount given from `who` to the DEX account and the amount taken back,
+		/// each with the index of its asset in `pool`, and stores the pool with its reserves
+		/// updated.
+		fn do_weighted_swap(
+			who: T::AccountId,
+			lp_token_id: T::DexAssetId,
+			mut pool: WeightedPool<T>,
+			(index_in, give_amount): (usize, AssetBalanceOf<T>),
+			(index_out, take_amount): (usize, AssetBalanceOf<T>),
+		) -> DispatchResult {
+			let give = AssetAmount::<T>::new(pool.assets[index_in].asset_id, give_amount);
+			let take = AssetAmount::<T>::new(pool.assets[index_out].asset_id, take_amount);
+
+			NativeOrAssets::<T>::transfer(
+				give.asset_id,
+				&who,
+				&Self::dex_account_id(),
+				give_amount,
+				Preservation::Preserve,
+			)?;
+			NativeOrAssets::<T>::transfer(
+				take.asset_id,
+				&Self::dex_account_id(),
+				&who,
+				take_amount,
+				Preservation::Preserve,
+			)?;
+			pool.assets[index_in].reserve =
+				pool.assets[index_in].reserve.ensure_add(give_amount)?;
+			pool.assets[index_out].reserve =
+				pool.assets[index_out].reserve.ensure_sub(take_amount)?;
+
+			WeightedPools::<T>::insert(lp_token_id, pool);
+			Self::deposit_event(Event::<T>::WeightedTokenSwapped { who, lp_token_id, give, take });
+			Ok(())
+		}
+
+		/// Returns the position `position_id`, if owned by `who`.
+		///
+		/// # Errors
+		///
+		/// Returns `PositionNotFound` if the position doesn't exist, or `NotPositionOwner` if
+		/// another account owns it.
+		fn owned_position(
+			position_id: PositionId,
+			who: &T::AccountId,
+		) -> Result<Position<T>, DispatchError> {
+			let position = Positions::<T>::get(position_id).ok_or(Error::<T>::PositionNotFound)?;
+			ensure!(&position.owner == who, Error::<T>::NotPositionOwner);
+			Ok(position)
+		}
+
+		/// Stores `position`, or closes it if it has neither liquidity nor fees left.
+		fn store_position(position_id: PositionId, position: Position<T>) {
+			if position.liquidity == 0
+				&& position.tokens_owed_x.is_zero()
+				&& position.tokens_owed_y.is_zero()
+			{
+				Positions::<T>::remove(position_id);
+			} else {
+				Positions::<T>::insert(position_id, position);
+			}
+		}
+
+		/// Accrues the fees earned by `position` since they were last accrued to the amounts owed
+		/// to it, from the growth of the fees within its range in `pool`.
+		fn accrue_position_fees(
+			pool: &ConcentratedPool<T>,
+			position: &mut Position<T>,
+		) -> DispatchResult {
+			let (inside_x, inside_y) =
+				pool.fee_growth_inside(position.tick_lower, position.tick_upper);
+			let fees_x = math::fees_earned(
+				inside_x.wrapping_sub(position.fee_growth_inside_last_x),
+				position.liquidity,
+			)?;
+			let fees_y = math::fees_earned(
+				inside_y.wrapping_sub(position.fee_growth_inside_last_y),
+				position.liquidity,
+			)?;
+			position.tokens_owed_x =
+				position.tokens_owed_x.ensure_add(Self::to_balance(fees_x)?)?;
+			position.tokens_owed_y =
+				position.tokens_owed_y.ensure_add(Self::to_balance(fees_y)?)?;
+			position.fee_growth_inside_last_x = inside_x;
+			position.fee_growth_inside_last_y = inside_y;
+			Ok(())
+		}
+
+		/// Adds `liquidity_delta` to the liquidity of `position` in `pool`, and to that of the
+		/// pool if its range contains the pool's price, after accruing the fees earned by it.
+		/// Returns the amounts of the first and second asset the liquidity added or removed is
+		/// worth at the pool's price, rounded up if added and down if removed.
+		///
+		/// # Errors
+		///
+		/// Returns `TooManyTicks` if a tick bounding the position is new and the pool already has
+		/// `MaxConcentratedTicks` ticks.
+		fn modify_position(
+			pool: &mut ConcentratedPool<T>,
+			position: &mut Position<T>,
+			liquidity_delta: i128,
+		) -> Result<(AssetBalanceOf<T>, AssetBalanceOf<T>), DispatchError> {
+			let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+			pool.update_tick(tick_lower, liquidity_delta, false)?;
+			pool.update_tick(tick_upper, liquidity_delta, true)?;
+			Self::accrue_position_fees(pool, position)?;
+			position.liquidity = position
+				.liquidity
+				.checked_add_signed(liquidity_delta)
+				.ok_or(Error::<T>::Arithmetic)?;
+			pool.clear_ticks();
+
+			let sqrt_price_lower = math::sqrt_price_at_tick(tick_lower)?;
+			let sqrt_price_upper = math::sqrt_price_at_tick(tick_upper)?;
+			let liquidity = liquidity_delta.unsigned_abs();
+			let rounding = if liquidity_delta > 0 { Rounding::Up } else { Rounding::Down };
+			let (amount_x, amount_y) = if pool.tick < tick_lower {
+				(math::amount_x_delta(sqrt_price_lower, sqrt_price_upper, liquidity, rounding)?, 0)
+			} else if pool.tick < tick_upper {
+				pool.liquidity = pool
+					.liquidity
+					.checked_add_signed(liquidity_delta)
+					.ok_or(Error::<T>::Arithmetic)?;
+				(
+					math::amount_x_delta(pool.sqrt_price, sqrt_price_upper, liquidity, rounding)?,
+					math::amount_y_delta(sqrt_price_lower, pool.sqrt_price, liquidity, rounding)?,
+				)
+			} else {
+				(0, math::amount_y_delta(sqrt_price_lower, sqrt_price_upper, liquidity, rounding)?)
+			};
+			Ok((Self::to_balance(amount_x)?, Self::to_balance(amount_y)?))
+		}
+
+		/// Returns whether `asset_id` is the first asset of `pool_id`, and the other asset of it.
+		///
+		/// # Errors
+		///
+		/// Returns `AssetNotInPool` if `asset_id` isn't either asset of `pool_id`.
+		fn swap_direction(
+			pool_id: &AssetIdPair<T>,
+			asset_id: T::DexAssetId,
+		) -> Result<(bool, T::DexAssetId), DispatchError> {
+			if asset_id == pool_id.asset_x_id {
+				Ok((true, pool_id.asset_y_id))
+			} else if asset_id == pool_id.asset_y_id {
+				Ok((false, pool_id.asset_x_id))
+			} else {
+				Err(Error::<T>::AssetNotInPool.into())
+			}
+		}
+
+		/// Swaps `amount` in `pool`, giving it the first asset for the second if `x_for_y` and
+		/// the second for the first otherwise, step by step between the ticks bounding positions,
+		/// as in Uniswap V3. `amount` is given, including the fee, if `exact_in`, and taken
+		/// otherwise. Returns the amounts given and taken.
+		///
+		/// Crossing a tick adds its net liquidity to that of the pool, or removes it when crossing
+		/// downwards, and flips its fee growths outside to the other side of it.
+		///
+		/// # Errors
+		///
+		/// Returns `SwapCannotBeSatisfied` if the price reaches the end of the range of ticks
+		/// before `amount` is swapped.
+		fn concentrated_swap(
+			pool: &mut ConcentratedPool<T>,
+			fee_tier: Perbill,
+			x_for_y: bool,
+			amount: u128,
+			exact_in: bool,
+		) -> Result<(u128, u128), DispatchError> {
+			let (mut remaining, mut amount_in, mut amount_out) = (amount, 0u128, 0u128);
+			while remaining > 0 {
+				let sqrt_price_limit =
+					if x_for_y { math::MIN_SQRT_PRICE } else { math::MAX_SQRT_PRICE };
+				ensure!(pool.sqrt_price != sqrt_price_limit, Error::<T>::SwapCannotBeSatisfied);
+
+				let next_tick = if x_for_y {
+					pool.ticks
+						.iter()
+						.rev()
+						.find(|info| info.tick <= pool.tick)
+						.map(|info| info.tick)
+				} else {
+					pool.ticks.iter().find(|info| info.tick > pool.tick).map(|info| info.tick)
+				}
+				.unwrap_or(if x_for_y { math::MIN_TICK } else { math::MAX_TICK });
+				let sqrt_price_target = math::sqrt_price_at_tick(next_tick)?;
+
+				let step = math::swap_step(
+					pool.sqrt_price,
+					sqrt_price_target,
+					pool.liquidity,
+					remaining,
+					fee_tier,
+					exact_in,
+				)?;
+				let step_in = step.amount_in.checked_add(step.fee).ok_or(Error::<T>::Arithmetic)?;
+				remaining = remaining
+					.checked_sub(if exact_in { step_in } else { step.amount_out })
+					.ok_or(Error::<T>::Arithmetic)?;
+				amount_in = amount_in.checked_add(step_in).ok_or(Error::<T>::Arithmetic)?;
+				amount_out =
+					amount_out.checked_add(step.amount_out).ok_or(Error::<T>::Arithmetic)?;
+
+				let fee_growth = math::fee_growth(step.fee, pool.liquidity);
+				if x_for_y {
+					pool.fee_growth_global_x = pool.fee_growth_global_x.wrapping_add(fee_growth);
+				} else {
+					pool.fee_growth_global_y = pool.fee_growth_global_y.wrapping_add(fee_growth);
+				}
+				pool.sqrt_price = step.sqrt_price;
+
+				if step.sqrt_price == sqrt_price_target {
+					if let Ok(index) = pool.tick_index(next_tick) {
+						let (global_x, global_y) =
+							(pool.fee_growth_global_x, pool.fee_growth_global_y);
+						let info = &mut pool.ticks[index];
+						info.fee_growth_outside_x =
+							global_x.wrapping_sub(info.fee_growth_outside_x);
+						info.fee_growth_outside_y =
+							global_y.wrapping_sub(info.fee_growth_outside_y);
+						let liquidity_net = if x_for_y {
+							info.liquidity_net.checked_neg().ok_or(Error::<T>::Arithmetic)?
+						} else {
+							info.liquidity_net
+						};
+						pool.liquidity = pool
+							.liquidity
+							.checked_add_signed(liquidity_net)
+							.ok_or(Error::<T>::Arithmetic)?;
+					}
+					pool.tick = if x_for_y { next_tick - 1 } else { next_tick };
+				} else {
+					pool.tick = math::tick_at_sqrt_price(step.sqrt_price)?;
+				}
+			}
+			Ok((amount_in, amount_out))
+		}
+
+		/// Transfers `give` from `who` to the DEX account and `take` back, and stores `pool`.
+		fn do_concentrated_swap(
+			who: T::AccountId,
+			pool_id: AssetIdPair<T>,
+			fee_tier: Perbill,
+			pool: ConcentratedPool<T>,
+			give: AssetAmount<T>,
+			take: AssetAmount<T>,
+		) -> DispatchResult {
+			NativeOrAssets::<T>::transfer(
+				give.asset_id,
+				&who,
+				&Self::dex_account_id(),
+				give.balance,
+				Preservation::Preserve,
+			)?;
+			NativeOrAssets::<T>::transfer(
+				take.asset_id,
+				&Self::dex_account_id(),
+				&who,
+				take.balance,
+				Preservation::Preserve,
+			)?;
+
+			ConcentratedPools::<T>::insert(&pool_id, fee_tier, pool);
+			Self::deposit_event(Event::<T>::ConcentratedTokenSwapped { who, give, take, fee_tier });
+			Ok(())
+		}
+
+		/// Transfers the non-zero amounts of `amounts` from `from` to `to`.
+		fn transfer_amounts(
+			amounts: &AssetAmountPair<T>,
+			from: &T::AccountId,
+			to: &T::AccountId,
+		) -> DispatchResult {
+			for amount in [&amounts.amount_x, &amounts.amount_y] {
+				if !amount.balance.is_zero() {
+					NativeOrAssets::<T>::transfer(
+						amount.asset_id,
+						from,
+						to,
+						amount.balance,
+						Preservation::Preserve,
+					)?;
+				}
+			}
+			Ok(())
+		}
+
+		/// Converts `amount` of the concentrated liquidity math to a balance.
+		fn to_balance(amount: u128) -> Result<AssetBalanceOf<T>, ArithmeticError> {
+			<AssetBalanceOf<T> as TryFrom<u128>>::try_from(amount)
+				.map_err(|_| ArithmeticError::Overflow)
+		}
+
+		/// Converts `balance` to an amount of the concentrated liquidity math.
+		fn to_u128(balance: AssetBalanceOf<T>) -> Result<u128, ArithmeticError> {
+			TryInto::<u128>::try_into(balance).map_err(|_| ArithmeticError::Overflow)
+		}
+
+		/// Returns the index of the order `order_id` in `book`.
+		///
+		/// # Errors
+		///
+		/// Returns `OrderNotFound` if the order isn't in `book`.
+		fn order_index(book: &[Order<T>], order_id: OrderId) -> Result<usize, DispatchError> {
+			book.iter()
+				.position(|order| order.id == order_id)
+				.ok_or(Error::<T>::OrderNotFound.into())
+		}
+
+		/// Stores `book` as the book of `pair` offering `offer_asset_id`, forgetting the orders
+		/// of it that are filled.
+		fn store_order_book(
+			pair: &AssetIdPair<T>,
+			offer_asset_id: T::DexAssetId,
+			mut book: BoundedVec<Order<T>, T::MaxOrdersPerBook>,
+		) {
+			book.retain(|order| {
+				let filled = order.remaining.is_zero();
+				if filled {
+					OrderBookOf::<T>::remove(order.id);
+				}
+				!filled
+			});
+			if book.is_empty() {
+				OrderBooks::<T>::remove(pair, offer_asset_id);
+			} else {
+				OrderBooks::<T>::insert(pair, offer_asset_id, book);
+			}
+		}
+
+		/// Fills `amount` of `order`, offering `offer_asset_id` for `want_asset_id`, for `cost`:
+		/// `taker` pays `cost` to the maker of the order and takes `amount` out of its escrow in
+		/// the DEX account. The DEX account takes orders itself on the hops of a swap along a
+		/// path, and then already holds what it takes.
+		fn do_fill_order(
+			taker: &T::AccountId,
+			order: &mut Order<T>,
+			offer_asset_id: T::DexAssetId,
+			want_asset_id: T::DexAssetId,
+			amount: AssetBalanceOf<T>,
+			cost: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			order.remaining = order.remaining.ensure_sub(amount)?;
+			NativeOrAssets::<T>::transfer(
+				want_asset_id,
+				taker,
+				&order.owner,
+				cost,
+				Preservation::Preserve,
+			)?;
+			if *taker != Self::dex_account_id() {
+				NativeOrAssets::<T>::transfer(
+					offer_asset_id,
+					&Self::dex_account_id(),
+					taker,
+					amount,
+					Preservation::Preserve,
+				)?;
+			}
+
+			Self::deposit_event(Event::<T>::OrderFilled {
+				order_id: order.id,
+				maker: order.owner.clone(),
+				taker: taker.clone(),
+				filled: AssetAmount::new(offer_asset_id, amount),
+				paid: AssetAmount::new(want_asset_id, cost),
+			});
+			Ok(())
+		}
+
+		/// Flash swaps `take` out of the pool of its assets at `fee_tier` on behalf of `who`, for
+		/// other pallets: `B` borrows it once it is transferred to `who`, and `repay` is then
+		/// taken from them, as in `flash_swap`. Nothing is changed if it fails.
+		///
+		/// # Errors
+		///
+		/// Returns the errors of `flash_swap`, or that of `B` if it fails.
+		pub fn flash_swap_with<B: FlashBorrower<T>>(
+			who: T::AccountId,
+			take: AssetAmountPair<T>,
+			repay: AssetAmountPair<T>,
+			fee_tier: Perbill,
+		) -> DispatchResult {
+			frame_support::storage::with_storage_layer(|| {
+				Self::do_flash_swap(who.clone(), take.clone(), repay, fee_tier, || {
+					B::on_flash_swap(&who, &take)
+				})
+			})
+		}
+
+		/// Transfers `take` out of the pool of its assets at `fee_tier` to `who`, calls `borrow`,
+		/// then takes `repay` from `who` back to the pool, ensuring it keeps the invariant of the
+		/// pool less the flash fee.
+		///
+		/// # Errors
+		///
+		/// Returns the errors of `flash_swap`, or that of `borrow` if it fails.
+		fn do_flash_swap(
+			who: T::AccountId,
+			take: AssetAmountPair<T>,
+			repay: AssetAmountPair<T>,
+			fee_tier: Perbill,
+			borrow: impl FnOnce() -> DispatchResult,
+		) -> DispatchResult {
+			ensure!(
+				!take.amount_x.balance.is_zero() || !take.amount_y.balance.is_zero(),
+				Error::<T>::ZeroSwapAmountRequested
+			);
+			let pool_id = take.id()?;
+			ensure!(repay.id()? == pool_id, Error::<T>::InvalidPair);
+			let stored = Pools::<T>::get(&pool_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+
+			// amounts of the first and second asset of the pool, in whichever order they are given
+			let in_order = |amounts: &AssetAmountPair<T>| {
+				if amounts.amount_x.asset_id == pool_id.asset_x_id {
+					(amounts.amount_x.balance, amounts.amount_y.balance)
+				} else {
+					(amounts.amount_y.balance, amounts.amount_x.balance)
+				}
+			};
+			let (take_x, take_y) = in_order(&take);
+			let (repay_x, repay_y) = in_order(&repay);
+			let (reserve_x, reserve_y) =
+				(stored.asset_amounts.amount_x.balance, stored.asset_amounts.amount_y.balance);
+			ensure!(take_x < reserve_x && take_y < reserve_y, Error::<T>::SwapCannotBeSatisfied);
+
+			Self::transfer_amounts(&take, &Self::dex_account_id(), &who)?;
+			borrow()?;
+			ensure!(
+				Pools::<T>::get(&pool_id, fee_tier).as_ref() == Some(&stored),
+				Error::<T>::PoolChangedDuringFlashSwap
+			);
+			Self::transfer_amounts(&repay, &who, &Self::dex_account_id())?;
+
+			// the reserves left after the swap, without the flash fee on what is repaid
+			let flash_fee = DexParameters::<T>::get().flash_fee;
+			let adjusted = |reserve: AssetBalanceOf<T>,
+			                taken: AssetBalanceOf<T>,
+			                repaid: AssetBalanceOf<T>|
+			 -> Result<AssetBalanceOf<T>, ArithmeticError> {
+				reserve
+					.ensure_sub(taken)?
+					.ensure_add(repaid.ensure_sub(flash_fee.mul_ceil(repaid))?)
+			};
+			let mut pool = stored;
+			Self::ensure_flash_swap_repaid(
+				&pool,
+				(reserve_x, reserve_y),
+				(adjusted(reserve_x, take_x, repay_x)?, adjusted(reserve_y, take_y, repay_y)?),
+			)?;
+
+			Self::update_price_cumulatives(&mut pool)?;
+			pool.asset_amounts.amount_x.balance =
+				reserve_x.ensure_sub(take_x)?.ensure_add(repay_x)?;
+			pool.asset_amounts.amount_y.balance =
+				reserve_y.ensure_sub(take_y)?.ensure_add(repay_y)?;
+			Pools::<T>::insert(&pool_id, fee_tier, pool);
+
+			Self::deposit_event(Event::<T>::FlashSwapped {
+				who,
+				taken: take,
+				repaid: repay,
+				fee_tier,
+			});
+			Ok(())
+		}
+
+		/// Ensures `pool` is repaid by a flash swap leaving reserves `after`, less the flash fee,
+		/// from reserves `before`, amounts of its first and second asset: that what is added to a
+		/// reserve is at least what the curve of the pool charges, without its fee, for what is
+		/// taken from the other.
+		///
+		/// # Errors
+		///
+		/// Returns `FlashSwapNotRepaid` if the pool isn't repaid.
+		fn ensure_flash_swap_repaid(
+			pool: &LiquidityPool<T>,
+			before: (AssetBalanceOf<T>, AssetBalanceOf<T>),
+			after: (AssetBalanceOf<T>, AssetBalanceOf<T>),
+		) -> DispatchResult {
+			let ((x, y), (x_after, y_after)) = (before, after);
+			let amount_in = |amount_out: AssetBalanceOf<T>,
+			                 reserve_in: AssetBalanceOf<T>,
+			                 reserve_out: AssetBalanceOf<T>| {
+				Self::with_pricing_curve(pool, |curve| {
+					curve.amount_in(amount_out, reserve_in, reserve_out, Perbill::zero())
+				})
+			};
+			let repaid = match (x_after.checked_sub(&x), y_after.checked_sub(&y)) {
+				(Some(_), Some(_)) => true,
+				(Some(added_x), None) => added_x >= amount_in(y.ensure_sub(y_after)?, x, y)?,
+				(None, Some(added_y)) => added_y >= amount_in(x.ensure_sub(x_after)?, y, x)?,
+				(None, None) => false,
+			};
+			ensure!(repaid, Error::<T>::FlashSwapNotRepaid);
+			Ok(())
+		}
+
+		/// Fills the orders of the book of `pool_id` offering `take_asset_id` for `give_asset_id`
+		/// on behalf of `who`, cheapest first, as long as they are cheaper than `pool`, and returns
+		/// the amounts given to and taken from them. `amount` is given if `exact_in` and taken
+		/// otherwise, and the last order filled is only filled in part if it offers more than is
+		/// left of it.
+		///
+		/// An order is cheaper than `pool` when it gives at least as much as the pool would for
+		/// what is paid for it, or, if `amount` is taken, asks at most as much as the pool would
+		/// for what is taken from it.
+		fn fill_from_book(
+			who: &T::AccountId,
+			pool_id: &AssetIdPair<T>,
+			pool: &LiquidityPool<T>,
+			give_asset_id: T::DexAssetId,
+			take_asset_id: T::DexAssetId,
+			amount: AssetBalanceOf<T>,
+			exact_in: bool,
+		) -> Result<(AssetBalanceOf<T>, AssetBalanceOf<T>), DispatchError> {
+			let (reserve_in, reserve_out) = Self::reserves_in_order(pool, give_asset_id);
+			let mut book = OrderBooks::<T>::get(pool_id, take_asset_id);
+			let (mut given, mut taken) = (AssetBalanceOf::<T>::zero(), AssetBalanceOf::<T>::zero());
+			for order in book.iter_mut() {
+				let left = amount.ensure_sub(if exact_in { given } else { taken })?;
+				let (fill, cost) = if exact_in {
+					let cost = order.cost_of(order.remaining)?;
+					if cost <= left {
+						(order.remaining, cost)
+					} else {
+						let fill = order.amount_for(left)?;
+						(fill, order.cost_of(fill)?)
+					}
+				} else {
+					let fill = left.min(order.remaining);
+					(fill, order.cost_of(fill)?)
+				};
+				if fill.is_zero() {
+					break;
+				}
+
+				let cheaper = if exact_in {
+					fill >= Self::calculate_amount_out(pool, cost, reserve_in, reserve_out)?
+				} else {
+					fill >= reserve_out
+						|| cost <= Self::calculate_amount_in(pool, fill, reserve_in, reserve_out)?
+				};
+				if !cheaper {
+					break;
+				}
+
+				Self::do_fill_order(who, order, take_asset_id, give_asset_id, fill, cost)?;
+				given = given.ensure_add(cost)?;
+				taken = taken.ensure_add(fill)?;
+			}
+			Self::store_order_book(pool_id, take_asset_id, book);
+			Ok((given, taken))
+		}
+
+		/// Ensures the current block is not past `deadline`, if one was given.
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::DeadlineExpired` if the current block is past `deadline`.
+		fn ensure_deadline(deadline: Option<BlockNumberFor<T>>) -> DispatchResult {
+			if let Some(deadline) = deadline {
+				ensure!(
+					frame_system::Pallet::<T>::block_number() <= deadline,
+					Error::<T>::DeadlineExpired
+				);
+			}
+			Ok(())
+		}
+
+		/// Adds `fee_tier` to `FeeTiers` if `allowed`, or removes it otherwise, keeping them
+		/// sorted. Does nothing if it is already allowed or disallowed.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidFeeTier` if `fee_tier` is 100%.
+		/// Returns `TooManyFeeTiers` if `MaxFeeTiers` fee tiers are already allowed.
+		fn set_fee_tier_allowed(fee_tier: Perbill, allowed: bool) -> DispatchResult {
+			ensure!(fee_tier < Perbill::one(), Error::<T>::InvalidFeeTier);
+			FeeTiers::<T>::try_mutate(|fee_tiers| {
+				match (fee_tiers.binary_search(&fee_tier), allowed) {
+					(Err(index), true) => fee_tiers
+						.try_insert(index, fee_tier)
+						.map_err(|_| Error::<T>::TooManyFeeTiers.into()),
+					(Ok(index), false) => {
+						fee_tiers.remove(index);
+						Ok(())
+					},
+					_ => Ok(()),
+				}
+			})
+		}
+
+		/// Transfers `provision` from `who` to the DEX account, adds it to the reserves of `pool`
+		/// and mints the LP tokens it is worth to `who`, returning their amount.
+		///
+		/// `pool` is expected to have its price cumulatives already updated.
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::Arithmetic` on overflow or underflow during calculations.
+		fn do_provide_liquidity(
+			who: T::AccountId,
+			provision: AssetAmountPair<T>,
+			mut pool: LiquidityPool<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			// Transfer assets to the DEX account.
+			NativeOrAssets::<T>::transfer(
+				provision.amount_x.asset_id.clone(),
+				&who,
+				&Self::dex_account_id(),
+				provision.amount_x.balance,
+				Preservation::Preserve,
+			)?;
+			NativeOrAssets::<T>::transfer(
+				provision.amount_y.asset_id.clone(),
+				&who,
+				&Self::dex_account_id(),
+				provision.amount_y.balance,
+				Preservation::Preserve,
+			)?;
+
+			Self::mint_protocol_fee(&mut pool)?;
+			let lp_tokens = Self::calculate_tokens_to_mint(&provision, &pool)?;
+			if pool.total_liquidity.is_zero() {
+				Self::lock_minimum_liquidity(pool.lp_token_id, &mut pool.total_liquidity)?;
+			}
+
+			T::Fungibles::mint_into(pool.lp_token_id, &who, lp_tokens)?;
+			Self::deposit_event(Event::LiquidityProvided {
+				who,
+				provided: provision.clone(),
+				lp_tokens,
+				fee_tier: pool.fee_tier,
+			});
+
+			pool.asset_amounts.amount_x.balance += provision.amount_x.balance;
+			pool.asset_amounts.amount_y.balance += provision.amount_y.balance;
+			pool.total_liquidity += lp_tokens;
+			Self::update_root_k_last(&mut pool)?;
+			Pools::<T>::insert(provision.id()?, pool.fee_tier, pool);
+
+			Ok(lp_tokens)
+		}
+
+		/// Calculates the amount of `asset_y` worth `amount_x` of `asset_x` at the ratio of the
+		/// given reserves, rounded down.
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::Arithmetic` on overflow, or if `reserve_x` is zero.
+		fn quote(
+			amount_x: AssetBalanceOf<T>,
+			reserve_x: AssetBalanceOf<T>,
+			reserve_y: AssetBalanceOf<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			Ok(math::mul_div(amount_x, reserve_y, reserve_x, Rounding::Down)?)
+		}
+
+		/// Calculates the number of liquidity provider tokens to mint for a given provision.
+		///
+		/// # Arguments
+		///
+		/// * `added` - The provision of assets being added to the pool.
+		/// * `pool` - The current state of the liquidity pool.
+		///
+		/// # Returns
+		///
+		/// Returns the number of LP tokens to mint as `AssetBalanceOf<T>`, less the minimum
+		/// liquidity of `DexParameters` for the first provision to the pool, which mints the
+		/// liquidity invariant of the provision, see [`Pallet::invariant`].
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::Arithmetic` on overflow or underflow during calculations.
+		/// Returns `Error::<T>::InsufficientLiquidityProvided` if the first provision to the pool
+		/// isn't worth more than the minimum liquidity.
+		fn calculate_tokens_to_mint(
 			added: &AssetAmountPair<T>,
 			pool: &LiquidityPool<T>,
 		) -> Result<AssetBalanceOf<T>, DispatchError> {
-			let (added_x, added_y) = (added.amount_x.balance, added.amount_y.balance);
-			let (reserve_x, reserve_y) =
-				(pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance);
+			let lp_tokens = Self::with_pricing_curve(pool, |curve| {
+				curve.lp_tokens_to_mint(
+					(added.amount_x.balance, added.amount_y.balance),
+					(pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance),
+					pool.total_liquidity,
+				)
+			})?;
 
 			if pool.total_liquidity.is_zero() {
-				let sqrt = added_x
-					.checked_mul(&added_y)
-					.ok_or(Error::<T>::Arithmetic)?
-					.integer_sqrt_checked()
-					.ok_or(Error::<T>::Arithmetic)?;
-				return Ok(sqrt);
+				// the minimum liquidity of the first provision is locked, see
+				// `lock_minimum_liquidity`
+				let minimum = DexParameters::<T>::get().minimum_liquidity;
+				ensure!(lp_tokens > minimum, Error::<T>::InsufficientLiquidityProvided);
+				return Ok(lp_tokens - minimum);
 			}
+			Ok(lp_tokens)
+		}
 
-			let lp_tokens = added_x
-				.checked_mul(&pool.total_liquidity)
-				.ok_or(Error::<T>::Arithmetic)?
-				.checked_div(&reserve_x)
-				.ok_or(Error::<T>::Arithmetic)?
-				.min(
-					added_y
-						.checked_mul(&pool.total_liquidity)
-						.ok_or(Error::<T>::Arithmetic)?
-						.checked_div(&reserve_y)
-						.ok_or(Error::<T>::Arithmetic)?,
-				);
+		/// Mints the minimum liquidity of `DexParameters` in `lp_token_id`, the LP token of a pool
+		/// without liquidity, to the DEX account, where they are locked forever, as calls only burn
+		/// exactly the LP tokens they remove from the caller, and adds them to the pool's
+		/// `total_liquidity`.
+		///
+		/// Since at least this much liquidity always stays in the pool, inflating the value of a
+		/// single LP token, e.g. by swapping assets into the pool for nothing in return, costs the
+		/// attacker the minimum liquidity times as much as the value they can steal by rounding.
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::Arithmetic` on overflow.
+		fn lock_minimum_liquidity(
+			lp_token_id: T::DexAssetId,
+			total_liquidity: &mut AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let minimum = DexParameters::<T>::get().minimum_liquidity;
+			if !minimum.is_zero() {
+				T::Fungibles::mint_into(lp_token_id, &Self::dex_account_id(), minimum)?;
+			}
+			*total_liquidity = total_liquidity.ensure_add(minimum)?;
+			Ok(())
+		}
 
-			Ok(lp_tokens)
+		/// Mints the protocol's share of the swap fees accrued by `pool` since the last liquidity
+		/// event as LP tokens to `ProtocolFeeRecipient`, if the protocol fee is on.
+		///
+		/// The fees are measured by the growth of the liquidity invariant since `root_k_last`,
+		/// `sqrt(k)` for constant product pools following Uniswap V2, `D` for StableSwap ones and
+		/// `x + y` for constant sum ones, the minted amount `s` is chosen so that the recipient's
+		/// share of the new supply is the protocol's share of `DexParameters` of that growth:
+		///
+		/// ```text
+		/// s = total * (sqrt(k) - root_k_last) * share
+		///     / (sqrt(k) * (1 - share) + root_k_last * share)
+		/// ```
+		///
+		/// The amount is rounded down, see [`math::protocol_fee`].
+		///
+		/// # Errors
+		///
+		/// Returns `Error::<T>::Arithmetic` on overflow or underflow during calculations.
+		fn mint_protocol_fee(pool: &mut LiquidityPool<T>) -> DispatchResult {
+			if !pool.protocol_fee_on || pool.root_k_last.is_zero() {
+				return Ok(());
+			}
+
+			let lp_tokens = math::protocol_fee(
+				pool.total_liquidity,
+				Self::root_k_as_last_measured(pool)?,
+				pool.root_k_last,
+				DexParameters::<T>::get().protocol_share,
+			)?;
+			if lp_tokens.is_zero() {
+				return Ok(());
+			}
+
+			let recipient = T::ProtocolFeeRecipient::get();
+			T::Fungibles::mint_into(pool.lp_token_id, &recipient, lp_tokens)?;
+			pool.total_liquidity = pool.total_liquidity.ensure_add(lp_tokens)?;
+			Self::deposit_event(Event::<T>::ProtocolFeeMinted {
+				recipient,
+				lp_token_id: pool.lp_token_id,
+				lp_tokens,
+			});
+
+			Ok(())
+		}
+
+		/// Records the current liquidity invariant of the reserves of `pool` as `root_k_last`, and
+		/// the amplification coefficient of a StableSwap pool as `amplification_last`, if the
+		/// protocol fee is on, or clears them otherwise.
+		fn update_root_k_last(pool: &mut LiquidityPool<T>) -> DispatchResult {
+			pool.root_k_last = if pool.protocol_fee_on {
+				Self::root_k(pool)?
+			} else {
+				AssetBalanceOf::<T>::zero()
+			};
+			pool.amplification_last = match pool.curve {
+				Curve::StableSwap(amplification) if pool.protocol_fee_on =>
+					amplification.at(T::Time::now().as_secs()),
+				_ => 0,
+			};
+			Ok(())
+		}
+
+		/// Returns the liquidity invariant of the reserves of `pool` as `root_k_last` was
+		/// measured, at `amplification_last` for a StableSwap pool, so that only the fees of swaps
+		/// grow it and not a ramp of the amplification coefficient since.
+		fn root_k_as_last_measured(
+			pool: &LiquidityPool<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			let curve = match pool.curve {
+				Curve::StableSwap(_) =>
+					Curve::StableSwap(Amplification::constant(pool.amplification_last)),
+				curve => curve,
+			};
+			let (x, y) = (pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance);
+			Self::with_curve(curve, |curve| curve.invariant(x, y)).map_err(Into::into)
+		}
+
+		/// Returns the liquidity invariant of the reserves of `pool`, see [`Pallet::invariant`].
+		fn root_k(pool: &LiquidityPool<T>) -> Result<AssetBalanceOf<T>, DispatchError> {
+			Self::invariant(
+				pool,
+				pool.asset_amounts.amount_x.balance,
+				pool.asset_amounts.amount_y.balance,
+			)
+		}
+
+		/// Returns the liquidity invariant of reserves `x` and `y` by the curve of `pool`, see
+		/// [`PricingCurve::invariant`]. That of a StableSwap pool also grows with its
+		/// amplification coefficient while it ramps.
+		fn invariant(
+			pool: &LiquidityPool<T>,
+			x: AssetBalanceOf<T>,
+			y: AssetBalanceOf<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			Self::with_pricing_curve(pool, |curve| curve.invariant(x, y)).map_err(Into::into)
+		}
+
+		/// Calls `f` with the pricing curve of `pool`, see [`Pallet::with_curve`].
+		fn with_pricing_curve<R>(
+			pool: &LiquidityPool<T>,
+			f: impl FnOnce(&dyn PricingCurve<AssetBalanceOf<T>>) -> Result<R, ArithmeticError>,
+		) -> Result<R, Error<T>> {
+			Self::with_curve(pool.curve, f)
+		}
+
+		/// Calls `f` with the pricing curve the runtime's `Curves` resolves `curve` to, at the
+		/// current time.
+		///
+		/// # Errors
+		///
+		/// Returns `UnknownCurve` if `curve` isn't resolved, or `Arithmetic` if `f` fails.
+		fn with_curve<R>(
+			curve: Curve,
+			f: impl FnOnce(&dyn PricingCurve<AssetBalanceOf<T>>) -> Result<R, ArithmeticError>,
+		) -> Result<R, Error<T>> {
+			T::Curves::with_curve(curve, T::Time::now().as_secs(), f)
+				.ok_or(Error::<T>::UnknownCurve)?
+				.map_err(Into::into)
 		}
 
 		/// Retrieves the price ratio of a specified asset in a given liquidity pool.
@@ -745,28 +4631,462 @@ pub mod pallet {
 		///
 		/// # Errors
 		///
-		/// Returns `ArithmeticError` on overflow or underflow during calculations.
+		/// Returns `Arithmetic` on overflow or underflow during calculations.
+		/// Returns `UnknownCurve` if the curve of the pool isn't resolved.
 		fn get_price_of_asset_in_pool(
 			asset_id: <T as Config>::DexAssetId,
 			pool: &LiquidityPool<T>,
-		) -> Result<FixedU128, ArithmeticError> {
-			let price_ratio = if asset_id == pool.asset_amounts.amount_x.asset_id {
-				T::AssetBalancePairToRatioConverter::convert((
-					pool.asset_amounts.amount_x.balance,
-					pool.asset_amounts.amount_y.balance,
-				))
+		) -> Result<FixedU128, Error<T>> {
+			let (reserve, other_reserve) = Self::reserves_in_order(pool, asset_id);
+			let price =
+				Self::with_pricing_curve(pool, |curve| curve.price(reserve, other_reserve))?;
+			Ok(price.unwrap_or_else(|| {
+				T::AssetBalancePairToRatioConverter::convert((reserve, other_reserve))
+			}))
+		}
+
+		pub fn dex_account_id() -> T::AccountId {
+			PALLET_ID.into_account_truncating()
+		}
+
+		/// Quotes the amount of `asset_out` received for giving exactly `amount_in` of `asset_in`,
+		/// as [`Pallet::swap_limit_take`] would compute it against the current reserves of the
+		/// pool of the pair at the fee tier taking the most out of it.
+		///
+		/// Returns `None` if there is no pool for the pair or the swap cannot be satisfied.
+		pub fn quote_exact_in(
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+			amount_in: AssetBalanceOf<T>,
+		) -> Option<AssetBalanceOf<T>> {
+			Self::best_pool_for_exact_in(asset_in, asset_out, amount_in)
+				.ok()
+				.map(|(_, amount_out)| amount_out)
+		}
+
+		/// Quotes the amount of `asset_in` required to receive exactly `amount_out` of `asset_out`,
+		/// as [`Pallet::swap_limit_give`] would compute it against the current reserves of the
+		/// pool of the pair at the fee tier requiring the least to be given to it.
+		///
+		/// Returns `None` if there is no pool for the pair or the swap cannot be satisfied.
+		pub fn quote_exact_out(
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+			amount_out: AssetBalanceOf<T>,
+		) -> Option<AssetBalanceOf<T>> {
+			Self::best_pool_for_exact_out(asset_in, asset_out, amount_out)
+				.ok()
+				.map(|(_, amount_in)| amount_in)
+		}
+
+		/// Swaps exactly `amount_in` of `asset_in` from `who` for `asset_out`, through the limit
+		/// orders of the pair cheaper than its pool at the fee tier taking the most out of it and
+		/// then that pool, for other pallets to swap on behalf of an account. Returns the amount
+		/// taken. All of it is reverted if it fails.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidPath` if both assets are the same.
+		/// Returns `ZeroSwapAmountRequested` if `amount_in` is zero.
+		/// Returns `PoolDoesntExists` if there is no pool for the pair.
+		/// Returns `SwapCannotBeSatisfied` if the swap cannot be satisfied with the pool's
+		/// liquidity.
+		/// Returns `MinimumOutputNotReached` if the output is less than `min_amount_out`.
+		pub fn swap_exact_in(
+			who: T::AccountId,
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+			amount_in: AssetBalanceOf<T>,
+			min_amount_out: AssetBalanceOf<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			frame_support::storage::with_storage_layer(|| {
+				ensure!(!amount_in.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+				let path = Self::pair_path(asset_in, asset_out)?;
+				let (_, amount_out) =
+					Self::swap_along_path(who, path, amount_in, min_amount_out, true)?;
+				Ok(amount_out)
+			})
+		}
+
+		/// Swaps as little as possible of `asset_in` from `who` for exactly `amount_out` of
+		/// `asset_out`, through the limit orders of the pair cheaper than its pool at the fee tier
+		/// requiring the least to be given to it and then that pool, for other pallets to swap on
+		/// behalf of an account. Returns the amount given. All of it is reverted if it fails.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidPath` if both assets are the same.
+		/// Returns `ZeroSwapAmountRequested` if `amount_out` is zero.
+		/// Returns `PoolDoesntExists` if there is no pool for the pair.
+		/// Returns `SwapCannotBeSatisfied` if the swap cannot be satisfied with the pool's
+		/// liquidity.
+		/// Returns `MaximumInputExceeded` if the input is more than `max_amount_in`.
+		pub fn swap_exact_out(
+			who: T::AccountId,
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+			amount_out: AssetBalanceOf<T>,
+			max_amount_in: AssetBalanceOf<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			frame_support::storage::with_storage_layer(|| {
+				ensure!(!amount_out.is_zero(), Error::<T>::ZeroSwapAmountRequested);
+				let path = Self::pair_path(asset_in, asset_out)?;
+				let (amount_in, _) =
+					Self::swap_along_path(who, path, amount_out, max_amount_in, false)?;
+				Ok(amount_in)
+			})
+		}
+
+		/// Returns the path of a swap of `asset_in` for `asset_out` in the pool of the pair.
+		///
+		/// # Errors
+		///
+		/// Returns `InvalidPath` if both assets are the same.
+		fn pair_path(
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+		) -> Result<BoundedVec<T::DexAssetId, T::MaxPathLength>, DispatchError> {
+			let path = BoundedVec::try_from(vec![asset_in, asset_out])
+				.map_err(|_| Error::<T>::InvalidPath)?;
+			Self::validate_path(&path)?;
+			Ok(path)
+		}
+
+		/// Returns the reserves of the pool for the given assets at `fee_tier`, in the order the
+		/// assets were passed in, or `None` if there is no such pool.
+		pub fn get_reserves(
+			asset_a: T::DexAssetId,
+			asset_b: T::DexAssetId,
+			fee_tier: Perbill,
+		) -> Option<(AssetBalanceOf<T>, AssetBalanceOf<T>)> {
+			let pool = Pools::<T>::get(AssetIdPair::<T>::new(asset_a, asset_b).ok()?, fee_tier)?;
+			Some(Self::reserves_in_order(&pool, asset_a))
+		}
+
+		/// Returns the reserves of `pool`, starting with that of `asset_id`.
+		fn reserves_in_order(
+			pool: &LiquidityPool<T>,
+			asset_id: T::DexAssetId,
+		) -> (AssetBalanceOf<T>, AssetBalanceOf<T>) {
+			let (x, y) = (&pool.asset_amounts.amount_x, &pool.asset_amounts.amount_y);
+			if x.asset_id == asset_id {
+				(x.balance, y.balance)
+			} else {
+				(y.balance, x.balance)
+			}
+		}
+
+		/// Returns every pool of the given assets with its fee tier.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no pool for the assets at any fee tier.
+		fn pools_of(
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+		) -> Result<Vec<(Perbill, LiquidityPool<T>)>, DispatchError> {
+			let pair = AssetIdPair::<T>::new(asset_in, asset_out)?;
+			let pools: Vec<_> = Pools::<T>::iter_prefix(&pair).collect();
+			ensure!(!pools.is_empty(), Error::<T>::PoolDoesntExists);
+			Ok(pools)
+		}
+
+		/// Chooses, among the pools of `asset_in` and `asset_out` at every fee tier, the one taking
+		/// the most out of it for giving exactly `amount_in` to it, and returns its fee tier and
+		/// the amount taken.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no pool for the assets at any fee tier.
+		/// Returns `SwapCannotBeSatisfied` if none of the pools can satisfy the swap.
+		fn best_pool_for_exact_in(
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+			amount_in: AssetBalanceOf<T>,
+		) -> Result<(Perbill, AssetBalanceOf<T>), DispatchError> {
+			Self::pools_of(asset_in, asset_out)?
+				.into_iter()
+				.filter_map(|(fee_tier, pool)| {
+					let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, asset_in);
+					let amount_out =
+						Self::calculate_amount_out(&pool, amount_in, reserve_in, reserve_out)
+							.ok()?;
+					(amount_out < reserve_out).then_some((fee_tier, amount_out))
+				})
+				.max_by_key(|(_, amount_out)| *amount_out)
+				.ok_or_else(|| Error::<T>::SwapCannotBeSatisfied.into())
+		}
+
+		/// Chooses, among the pools of `asset_in` and `asset_out` at every fee tier, the one
+		/// requiring the least to be given to it for taking exactly `amount_out` out of it, and
+		/// returns its fee tier and the amount to give.
+		///
+		/// # Errors
+		///
+		/// Returns `PoolDoesntExists` if there is no pool for the assets at any fee tier.
+		/// Returns `SwapCannotBeSatisfied` if none of the pools can satisfy the swap.
+		fn best_pool_for_exact_out(
+			asset_in: T::DexAssetId,
+			asset_out: T::DexAssetId,
+			amount_out: AssetBalanceOf<T>,
+		) -> Result<(Perbill, AssetBalanceOf<T>), DispatchError> {
+			Self::pools_of(asset_in, asset_out)?
+				.into_iter()
+				.filter_map(|(fee_tier, pool)| {
+					let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, asset_in);
+					if amount_out >= reserve_out {
+						return None;
+					}
+					let amount_in =
+						Self::calculate_amount_in(&pool, amount_out, reserve_in, reserve_out)
+							.ok()?;
+					Some((fee_tier, amount_in))
+				})
+				.min_by_key(|(_, amount_in)| *amount_in)
+				.ok_or_else(|| Error::<T>::SwapCannotBeSatisfied.into())
+		}
+
+		/// Ensures a swap path has at least two assets and doesn't go through an asset twice,
+		/// which would only loop back through pools at the cost of their fees.
+		fn validate_path(path: &[T::DexAssetId]) -> DispatchResult {
+			ensure!(path.len() >= 2, Error::<T>::InvalidPath);
+			let mut seen = BTreeSet::new();
+			ensure!(path.iter().all(|asset_id| seen.insert(asset_id)), Error::<T>::InvalidPath);
+			Ok(())
+		}
+
+		/// Swaps along `path` on behalf of `who`, hop by hop, and returns the amounts of the first
+		/// asset given and of the last asset taken. `amount` of the first asset is given if
+		/// `exact_in`, going from the first hop, and `amount` of the last asset is taken
+		/// otherwise, going from the last hop. The outcome is then checked against `limit`, the
+		/// minimum to take if `exact_in` and the maximum to give otherwise.
+		///
+		/// Only the first and last assets are transferred between the account and the DEX, once
+		/// every hop is swapped. The intermediate amounts never leave the DEX account, which
+		/// pays the orders filled on the way from its own holdings until they are settled.
+		fn swap_along_path(
+			who: T::AccountId,
+			path: BoundedVec<T::DexAssetId, T::MaxPathLength>,
+			amount: AssetBalanceOf<T>,
+			limit: AssetBalanceOf<T>,
+			exact_in: bool,
+		) -> Result<(AssetBalanceOf<T>, AssetBalanceOf<T>), DispatchError> {
+			let mut hop_amount = amount;
+			if exact_in {
+				for hop in path.windows(2) {
+					hop_amount = Self::swap_hop(&who, hop[0], hop[1], hop_amount, true)?;
+				}
+				ensure!(hop_amount >= limit, Error::<T>::MinimumOutputNotReached);
+			} else {
+				for hop in path.windows(2).rev() {
+					hop_amount = Self::swap_hop(&who, hop[0], hop[1], hop_amount, false)?;
+				}
+				ensure!(hop_amount <= limit, Error::<T>::MaximumInputExceeded);
+			}
+			let (give_amount, take_amount) =
+				if exact_in { (amount, hop_amount) } else { (hop_amount, amount) };
+			let give =
+				AssetAmount::<T>::new(*path.first().ok_or(Error::<T>::InvalidPath)?, give_amount);
+			let take =
+				AssetAmount::<T>::new(*path.last().ok_or(Error::<T>::InvalidPath)?, take_amount);
+
+			// Give to dex from user
+			NativeOrAssets::<T>::transfer(
+				give.asset_id,
+				&who,
+				&Self::dex_account_id(),
+				give.balance,
+				Preservation::Preserve,
+			)?;
+
+			// Take from dex to user
+			NativeOrAssets::<T>::transfer(
+				take.asset_id,
+				&Self::dex_account_id(),
+				&who,
+				take.balance,
+				Preservation::Preserve,
+			)?;
+
+			Self::deposit_event(Event::<T>::PathSwapped { who, path, give, take });
+
+			Ok((give_amount, take_amount))
+		}
+
+		/// Swaps a hop of a path on behalf of `who`, `give_asset_id` for `take_asset_id`, giving
+		/// `amount` if `exact_in` and taking it otherwise, and returns the amount taken if
+		/// `exact_in` and given otherwise. The pool of the pair at the fee tier best for `amount`
+		/// is chosen, see [`Pallet::best_pool_for_exact_in`] and
+		/// [`Pallet::best_pool_for_exact_out`].
+		///
+		/// The orders of the pair cheaper than the pool are filled first by the DEX account, as
+		/// in [`Pallet::swap_limit_take`] and [`Pallet::swap_limit_give`], and the rest is swapped
+		/// in the pool, emitting `TokenSwapped` for its part only.
+		fn swap_hop(
+			who: &T::AccountId,
+			give_asset_id: T::DexAssetId,
+			take_asset_id: T::DexAssetId,
+			amount: AssetBalanceOf<T>,
+			exact_in: bool,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			let (fee_tier, _) = if exact_in {
+				Self::best_pool_for_exact_in(give_asset_id, take_asset_id, amount)?
+			} else {
+				Self::best_pool_for_exact_out(give_asset_id, take_asset_id, amount)?
+			};
+			let pool_id = AssetIdPair::<T>::new(give_asset_id, take_asset_id)?;
+			let pool = Pools::<T>::get(&pool_id, fee_tier).ok_or(Error::<T>::PoolDoesntExists)?;
+
+			let (book_give, book_take) = Self::fill_from_book(
+				&Self::dex_account_id(),
+				&pool_id,
+				&pool,
+				give_asset_id,
+				take_asset_id,
+				amount,
+				exact_in,
+			)?;
+
+			let (reserve_in, reserve_out) = Self::reserves_in_order(&pool, give_asset_id);
+			let (give_amount, take_amount) = if exact_in {
+				let give_amount = amount.ensure_sub(book_give)?;
+				let take_amount = if give_amount.is_zero() {
+					Zero::zero()
+				} else {
+					Self::calculate_amount_out(&pool, give_amount, reserve_in, reserve_out)?
+				};
+				ensure!(take_amount < reserve_out, Error::<T>::SwapCannotBeSatisfied);
+				(give_amount, take_amount)
 			} else {
-				T::AssetBalancePairToRatioConverter::convert((
-					pool.asset_amounts.amount_y.balance,
-					pool.asset_amounts.amount_x.balance,
-				))
+				let take_amount = amount.ensure_sub(book_take)?;
+				ensure!(take_amount < reserve_out, Error::<T>::SwapCannotBeSatisfied);
+				let give_amount = if take_amount.is_zero() {
+					Zero::zero()
+				} else {
+					Self::calculate_amount_in(&pool, take_amount, reserve_in, reserve_out)?
+				};
+				(give_amount, take_amount)
 			};
 
-			Ok(price_ratio)
+			let swapped_in_pool =
+				if exact_in { !give_amount.is_zero() } else { !take_amount.is_zero() };
+			if swapped_in_pool {
+				let give = AssetAmount::<T>::new(give_asset_id, give_amount);
+				let take = AssetAmount::<T>::new(take_asset_id, take_amount);
+				Self::update_reserves_after_swap(&give, &take, fee_tier)?;
+				Self::deposit_event(Event::<T>::TokenSwapped {
+					who: who.clone(),
+					give,
+					take,
+					fee_tier,
+				});
+			}
+
+			let total = if exact_in {
+				take_amount.ensure_add(book_take)?
+			} else {
+				give_amount.ensure_add(book_give)?
+			};
+			Ok(total)
 		}
 
-		pub fn dex_account_id() -> T::AccountId {
-			PALLET_ID.into_account_truncating()
+		/// Updates the reserves of the pool of `give` and `take` at `fee_tier` after `give` was
+		/// swapped for `take` in it.
+		fn update_reserves_after_swap(
+			give: &AssetAmount<T>,
+			take: &AssetAmount<T>,
+			fee_tier: Perbill,
+		) -> DispatchResult {
+			let pool_id = AssetIdPair::<T>::new(give.asset_id, take.asset_id)?;
+			Pools::<T>::try_mutate(&pool_id, fee_tier, |pool| -> DispatchResult {
+				let pool = pool.as_mut().ok_or(Error::<T>::PoolDoesntExists)?;
+				Self::update_price_cumulatives(pool)?;
+				let (give_to, take_from) = Self::get_swap_assets(pool, give.asset_id);
+				give_to.balance = give_to.balance.ensure_add(give.balance)?;
+				take_from.balance = take_from.balance.ensure_sub(take.balance)?;
+				Ok(())
+			})
+		}
+
+		/// Accumulates the prices of `pool` over the seconds elapsed since it was last touched and
+		/// records an observation of the price cumulatives. Must be called before the reserves of
+		/// the pool change, it only has an effect on the first touch of the pool in a block.
+		fn update_price_cumulatives(pool: &mut LiquidityPool<T>) -> DispatchResult {
+			let now = T::Time::now().as_secs();
+			let elapsed = now.saturating_sub(pool.last_timestamp);
+			if elapsed == 0 {
+				return Ok(());
+			}
+
+			let (x, y) = (&pool.asset_amounts.amount_x, &pool.asset_amounts.amount_y);
+			if !x.balance.is_zero() && !y.balance.is_zero() {
+				let price_x = Self::get_price_of_asset_in_pool(x.asset_id, pool)?.into_inner();
+				let price_y = Self::get_price_of_asset_in_pool(y.asset_id, pool)?.into_inner();
+				pool.price_x_cumulative =
+					pool.price_x_cumulative.wrapping_add(price_x.wrapping_mul(elapsed.into()));
+				pool.price_y_cumulative =
+					pool.price_y_cumulative.wrapping_add(price_y.wrapping_mul(elapsed.into()));
+			}
+			pool.last_timestamp = now;
+
+			let observation = Observation {
+				timestamp: now,
+				price_x_cumulative: pool.price_x_cumulative,
+				price_y_cumulative: pool.price_y_cumulative,
+			};
+			Observations::<T>::mutate(pool.asset_amounts.id()?, pool.fee_tier, |observations| {
+				// only fails if no observations are kept at all
+				let _ = observations.force_insert_keep_left(0, observation);
+			});
+
+			Ok(())
+		}
+
+		/// Returns the time-weighted average price of `asset_id` in its pool with `other_asset_id`
+		/// at `fee_tier`, i.e. the average of what `get_asset_price` returned over the period
+		/// between the newest observations at or before `from` and `to`, both in seconds.
+		///
+		/// Returns `None` if there is no such pool, no observation at or before `from`, or both
+		/// bounds resolve to the same observation.
+		pub fn get_twap(
+			asset_id: T::DexAssetId,
+			other_asset_id: T::DexAssetId,
+			fee_tier: Perbill,
+			from: u64,
+			to: u64,
+		) -> Option<FixedU128> {
+			let pair = AssetIdPair::<T>::new(asset_id, other_asset_id).ok()?;
+			let observations = Observations::<T>::get(&pair, fee_tier);
+			let at_or_before =
+				|timestamp: u64| observations.iter().find(|o| o.timestamp <= timestamp);
+			let (start, end) = (at_or_before(from)?, at_or_before(to)?);
+			let elapsed = end.timestamp.checked_sub(start.timestamp).filter(|e| *e > 0)?;
+
+			let (start_cumulative, end_cumulative) = if asset_id == pair.asset_x_id {
+				(start.price_x_cumulative, end.price_x_cumulative)
+			} else {
+				(start.price_y_cumulative, end.price_y_cumulative)
+			};
+			Some(FixedU128::from_inner(
+				end_cumulative.wrapping_sub(start_cumulative) / u128::from(elapsed),
+			))
+		}
+
+		/// Lists all existing pools of a pair of assets, priced by a curve of [`Curve`]. Weighted
+		/// and concentrated liquidity pools aren't listed.
+		pub fn list_pools() -> Vec<PoolInfo<T::DexAssetId, AssetBalanceOf<T>>> {
+			Pools::<T>::iter_values().map(Into::into).collect()
+		}
+
+		/// Returns the LP token of the pool for the given assets at `fee_tier`, or `None` if there
+		/// is no such pool.
+		pub fn lp_token_of(
+			asset_a: T::DexAssetId,
+			asset_b: T::DexAssetId,
+			fee_tier: Perbill,
+		) -> Option<T::DexAssetId> {
+			Pools::<T>::get(AssetIdPair::<T>::new(asset_a, asset_b).ok()?, fee_tier)
+				.map(|pool| pool.lp_token_id)
 		}
 
 		fn get_swap_assets(
@@ -784,30 +5104,132 @@ pub mod pallet {
 			PALLET_ID.into_sub_account_truncating(*b"Admin!")
 		}
 
+		/// Allocates the next free LP token id, skipping ids of assets that already exist and the
+		/// native asset id.
+		///
+		/// # Errors
+		///
+		/// Returns `LpTokenIdsExhausted` if incrementing the id overflows.
+		fn allocate_lp_token_id() -> Result<T::DexAssetId, DispatchError> {
+			let mut lp_token_id = NextLpTokenId::<T>::get().unwrap_or_else(T::LpTokenIdStart::get);
+			while NativeOrAssets::<T>::asset_exists(lp_token_id) {
+				lp_token_id = Self::increment_lp_token_id(&lp_token_id)?;
+			}
+			NextLpTokenId::<T>::put(Self::increment_lp_token_id(&lp_token_id)?);
+			Ok(lp_token_id)
+		}
+
+		fn increment_lp_token_id(
+			lp_token_id: &T::DexAssetId,
+		) -> Result<T::DexAssetId, DispatchError> {
+			lp_token_id
+				.checked_add(&T::DexAssetId::one())
+				.ok_or(Error::<T>::LpTokenIdsExhausted.into())
+		}
+
 		fn new_empty_pool(
 			id_pair: AssetIdPair<T>,
 			lp_token_id: &T::DexAssetId,
+			fee_tier: Perbill,
+			curve: Curve,
 		) -> Result<LiquidityPool<T>, DispatchError> {
+			Self::create_lp_token(lp_token_id)?;
+			Ok(LiquidityPool::empty_from_pair(id_pair, lp_token_id.clone(), fee_tier, curve))
+		}
+
+		/// Creates the LP token `lp_token_id` of a new pool, with the LP token dust of
+		/// `DexParameters` as its minimum balance.
+		fn create_lp_token(lp_token_id: &T::DexAssetId) -> DispatchResult {
 			T::Fungibles::create(
 				lp_token_id.clone(),
 				Self::admin_account_id(),
 				false,
-				T::LpTokenDust::get(),
-			)?;
-			Ok(LiquidityPool::empty_from_pair(id_pair, lp_token_id.clone()))
+				DexParameters::<T>::get().lp_token_dust,
+			)
 		}
 
-		fn calculate_swap_amounts(
-			give_balance: AssetBalanceOf<T>,
-			give_to_balance: AssetBalanceOf<T>,
-			take_from_balance: AssetBalanceOf<T>,
+		/// Calculates the amount taken from `pool` for giving `amount_in` to it, with reserves
+		/// `reserve_in` of the given asset and `reserve_out` of the taken one, by the curve of the
+		/// pool.
+		fn calculate_amount_out(
+			pool: &LiquidityPool<T>,
+			amount_in: AssetBalanceOf<T>,
+			reserve_in: AssetBalanceOf<T>,
+			reserve_out: AssetBalanceOf<T>,
 		) -> Result<AssetBalanceOf<T>, DispatchError> {
-			let fee_pct = T::FeePct::get();
-			let amount_in_with_fee = give_balance.ensure_sub(fee_pct * give_balance)?;
-			let numerator = take_from_balance.ensure_mul(amount_in_with_fee)?;
-			let denominator = give_to_balance.ensure_add(amount_in_with_fee)?;
+			Self::with_pricing_curve(pool, |curve| {
+				curve.amount_out(amount_in, reserve_in, reserve_out, pool.fee_tier)
+			})
+			.map_err(Into::into)
+		}
 
-			numerator.ensure_div(denominator).map_err(Into::into)
+		/// Calculates the amount to give to `pool` for taking `amount_out` from it, with reserves
+		/// `reserve_in` of the given asset and `reserve_out` of the taken one, by the curve of the
+		/// pool.
+		///
+		/// `amount_out` must be less than `reserve_out`.
+		fn calculate_amount_in(
+			pool: &LiquidityPool<T>,
+			amount_out: AssetBalanceOf<T>,
+			reserve_in: AssetBalanceOf<T>,
+			reserve_out: AssetBalanceOf<T>,
+		) -> Result<AssetBalanceOf<T>, DispatchError> {
+			Self::with_pricing_curve(pool, |curve| {
+				curve.amount_in(amount_out, reserve_in, reserve_out, pool.fee_tier)
+			})
+			.map_err(Into::into)
 		}
 	}
 }
+
+sp_api::decl_runtime_apis! {
+	/// Read-only access to DEX prices and pool state, so that clients can quote swaps without
+	/// submitting and paying for a `get_asset_price` extrinsic.
+	pub trait DexApi<AssetId, Balance>
+	where
+		AssetId: Codec,
+		Balance: Codec,
+	{
+		/// Amount of `asset_out` received for giving exactly `amount_in` of `asset_in`, in the pool
+		/// of the assets at the fee tier taking the most out of it.
+		fn quote_exact_in(
+			asset_in: AssetId,
+			asset_out: AssetId,
+			amount_in: Balance,
+		) -> Option<Balance>;
+
+		/// Amount of `asset_in` required to receive exactly `amount_out` of `asset_out`, in the pool
+		/// of the assets at the fee tier requiring the least to be given to it.
+		fn quote_exact_out(
+			asset_in: AssetId,
+			asset_out: AssetId,
+			amount_out: Balance,
+		) -> Option<Balance>;
+
+		/// Reserves of the pool for the given assets at `fee_tier`, in the order the assets were
+		/// passed in.
+		fn get_reserves(
+			asset_a: AssetId,
+			asset_b: AssetId,
+			fee_tier: Perbill,
+		) -> Option<(Balance, Balance)>;
+
+		/// All existing pools of a pair of assets, priced by a curve of [`Curve`]. Weighted and
+		/// concentrated liquidity pools, which `PoolInfo` can't describe, aren't listed.
+		fn list_pools() -> Vec<PoolInfo<AssetId, Balance>>;
+
+		/// LP token of the pool for the given assets at `fee_tier`.
+		fn lp_token_of(asset_a: AssetId, asset_b: AssetId, fee_tier: Perbill) -> Option<AssetId>;
+
+		/// Time-weighted average price of `asset_id` in its pool with `other_asset_id` at
+		/// `fee_tier`, between the newest observations at or before `from` and `to`, in unix
+		/// seconds.
+		fn get_twap(
+			asset_id: AssetId,
+			other_asset_id: AssetId,
+			fee_tier: Perbill,
+			from: u64,
+			to: u64,
+		) -> Option<FixedU128>;
+	}
+}
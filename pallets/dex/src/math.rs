@@ -0,0 +1,1041 @@
+//! Pricing and share math of the pools.
+//!
+//! Products of two balances are computed over `U256`, so that they can't overflow for any pair of
+//! `u128` balances, and every result is rounded in the direction that favours the pool: amounts
+//! paid out of it and LP tokens minted for it are rounded down, fees are rounded up.
+//!
+//! The StableSwap invariant of pegged pools is solved with Newton's method over `U512`, as in
+//! Curve's contracts, where its cube of `D` fits for any pair of `u128` reserves.
+//!
+//! The prices of concentrated liquidity pools are kept as their square roots in Q64.64 fixed
+//! point, as in Uniswap V3 where they are in Q64.96, with ticks limited so that they fit 96 bits
+//! and products of a square root with a liquidity fit `U512`.
+//!
+//! The powers of the weighted constant product are computed as `e^(y * ln(x))` in fixed point
+//! with 18 decimals, and rounded by their error bound in the direction that favours the pool, as
+//! in Balancer's `LogExpMath`.
+
+use sp_core::{U256, U512};
+use sp_runtime::traits::{AtLeast32BitUnsigned, CheckedSub};
+use sp_runtime::{ArithmeticError, FixedPointNumber, FixedU128, PerThing, Perbill};
+
+/// The number of assets in a pool, `n` in the StableSwap invariant.
+const N_ASSETS: u32 = 2;
+
+/// The most iterations of Newton's method run to solve the StableSwap invariant, as in Curve's
+/// contracts. It converges within a few for any reserves a swap can leave.
+const MAX_ITERATIONS: u32 = 255;
+
+/// `1` in the fixed point representation of logarithms and powers, `10^18`.
+const FIXED_ONE: i128 = 1_000_000_000_000_000_000;
+
+/// `ln(2)`, scaled by [`FIXED_ONE`].
+const LN_2: i128 = 693_147_180_559_945_309;
+
+/// The largest power of two [`exp`] shifts its result by, so that it fits `U256`.
+const MAX_EXP_SHIFT: i128 = 192;
+
+/// The relative error bound [`pow`] rounds its result by, `10^-14` scaled by [`FIXED_ONE`]. The
+/// error of the series is below `10^-17`, and grows with the exponent, at most 99 between the
+/// weights of a pool.
+const MAX_POW_RELATIVE_ERROR: u128 = 10_000;
+
+/// The absolute error bound [`pow`] rounds its result by on top of the relative one, in units of
+/// its last digit, for powers small enough to be truncated by the fixed point representation.
+const MAX_POW_ABSOLUTE_ERROR: u128 = 10;
+
+/// The highest tick of a concentrated liquidity pool, at which the square root of its price is
+/// about `2^32`, within 96 bits in Q64.64.
+pub const MAX_TICK: i32 = 443_636;
+
+/// The lowest tick of a concentrated liquidity pool, at which its price is the inverse of that at
+/// [`MAX_TICK`].
+pub const MIN_TICK: i32 = -MAX_TICK;
+
+/// The square root of the price at [`MIN_TICK`], in Q64.64.
+pub const MIN_SQRT_PRICE: u128 = 4_295_048_017;
+
+/// The square root of the price at [`MAX_TICK`], in Q64.64.
+pub const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_062;
+
+/// The number of fractional bits of the Q64.64 square roots of prices and fee growths.
+const RESOLUTION: usize = 64;
+
+/// `sqrt(1.0001)^-(2^k)` in Q128, for `k` from 0 to 18, the bits of the ticks from
+/// [`MIN_TICK`] to [`MAX_TICK`], as in Uniswap V3's `TickMath`.
+const TICK_RATIOS: [u128; 19] = [
+	0xfffcb933bd6fad37aa2d162d1a594001,
+	0xfff97272373d413259a46990580e213a,
+	0xfff2e50f5f656932ef12357cf3c7fdcc,
+	0xffe5caca7e10e4e61c3624eaa0941cd0,
+	0xffcb9843d60f6159c9db58835c926644,
+	0xff973b41fa98c081472e6896dfb254c0,
+	0xff2ea16466c96a3843ec78b326b52861,
+	0xfe5dee046a99a2a811c461f1969c3053,
+	0xfcbe86c7900a88aedcffc83b479aa3a4,
+	0xf987a7253ac413176f2b074cf7815e54,
+	0xf3392b0822b70005940c7a398e4b70f3,
+	0xe7159475a2c29b7443b29c7fa6e889d9,
+	0xd097f3bdfd2022b8845ad8f792aa5825,
+	0xa9f746462d870fdf8a65dc1f90e061e5,
+	0x70d869a156d2a1b890bb3df62baf32f7,
+	0x31be135f97d08fd981231505542fcfa6,
+	0x9aa508b5b7a84e1c677de54f3e99bc9,
+	0x5d6af8dedb81196699c329225ee604,
+	0x2216e584f5fa1ea926041bedfe98,
+];
+
+/// The direction to round the result of a division in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+	/// Towards zero.
+	Down,
+	/// Away from zero.
+	Up,
+}
+
+/// Returns `a * b / c`, rounded in the direction of `rounding`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if `c` is zero, or `ArithmeticError::Overflow` if
+/// the result doesn't fit the balance type.
+pub fn mul_div<B: AtLeast32BitUnsigned>(
+	a: B,
+	b: B,
+	c: B,
+	rounding: Rounding,
+) -> Result<B, ArithmeticError> {
+	from_u256(div(product(a, b)?, to_u256(c)?, rounding)?)
+}
+
+/// Returns `sqrt(a * b)`, rounded down.
+pub fn sqrt_of_product<B: AtLeast32BitUnsigned>(a: B, b: B) -> Result<B, ArithmeticError> {
+	from_u256(product(a, b)?.integer_sqrt())
+}
+
+/// Returns the product of two balances.
+pub fn product<B: AtLeast32BitUnsigned>(a: B, b: B) -> Result<U256, ArithmeticError> {
+	// both factors fit in 128 bits, so their product can't overflow 256
+	Ok(to_u256(a)? * to_u256(b)?)
+}
+
+/// Returns the amount taken out of a pool with reserves `reserve_in` and `reserve_out` for
+/// giving `amount_in` to it, of which `fee` is kept by the pool.
+///
+/// The fee is rounded up and the amount taken down.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if both `reserve_in` and the amount given after the
+/// fee are zero.
+pub fn get_amount_out<B: AtLeast32BitUnsigned + Copy>(
+	amount_in: B,
+	reserve_in: B,
+	reserve_out: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let amount_in_less_fee = amount_in
+		.checked_sub(&fee.mul_ceil(amount_in))
+		.ok_or(ArithmeticError::Underflow)?;
+	let numerator = product(reserve_out, amount_in_less_fee)?;
+	let denominator = to_u256(reserve_in)? + to_u256(amount_in_less_fee)?;
+	from_u256(div(numerator, denominator, Rounding::Down)?)
+}
+
+/// Returns the amount to give to a pool with reserves `reserve_in` and `reserve_out` to take
+/// `amount_out` from it, of which `fee` is kept by the pool:
+///
+/// `reserve_in * amount_out / ((reserve_out - amount_out) * (1 - fee))`
+///
+/// The amount given is rounded up, so what is left of it after the fee never grows the pool by
+/// less than the exact amount worth `amount_out`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Underflow` if `amount_out` is more than `reserve_out`, or
+/// `ArithmeticError::DivisionByZero` if it is all of it or `fee` is the whole amount given.
+pub fn get_amount_in<B: AtLeast32BitUnsigned + Copy>(
+	amount_out: B,
+	reserve_in: B,
+	reserve_out: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let reserve_out_left =
+		reserve_out.checked_sub(&amount_out).ok_or(ArithmeticError::Underflow)?;
+	// the numerator scaled by the fee's accuracy can overflow 256 bits
+	let numerator = product(reserve_in, amount_out)?.full_mul(U256::from(Perbill::ACCURACY));
+	let denominator =
+		U512::from(to_u256(reserve_out_left)? * U256::from(fee.left_from_one().deconstruct()));
+	if denominator.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let (quotient, remainder) = numerator.div_mod(denominator);
+	let amount_in = if remainder.is_zero() { quotient } else { quotient + U512::one() };
+	from_u256(U256::try_from(amount_in).map_err(|_| ArithmeticError::Overflow)?)
+}
+
+/// Returns the share of `amount_in` to swap in a constant product pool with reserve `reserve_in`
+/// of the given asset, of which `fee` is kept by the pool, for the rest of `amount_in` and the
+/// amount taken for it to be at the ratio of the reserves after the swap:
+///
+/// `(sqrt((reserve_in * (2 - fee))^2 + 4 * (1 - fee) * amount_in * reserve_in) - reserve_in * (2 - fee)) / (2 * (1 - fee))`
+///
+/// The share is rounded down.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if `fee` is the whole amount given.
+pub fn zap_swap_amount<B: AtLeast32BitUnsigned + Copy>(
+	amount_in: B,
+	reserve_in: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let one = U512::from(Perbill::ACCURACY);
+	let left = U512::from(fee.left_from_one().deconstruct());
+	if left.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let reserve_in = to_u512(reserve_in)?;
+	// every term is scaled by the square of the fee's accuracy, which fits `U512` for any pair of
+	// `u128` balances
+	let b = reserve_in * (one + left);
+	let discriminant = b * b + U512::from(4) * left * one * to_u512(amount_in)? * reserve_in;
+	from_u512((discriminant.integer_sqrt() - b) / (U512::from(2) * left))
+}
+
+/// Returns the amount taken out of a constant sum pool for giving `amount_in` to it, of which
+/// `fee` is kept by the pool: what is left of it after the fee, one for one whatever the
+/// reserves. The fee is rounded up.
+pub fn constant_sum_amount_out<B: AtLeast32BitUnsigned + Copy>(
+	amount_in: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	amount_in
+		.checked_sub(&fee.mul_ceil(amount_in))
+		.ok_or(ArithmeticError::Underflow)
+}
+
+/// Returns the amount to give to a constant sum pool to take `amount_out` from it, of which `fee`
+/// is kept by the pool, `amount_out / (1 - fee)`, rounded up.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if `fee` is the whole amount given.
+pub fn constant_sum_amount_in<B: AtLeast32BitUnsigned + Copy>(
+	amount_out: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let numerator = to_u256(amount_out)? * U256::from(Perbill::ACCURACY);
+	let denominator = U256::from(fee.left_from_one().deconstruct());
+	from_u256(div(numerator, denominator, Rounding::Up)?)
+}
+
+/// Returns the LP tokens to mint for the protocol's `share` of the growth of the square root of
+/// the product of the reserves from `root_k_last` to `root_k`, for a pool with `total` LP tokens:
+///
+/// `total * (root_k - root_k_last) / (root_k * (1 - share) / share + root_k_last)`
+///
+/// The denominator is rounded up and the result down, so the protocol never dilutes the other
+/// liquidity providers by more than its share.
+pub fn protocol_fee<B: AtLeast32BitUnsigned + Copy>(
+	total: B,
+	root_k: B,
+	root_k_last: B,
+	share: Perbill,
+) -> Result<B, ArithmeticError> {
+	if share.is_zero() || root_k <= root_k_last {
+		return Ok(B::zero());
+	}
+
+	let root_k_over_share = div(
+		to_u256(root_k)? * U256::from(share.left_from_one().deconstruct()),
+		U256::from(share.deconstruct()),
+		Rounding::Up,
+	)?;
+	let denominator = root_k_over_share
+		.checked_add(to_u256(root_k_last)?)
+		.ok_or(ArithmeticError::Overflow)?;
+	from_u256(div(product(total, root_k - root_k_last)?, denominator, Rounding::Down)?)
+}
+
+/// Returns the StableSwap invariant `D` of the reserves `x` and `y` at the amplification
+/// coefficient `amplification`, rounded down. `D` is the total of the reserves when they are
+/// equal, and in general the solution of
+///
+/// `A * n^n * (x + y) + D = A * n^n * D + D^(n + 1) / (n^n * x * y)`
+///
+/// with `n = 2`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if only one of the reserves is zero, or
+/// `ArithmeticError::Overflow` if Newton's method doesn't converge.
+pub fn stable_swap_d<B: AtLeast32BitUnsigned>(
+	x: B,
+	y: B,
+	amplification: u32,
+) -> Result<B, ArithmeticError> {
+	from_u512(compute_d(to_u512(x)?, to_u512(y)?, ann(amplification))?)
+}
+
+/// Returns the amount taken out of a StableSwap pool with reserves `reserve_in` and
+/// `reserve_out` at the amplification coefficient `amplification` for giving `amount_in` to it,
+/// of which `fee` is kept by the pool.
+///
+/// The fee is rounded up, and the amount taken is one less than what keeps `D` unchanged, which
+/// covers the error of Newton's method. Returns zero if either reserve is zero.
+pub fn stable_swap_amount_out<B: AtLeast32BitUnsigned + Copy>(
+	amount_in: B,
+	reserve_in: B,
+	reserve_out: B,
+	fee: Perbill,
+	amplification: u32,
+) -> Result<B, ArithmeticError> {
+	if reserve_in.is_zero() || reserve_out.is_zero() {
+		return Ok(B::zero());
+	}
+	let amount_in_less_fee = amount_in
+		.checked_sub(&fee.mul_ceil(amount_in))
+		.ok_or(ArithmeticError::Underflow)?;
+
+	let ann = ann(amplification);
+	let (reserve_in, reserve_out) = (to_u512(reserve_in)?, to_u512(reserve_out)?);
+	let d = compute_d(reserve_in, reserve_out, ann)?;
+	let reserve_out_left = compute_y(reserve_in + to_u512(amount_in_less_fee)?, d, ann)?;
+	from_u512(reserve_out.saturating_sub(reserve_out_left).saturating_sub(U512::one()))
+}
+
+/// Returns the amount to give to a StableSwap pool with reserves `reserve_in` and `reserve_out`
+/// at the amplification coefficient `amplification` to take `amount_out` from it, of which `fee`
+/// is kept by the pool.
+///
+/// What is left of the amount after the fee is one more than what keeps `D` unchanged, and the
+/// amount is rounded up.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Underflow` if `amount_out` is more than `reserve_out`, or
+/// `ArithmeticError::DivisionByZero` if it is all of it, `reserve_in` is zero or `fee` is the
+/// whole amount given.
+pub fn stable_swap_amount_in<B: AtLeast32BitUnsigned + Copy>(
+	amount_out: B,
+	reserve_in: B,
+	reserve_out: B,
+	fee: Perbill,
+	amplification: u32,
+) -> Result<B, ArithmeticError> {
+	let reserve_out_left =
+		reserve_out.checked_sub(&amount_out).ok_or(ArithmeticError::Underflow)?;
+	if reserve_out_left.is_zero() || reserve_in.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+
+	let ann = ann(amplification);
+	let reserve_in = to_u512(reserve_in)?;
+	let d = compute_d(reserve_in, to_u512(reserve_out)?, ann)?;
+	let reserve_in_after = compute_y(to_u512(reserve_out_left)?, d, ann)?;
+	let amount_in_less_fee = reserve_in_after.saturating_sub(reserve_in) + U512::one();
+
+	let denominator = U512::from(fee.left_from_one().deconstruct());
+	if denominator.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let numerator = mul(amount_in_less_fee, U512::from(Perbill::ACCURACY))?;
+	let (quotient, remainder) = numerator.div_mod(denominator);
+	from_u512(if remainder.is_zero() { quotient } else { quotient + U512::one() })
+}
+
+/// Returns the price of the asset with reserve `reserve` in a StableSwap pool, in the same terms
+/// as `reserve / other_reserve` for constant product pools: the ratio of the partial
+/// derivatives of the invariant,
+///
+/// `reserve * (A * n^n * other_reserve + P) / (other_reserve * (A * n^n * reserve + P))`
+///
+/// where `P = D^(n + 1) / (n^n * reserve * other_reserve)`, rounded down. It is one at the peg,
+/// and tends to the constant product price as `A` goes to zero.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if either reserve is zero, or
+/// `ArithmeticError::Overflow` if the price doesn't fit `FixedU128`.
+pub fn stable_swap_price<B: AtLeast32BitUnsigned>(
+	reserve: B,
+	other_reserve: B,
+	amplification: u32,
+) -> Result<FixedU128, ArithmeticError> {
+	let ann = ann(amplification);
+	let (reserve, other_reserve) = (to_u512(reserve)?, to_u512(other_reserve)?);
+	let d = compute_d(reserve, other_reserve, ann)?;
+	let d_p = d_product(d, reserve, other_reserve)?;
+
+	let numerator =
+		mul(mul(reserve, add(mul(ann, other_reserve)?, d_p)?)?, U512::from(FixedU128::DIV))?;
+	let denominator = mul(other_reserve, add(mul(ann, reserve)?, d_p)?)?;
+	let price =
+		u128::try_from(div_512(numerator, denominator)?).map_err(|_| ArithmeticError::Overflow)?;
+	Ok(FixedU128::from_inner(price))
+}
+
+/// Returns `(base_numerator / base_denominator)^(exponent_numerator / exponent_denominator)`,
+/// scaled by `10^18`, rounded by its error bound in the direction of `rounding`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if either denominator is zero, or
+/// `ArithmeticError::Overflow` if the result doesn't fit `U256`.
+pub fn pow(
+	base_numerator: U256,
+	base_denominator: U256,
+	exponent_numerator: Perbill,
+	exponent_denominator: Perbill,
+	rounding: Rounding,
+) -> Result<U256, ArithmeticError> {
+	if base_denominator.is_zero() || exponent_denominator.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let one = U256::from(FIXED_ONE as u128);
+	if exponent_numerator.is_zero() || base_numerator == base_denominator {
+		return Ok(one);
+	}
+	if base_numerator.is_zero() {
+		return Ok(U256::zero());
+	}
+	if exponent_numerator == exponent_denominator {
+		let numerator = base_numerator.checked_mul(one).ok_or(ArithmeticError::Overflow)?;
+		return div(numerator, base_denominator, rounding);
+	}
+
+	let exponent = ln(base_numerator, base_denominator)?
+		.checked_mul(i128::from(exponent_numerator.deconstruct()))
+		.ok_or(ArithmeticError::Overflow)?
+		/ i128::from(exponent_denominator.deconstruct());
+	round_by_error(exp(exponent)?, rounding)
+}
+
+/// Returns the amount taken out of a weighted pool with reserves `reserve_in` and `reserve_out`,
+/// of assets weighing `weight_in` and `weight_out`, for giving `amount_in` to it, of which `fee`
+/// is kept by the pool:
+///
+/// `reserve_out * (1 - (reserve_in / (reserve_in + amount_in * (1 - fee)))^(weight_in / weight_out))`
+///
+/// The fee and the power are rounded up, and the amount taken down.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if both `reserve_in` and the amount given after the
+/// fee are zero, or `weight_out` is zero.
+pub fn weighted_amount_out<B: AtLeast32BitUnsigned + Copy>(
+	amount_in: B,
+	reserve_in: B,
+	weight_in: Perbill,
+	reserve_out: B,
+	weight_out: Perbill,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let amount_in_less_fee = amount_in
+		.checked_sub(&fee.mul_ceil(amount_in))
+		.ok_or(ArithmeticError::Underflow)?;
+	let reserve_in = to_u256(reserve_in)?;
+	let power = pow(
+		reserve_in,
+		reserve_in + to_u256(amount_in_less_fee)?,
+		weight_in,
+		weight_out,
+		Rounding::Up,
+	)?;
+	fixed_mul(reserve_out, complement(power), Rounding::Down)
+}
+
+/// Returns the amount to give to a weighted pool with reserves `reserve_in` and `reserve_out`,
+/// of assets weighing `weight_in` and `weight_out`, to take `amount_out` from it, of which `fee`
+/// is kept by the pool:
+///
+/// `reserve_in * ((reserve_out / (reserve_out - amount_out))^(weight_out / weight_in) - 1) / (1 - fee)`
+///
+/// The power and the amount given are rounded up.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Underflow` if `amount_out` is more than `reserve_out`, or
+/// `ArithmeticError::DivisionByZero` if it is all of it, `weight_in` is zero or `fee` is the
+/// whole amount given.
+pub fn weighted_amount_in<B: AtLeast32BitUnsigned + Copy>(
+	amount_out: B,
+	reserve_in: B,
+	weight_in: Perbill,
+	reserve_out: B,
+	weight_out: Perbill,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let reserve_out_left =
+		reserve_out.checked_sub(&amount_out).ok_or(ArithmeticError::Underflow)?;
+	if reserve_out_left.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let power = pow(
+		to_u256(reserve_out)?,
+		to_u256(reserve_out_left)?,
+		weight_out,
+		weight_in,
+		Rounding::Up,
+	)?;
+	let amount_in_less_fee =
+		fixed_mul(reserve_in, power.saturating_sub(U256::from(FIXED_ONE as u128)), Rounding::Up)?;
+
+	let denominator = U256::from(fee.left_from_one().deconstruct());
+	from_u256(div(
+		to_u256(amount_in_less_fee)? * U256::from(Perbill::ACCURACY),
+		denominator,
+		Rounding::Up,
+	)?)
+}
+
+/// Returns the invariant of a weighted pool with the given reserves and weights, the product of
+/// each reserve to the power of its weight, rounded down. With weights adding up to one, it is
+/// their weighted geometric mean, the square root of their product for two assets weighing half
+/// each.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if any reserve is zero, or
+/// `ArithmeticError::Overflow` if the invariant doesn't fit the balance type.
+pub fn weighted_invariant<B: AtLeast32BitUnsigned>(
+	reserves: impl IntoIterator<Item = (B, Perbill)>,
+) -> Result<B, ArithmeticError> {
+	let mut exponent: i128 = 0;
+	for (reserve, weight) in reserves {
+		let ln_reserve = ln(to_u256(reserve)?, U256::one())?;
+		exponent = exponent
+			.checked_add(
+				ln_reserve * i128::from(weight.deconstruct()) / i128::from(Perbill::ACCURACY),
+			)
+			.ok_or(ArithmeticError::Overflow)?;
+	}
+	let invariant = round_by_error(exp(exponent)?, Rounding::Down)?;
+	from_u256(invariant / U256::from(FIXED_ONE as u128))
+}
+
+/// Returns the LP tokens to mint for giving `amount_in` of a single asset weighing `weight` to a
+/// weighted pool with reserve `reserve` of it and `total` LP tokens:
+///
+/// `total * ((1 + amount_in * (1 - (1 - weight) * fee) / reserve)^weight - 1)`
+///
+/// Only the share of the amount that the pool would otherwise swap into its other assets, the
+/// complement of the asset's weight, pays `fee`. The fee is rounded up, and the power and the LP
+/// tokens down.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if `reserve` is zero.
+pub fn weighted_lp_tokens_for_amount_in<B: AtLeast32BitUnsigned + Copy>(
+	amount_in: B,
+	reserve: B,
+	weight: Perbill,
+	total: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let amount_in_less_fee = amount_in
+		.checked_sub(&fee.mul_ceil(weight.left_from_one().mul_ceil(amount_in)))
+		.ok_or(ArithmeticError::Underflow)?;
+	let reserve = to_u256(reserve)?;
+	let power = pow(
+		reserve + to_u256(amount_in_less_fee)?,
+		reserve,
+		weight,
+		Perbill::one(),
+		Rounding::Down,
+	)?;
+	fixed_mul(total, power.saturating_sub(U256::from(FIXED_ONE as u128)), Rounding::Down)
+}
+
+/// Returns the amount of a single asset weighing `weight` taken out of a weighted pool with
+/// reserve `reserve` of it and `total` LP tokens for burning `lp_tokens`:
+///
+/// `reserve * (1 - (1 - lp_tokens / total)^(1 / weight)) * (1 - (1 - weight) * fee)`
+///
+/// As for [`weighted_lp_tokens_for_amount_in`], only the complement of the asset's weight pays
+/// `fee`. The power and the fee are rounded up, and the amount taken down.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Underflow` if `lp_tokens` is more than `total`, or
+/// `ArithmeticError::DivisionByZero` if `total` or `weight` is zero.
+pub fn weighted_amount_out_for_lp_tokens<B: AtLeast32BitUnsigned + Copy>(
+	lp_tokens: B,
+	reserve: B,
+	weight: Perbill,
+	total: B,
+	fee: Perbill,
+) -> Result<B, ArithmeticError> {
+	let total_left = total.checked_sub(&lp_tokens).ok_or(ArithmeticError::Underflow)?;
+	let power = pow(to_u256(total_left)?, to_u256(total)?, Perbill::one(), weight, Rounding::Up)?;
+	let amount_out = fixed_mul(reserve, complement(power), Rounding::Down)?;
+	amount_out
+		.checked_sub(&fee.mul_ceil(weight.left_from_one().mul_ceil(amount_out)))
+		.ok_or(ArithmeticError::Underflow)
+}
+
+/// A step of a swap against a concentrated liquidity pool, within a range of ticks over which its
+/// liquidity is constant.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SwapStep {
+	/// Square root of the price the step moves the pool to, in Q64.64.
+	pub sqrt_price: u128,
+	/// Amount given to the pool, less the fee.
+	pub amount_in: u128,
+	/// Amount taken from the pool.
+	pub amount_out: u128,
+	/// Fee kept by the pool.
+	pub fee: u128,
+}
+
+/// Returns the square root of the price at `tick`, `sqrt(1.0001)^tick`, in Q64.64, rounded up.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Overflow` if `tick` is outside of [`MIN_TICK`] and [`MAX_TICK`].
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128, ArithmeticError> {
+	if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+		return Err(ArithmeticError::Overflow);
+	}
+	let abs_tick = tick.unsigned_abs();
+	let mut ratio = if abs_tick & 1 != 0 { U256::from(TICK_RATIOS[0]) } else { U256::one() << 128 };
+	for (bit, tick_ratio) in TICK_RATIOS.iter().enumerate().skip(1) {
+		if abs_tick & (1 << bit) != 0 {
+			ratio = (ratio * U256::from(*tick_ratio)) >> 128;
+		}
+	}
+	if tick > 0 {
+		ratio = U256::MAX / ratio;
+	}
+	// from Q128 to Q64.64
+	from_u256(div(ratio, U256::one() << RESOLUTION, Rounding::Up)?)
+}
+
+/// Returns the tick of a concentrated liquidity pool at the square root of its price
+/// `sqrt_price`, in Q64.64: the highest tick whose square root of the price is at most
+/// `sqrt_price`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Overflow` if `sqrt_price` is outside of [`MIN_SQRT_PRICE`] and
+/// [`MAX_SQRT_PRICE`].
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32, ArithmeticError> {
+	if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+		return Err(ArithmeticError::Overflow);
+	}
+	let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+	while low < high {
+		// rounded up, so that the range shrinks whichever half is kept
+		let middle = low + (high - low + 1) / 2;
+		if sqrt_price_at_tick(middle)? <= sqrt_price {
+			low = middle;
+		} else {
+			high = middle - 1;
+		}
+	}
+	Ok(low)
+}
+
+/// Returns the amount of the first asset of a concentrated liquidity pool worth `liquidity`
+/// between the square roots of prices `sqrt_price_a` and `sqrt_price_b`, in Q64.64 and in any
+/// order:
+///
+/// `liquidity * (sqrt_price_upper - sqrt_price_lower) / (sqrt_price_upper * sqrt_price_lower)`
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if either square root is zero, or
+/// `ArithmeticError::Overflow` if the amount doesn't fit `u128`.
+pub fn amount_x_delta(
+	sqrt_price_a: u128,
+	sqrt_price_b: u128,
+	liquidity: u128,
+	rounding: Rounding,
+) -> Result<u128, ArithmeticError> {
+	let (lower, upper) = (sqrt_price_a.min(sqrt_price_b), sqrt_price_a.max(sqrt_price_b));
+	let numerator = (U512::from(liquidity) * U512::from(upper - lower)) << RESOLUTION;
+	let denominator = U512::from(lower) * U512::from(upper);
+	from_u512(div_512_rounded(numerator, denominator, rounding)?)
+}
+
+/// Returns the amount of the second asset of a concentrated liquidity pool worth `liquidity`
+/// between the square roots of prices `sqrt_price_a` and `sqrt_price_b`, in Q64.64 and in any
+/// order:
+///
+/// `liquidity * (sqrt_price_upper - sqrt_price_lower)`
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Overflow` if the amount doesn't fit `u128`.
+pub fn amount_y_delta(
+	sqrt_price_a: u128,
+	sqrt_price_b: u128,
+	liquidity: u128,
+	rounding: Rounding,
+) -> Result<u128, ArithmeticError> {
+	let difference = sqrt_price_a.abs_diff(sqrt_price_b);
+	let product = U256::from(liquidity) * U256::from(difference);
+	from_u256(div(product, U256::one() << RESOLUTION, rounding)?)
+}
+
+/// Returns the step of a swap against a concentrated liquidity pool with `liquidity` at the
+/// square root of price `sqrt_price`, towards `sqrt_price_target`, in Q64.64, of which `fee` is
+/// kept by the pool. The swap gives the first asset to the pool if the target is at or below the
+/// current price, and the second otherwise.
+///
+/// If `exact_in`, `amount_remaining` is left to give, including the fee, and the step goes as
+/// far towards the target as it pays for. Otherwise, it is left to take, and the step goes as far
+/// as needed to take it. Amounts given and fees are rounded up, amounts taken and the prices
+/// reached in the direction that favours the pool, as in Uniswap V3's `SwapMath`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::DivisionByZero` if the step can't reach the target and the pool has
+/// no liquidity or `fee` is the whole amount given, or `ArithmeticError::Overflow` if an amount
+/// doesn't fit `u128`.
+pub fn swap_step(
+	sqrt_price: u128,
+	sqrt_price_target: u128,
+	liquidity: u128,
+	amount_remaining: u128,
+	fee: Perbill,
+	exact_in: bool,
+) -> Result<SwapStep, ArithmeticError> {
+	let x_for_y = sqrt_price_target <= sqrt_price;
+	let amount_in_between = |from: u128, to: u128| {
+		if x_for_y {
+			amount_x_delta(from, to, liquidity, Rounding::Up)
+		} else {
+			amount_y_delta(from, to, liquidity, Rounding::Up)
+		}
+	};
+	let amount_out_between = |from: u128, to: u128| {
+		if x_for_y {
+			amount_y_delta(from, to, liquidity, Rounding::Down)
+		} else {
+			amount_x_delta(from, to, liquidity, Rounding::Down)
+		}
+	};
+
+	let next_sqrt_price = if exact_in {
+		let amount_less_fee = fee.left_from_one().mul_floor(amount_remaining);
+		if amount_less_fee >= amount_in_between(sqrt_price, sqrt_price_target)? {
+			sqrt_price_target
+		} else {
+			next_sqrt_price_from_input(sqrt_price, liquidity, amount_less_fee, x_for_y)?
+		}
+	} else if amount_remaining >= amount_out_between(sqrt_price, sqrt_price_target)? {
+		sqrt_price_target
+	} else {
+		next_sqrt_price_from_output(sqrt_price, liquidity, amount_remaining, x_for_y)?
+	};
+
+	let amount_in = amount_in_between(sqrt_price, next_sqrt_price)?;
+	let mut amount_out = amount_out_between(sqrt_price, next_sqrt_price)?;
+	if !exact_in {
+		amount_out = amount_out.min(amount_remaining);
+	}
+	let fee = if exact_in && next_sqrt_price != sqrt_price_target {
+		// the whole amount left is given, what isn't priced into the step pays the fee
+		amount_remaining.checked_sub(amount_in).ok_or(ArithmeticError::Underflow)?
+	} else {
+		let numerator = U256::from(amount_in) * U256::from(fee.deconstruct());
+		let denominator = U256::from(fee.left_from_one().deconstruct());
+		from_u256(div(numerator, denominator, Rounding::Up)?)?
+	};
+	Ok(SwapStep { sqrt_price: next_sqrt_price, amount_in, amount_out, fee })
+}
+
+/// Returns the growth of the fees earned per unit of liquidity for `fee` earned by `liquidity`,
+/// in Q64.64, rounded down. Fee growths wrap around on overflow, as only differences between
+/// them are meaningful.
+pub fn fee_growth(fee: u128, liquidity: u128) -> u128 {
+	if liquidity == 0 {
+		return 0;
+	}
+	((U256::from(fee) << RESOLUTION) / U256::from(liquidity)).low_u128()
+}
+
+/// Returns the fees earned by `liquidity` over a growth of `fee_growth` of the fees earned per
+/// unit of liquidity, in Q64.64, rounded down.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Overflow` if the fees don't fit `u128`.
+pub fn fees_earned(fee_growth: u128, liquidity: u128) -> Result<u128, ArithmeticError> {
+	from_u256((U256::from(fee_growth) * U256::from(liquidity)) >> RESOLUTION)
+}
+
+/// Returns the square root of the price, in Q64.64, a concentrated liquidity pool with
+/// `liquidity` at `sqrt_price` moves to for giving it `amount_in`, of the first asset if
+/// `x_for_y` and of the second otherwise, rounded towards `sqrt_price`.
+fn next_sqrt_price_from_input(
+	sqrt_price: u128,
+	liquidity: u128,
+	amount_in: u128,
+	x_for_y: bool,
+) -> Result<u128, ArithmeticError> {
+	if amount_in == 0 {
+		return Ok(sqrt_price);
+	}
+	if x_for_y {
+		// liquidity * sqrt_price / (liquidity + amount_in * sqrt_price)
+		let scaled_liquidity = U512::from(liquidity) << RESOLUTION;
+		let numerator = scaled_liquidity * U512::from(sqrt_price);
+		let denominator = scaled_liquidity + U512::from(amount_in) * U512::from(sqrt_price);
+		from_u512(div_512_rounded(numerator, denominator, Rounding::Up)?)
+	} else {
+		// sqrt_price + amount_in / liquidity
+		let quotient =
+			div(U256::from(amount_in) << RESOLUTION, U256::from(liquidity), Rounding::Down)?;
+		from_u256(quotient + U256::from(sqrt_price))
+	}
+}
+
+/// Returns the square root of the price, in Q64.64, a concentrated liquidity pool with
+/// `liquidity` at `sqrt_price` moves to for taking `amount_out` from it, of the second asset if
+/// `x_for_y` and of the first otherwise, rounded away from `sqrt_price`.
+fn next_sqrt_price_from_output(
+	sqrt_price: u128,
+	liquidity: u128,
+	amount_out: u128,
+	x_for_y: bool,
+) -> Result<u128, ArithmeticError> {
+	if x_for_y {
+		// sqrt_price - amount_out / liquidity
+		let quotient =
+			div(U256::from(amount_out) << RESOLUTION, U256::from(liquidity), Rounding::Up)?;
+		from_u256(U256::from(sqrt_price).checked_sub(quotient).ok_or(ArithmeticError::Underflow)?)
+	} else {
+		// liquidity * sqrt_price / (liquidity - amount_out * sqrt_price)
+		let scaled_liquidity = U512::from(liquidity) << RESOLUTION;
+		let numerator = scaled_liquidity * U512::from(sqrt_price);
+		let denominator = scaled_liquidity
+			.checked_sub(U512::from(amount_out) * U512::from(sqrt_price))
+			.ok_or(ArithmeticError::Underflow)?;
+		from_u512(div_512_rounded(numerator, denominator, Rounding::Up)?)
+	}
+}
+
+/// Returns `A * n^n` for the amplification coefficient `A`.
+fn ann(amplification: u32) -> U512 {
+	U512::from(amplification) * U512::from(N_ASSETS.pow(N_ASSETS))
+}
+
+/// Solves the StableSwap invariant for `D` with Newton's method, starting from the total of the
+/// reserves, from which it converges from above.
+fn compute_d(x: U512, y: U512, ann: U512) -> Result<U512, ArithmeticError> {
+	let sum = add(x, y)?;
+	if sum.is_zero() {
+		return Ok(U512::zero());
+	}
+
+	let n = U512::from(N_ASSETS);
+	let ann_less_one = ann.checked_sub(U512::one()).ok_or(ArithmeticError::Underflow)?;
+	let mut d = sum;
+	for _ in 0..MAX_ITERATIONS {
+		let d_p = d_product(d, x, y)?;
+		let previous = d;
+		// d = (A * n^n * S + n * D_P) * D / ((A * n^n - 1) * D + (n + 1) * D_P)
+		let numerator = mul(add(mul(ann, sum)?, mul(d_p, n)?)?, d)?;
+		let denominator = add(mul(ann_less_one, d)?, mul(d_p, n + U512::one())?)?;
+		d = div_512(numerator, denominator)?;
+		if has_converged(d, previous) {
+			return Ok(d);
+		}
+	}
+	Err(ArithmeticError::Overflow)
+}
+
+/// Solves the StableSwap invariant at `d` for the reserve of one asset given the reserve `x` of
+/// the other with Newton's method, i.e. the positive root of
+///
+/// `y^2 + (x + D / (A * n^n) - D) * y = D^(n + 1) / (n^n * x * A * n^n)`
+///
+/// starting from `D`, from which it converges from above.
+fn compute_y(x: U512, d: U512, ann: U512) -> Result<U512, ArithmeticError> {
+	let n = U512::from(N_ASSETS);
+	let c = div_512(mul(div_512(mul(d, d)?, mul(x, n)?)?, d)?, mul(ann, n)?)?;
+	let b = add(x, div_512(d, ann)?)?;
+
+	let mut y = d;
+	for _ in 0..MAX_ITERATIONS {
+		let previous = y;
+		let numerator = add(mul(y, y)?, c)?;
+		let denominator = add(mul(y, n)?, b)?.checked_sub(d).ok_or(ArithmeticError::Underflow)?;
+		y = div_512(numerator, denominator)?;
+		if has_converged(y, previous) {
+			return Ok(y);
+		}
+	}
+	Err(ArithmeticError::Overflow)
+}
+
+/// Returns `D^(n + 1) / (n^n * x * y)`, dividing by one reserve at a time to stay within 512
+/// bits.
+fn d_product(d: U512, x: U512, y: U512) -> Result<U512, ArithmeticError> {
+	let n = U512::from(N_ASSETS);
+	let d_p = div_512(mul(d, d)?, mul(x, n)?)?;
+	div_512(mul(d_p, d)?, mul(y, n)?)
+}
+
+fn add(a: U512, b: U512) -> Result<U512, ArithmeticError> {
+	a.checked_add(b).ok_or(ArithmeticError::Overflow)
+}
+
+fn mul(a: U512, b: U512) -> Result<U512, ArithmeticError> {
+	a.checked_mul(b).ok_or(ArithmeticError::Overflow)
+}
+
+fn div_512(numerator: U512, denominator: U512) -> Result<U512, ArithmeticError> {
+	numerator.checked_div(denominator).ok_or(ArithmeticError::DivisionByZero)
+}
+
+fn div_512_rounded(
+	numerator: U512,
+	denominator: U512,
+	rounding: Rounding,
+) -> Result<U512, ArithmeticError> {
+	if denominator.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let (quotient, remainder) = numerator.div_mod(denominator);
+	match rounding {
+		Rounding::Up if !remainder.is_zero() => Ok(quotient + U512::one()),
+		_ => Ok(quotient),
+	}
+}
+
+/// Returns whether Newton's method converged from above once it moved from `previous` to
+/// `next`: when it moved by at most one, or stopped decreasing, as rounding can make it bounce
+/// around the root by a few units for very unbalanced reserves. When it bounced, the larger value
+/// is kept, which favours the pool.
+fn has_converged(next: U512, previous: U512) -> bool {
+	next >= previous || previous - next <= U512::one()
+}
+
+/// Returns the natural logarithm of `numerator / denominator`, scaled by [`FIXED_ONE`].
+///
+/// The ratio is split into `m * 2^k`, with `m` between 1 and 2, and `ln(m)` summed as
+/// `2 * atanh((m - 1) / (m + 1))`, whose series converges within 20 terms for such `m`.
+fn ln(numerator: U256, denominator: U256) -> Result<i128, ArithmeticError> {
+	if numerator.is_zero() || denominator.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let mut k = numerator.bits() as i128 - denominator.bits() as i128;
+	let (mut numerator, denominator) = if k >= 0 {
+		(numerator, denominator << k as usize)
+	} else {
+		(numerator << (-k) as usize, denominator)
+	};
+	// both have as many bits now, so their ratio is between 1/2 and 2
+	if numerator < denominator {
+		numerator = numerator << 1;
+		k -= 1;
+	}
+	let m = numerator
+		.checked_mul(U256::from(FIXED_ONE as u128))
+		.ok_or(ArithmeticError::Overflow)?
+		/ denominator;
+	let m = m.low_u128() as i128;
+
+	let s = (m - FIXED_ONE) * FIXED_ONE / (m + FIXED_ONE);
+	let s_squared = s * s / FIXED_ONE;
+	let (mut term, mut sum, mut i) = (s, s, 1);
+	while term > 0 {
+		term = term * s_squared / FIXED_ONE;
+		i += 2;
+		sum += term / i;
+	}
+	Ok(k * LN_2 + 2 * sum)
+}
+
+/// Returns `e^x`, with `x` scaled by [`FIXED_ONE`], scaled by it too.
+///
+/// `x` is split into `k * ln(2) + r`, with `r` at most `ln(2) / 2` either way, and `e^r` summed
+/// by its Taylor series, which converges within 20 terms for such `r`, before being shifted by
+/// `k`.
+///
+/// # Errors
+///
+/// Returns `ArithmeticError::Overflow` if the result doesn't fit `U256`.
+fn exp(x: i128) -> Result<U256, ArithmeticError> {
+	let k = x.checked_add(x.signum() * (LN_2 / 2)).ok_or(ArithmeticError::Overflow)? / LN_2;
+	let r = x - k * LN_2;
+	let (mut term, mut sum, mut i) = (FIXED_ONE, FIXED_ONE, 0);
+	while term != 0 {
+		i += 1;
+		term = term * r / (FIXED_ONE * i);
+		sum += term;
+	}
+
+	let sum = U256::from(sum as u128);
+	if k > MAX_EXP_SHIFT {
+		Err(ArithmeticError::Overflow)
+	} else if k >= 0 {
+		Ok(sum << k as usize)
+	} else if -k >= 128 {
+		Ok(U256::zero())
+	} else {
+		Ok(sum >> (-k) as usize)
+	}
+}
+
+/// Moves a power by its error bound in the direction of `rounding`.
+fn round_by_error(power: U256, rounding: Rounding) -> Result<U256, ArithmeticError> {
+	let error = power.saturating_mul(U256::from(MAX_POW_RELATIVE_ERROR))
+		/ U256::from(FIXED_ONE as u128)
+		+ U256::from(MAX_POW_ABSOLUTE_ERROR);
+	match rounding {
+		Rounding::Up => power.checked_add(error).ok_or(ArithmeticError::Overflow),
+		Rounding::Down => Ok(power.saturating_sub(error)),
+	}
+}
+
+/// Returns `1 - power`, for a power of a ratio below one scaled by [`FIXED_ONE`], or zero if
+/// rounding took the power above one.
+fn complement(power: U256) -> U256 {
+	U256::from(FIXED_ONE as u128).saturating_sub(power)
+}
+
+/// Returns `value * fixed`, for `fixed` scaled by [`FIXED_ONE`], rounded in the direction of
+/// `rounding`.
+fn fixed_mul<B: AtLeast32BitUnsigned>(
+	value: B,
+	fixed: U256,
+	rounding: Rounding,
+) -> Result<B, ArithmeticError> {
+	let product = to_u256(value)?.checked_mul(fixed).ok_or(ArithmeticError::Overflow)?;
+	from_u256(div(product, U256::from(FIXED_ONE as u128), rounding)?)
+}
+
+fn div(numerator: U256, denominator: U256, rounding: Rounding) -> Result<U256, ArithmeticError> {
+	if denominator.is_zero() {
+		return Err(ArithmeticError::DivisionByZero);
+	}
+	let (quotient, remainder) = numerator.div_mod(denominator);
+	match rounding {
+		Rounding::Up if !remainder.is_zero() => Ok(quotient + U256::one()),
+		_ => Ok(quotient),
+	}
+}
+
+fn to_u256<B: AtLeast32BitUnsigned>(value: B) -> Result<U256, ArithmeticError> {
+	let value: u128 = value.try_into().map_err(|_| ArithmeticError::Overflow)?;
+	Ok(U256::from(value))
+}
+
+fn from_u256<B: AtLeast32BitUnsigned>(value: U256) -> Result<B, ArithmeticError> {
+	let value = u128::try_from(value).map_err(|_| ArithmeticError::Overflow)?;
+	B::try_from(value).map_err(|_| ArithmeticError::Overflow)
+}
+
+fn to_u512<B: AtLeast32BitUnsigned>(value: B) -> Result<U512, ArithmeticError> {
+	Ok(U512::from(to_u256(value)?))
+}
+
+fn from_u512<B: AtLeast32BitUnsigned>(value: U512) -> Result<B, ArithmeticError> {
+	from_u256(U256::try_from(value).map_err(|_| ArithmeticError::Overflow)?)
+}
@@ -0,0 +1,116 @@
+//! Storage migrations of the DEX pallet.
+
+use core::marker::PhantomData;
+
+use frame_support::traits::{Get, GetStorageVersion, OnRuntimeUpgrade, UnixTime};
+use frame_support::weights::Weight;
+use sp_runtime::traits::Zero;
+use sp_runtime::Perbill;
+use sp_std::vec::Vec;
+
+use crate::{AssetBalanceOf, Config, Curve, FeeTiers, LiquidityPool, LpTokenToPair, Pallet, Pools};
+
+/// The layout of the pallet's storage before pools were keyed by their fee tier, at storage
+/// version 0.
+pub mod v0 {
+	use codec::{Decode, Encode, MaxEncodedLen};
+	use frame_support::{storage_alias, DebugNoBound};
+	use scale_info::TypeInfo;
+
+	use crate::{AssetAmountPair, AssetBalanceOf, AssetIdPair, Config, Hasher, Pallet};
+
+	/// A pool of a pair of assets, swapping at the fee the runtime charged every pool.
+	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct LiquidityPool<T: Config> {
+		pub asset_amounts: AssetAmountPair<T>,
+		pub total_liquidity: AssetBalanceOf<T>,
+		pub lp_token_id: T::DexAssetId,
+	}
+
+	/// Pools by their pair of assets.
+	#[storage_alias]
+	pub type Pools<T: Config> = StorageMap<Pallet<T>, Hasher, AssetIdPair<T>, LiquidityPool<T>>;
+}
+
+/// Moves the pools of storage version 0 to the fee tier `FeeTier`, the fee the runtime charged
+/// them, as constant product pools without the protocol fee, and allows that fee tier.
+///
+/// The pools' LP tokens are indexed back to them, and their price cumulatives start at the
+/// time of the upgrade. Does nothing unless the pallet is at storage version 0.
+pub struct MigrateToV1<T, FeeTier>(PhantomData<(T, FeeTier)>);
+
+impl<T: Config, FeeTier: Get<Perbill>> OnRuntimeUpgrade for MigrateToV1<T, FeeTier> {
+	fn on_runtime_upgrade() -> Weight {
+		if Pallet::<T>::on_chain_storage_version() != 0 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let fee_tier = FeeTier::get();
+		let now = T::Time::now().as_secs();
+		// Both layouts share the storage prefix, so the old pools are all taken out first.
+		let pools: Vec<_> = v0::Pools::<T>::drain().collect();
+		for (pair, pool) in &pools {
+			LpTokenToPair::<T>::insert(&pool.lp_token_id, (pair.clone(), fee_tier));
+			Pools::<T>::insert(
+				pair,
+				fee_tier,
+				LiquidityPool {
+					asset_amounts: pool.asset_amounts.clone(),
+					total_liquidity: pool.total_liquidity,
+					lp_token_id: pool.lp_token_id.clone(),
+					fee_tier,
+					curve: Curve::ConstantProduct,
+					protocol_fee_on: false,
+					root_k_last: AssetBalanceOf::<T>::zero(),
+					amplification_last: 0,
+					price_x_cumulative: 0,
+					price_y_cumulative: 0,
+					last_timestamp: now,
+				},
+			);
+		}
+		FeeTiers::<T>::mutate(|fee_tiers| {
+			if let Err(index) = fee_tiers.binary_search(&fee_tier) {
+				// `FeeTiers` is added by the same upgrade, so it has room for the fee tier.
+				let _ = fee_tiers.try_insert(index, fee_tier);
+			}
+		});
+		Pallet::<T>::current_storage_version().put::<Pallet<T>>();
+
+		let migrated = pools.len() as u64;
+		T::DbWeight::get().reads_writes(migrated + 2, 3 * migrated + 2)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+		use codec::Encode;
+
+		// The old pools can only be told apart from the new ones before the upgrade.
+		let count = (Pallet::<T>::on_chain_storage_version() == 0)
+			.then(|| v0::Pools::<T>::iter_keys().count() as u32);
+		Ok(count.encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+		use codec::Decode;
+		use frame_support::ensure;
+
+		let count =
+			Option::<u32>::decode(&mut &state[..]).map_err(|_| "pool count should decode")?;
+		ensure!(
+			Pallet::<T>::on_chain_storage_version() == Pallet::<T>::current_storage_version(),
+			"storage version should be current"
+		);
+		if let Some(count) = count {
+			let fee_tier = FeeTier::get();
+			ensure!(
+				Pools::<T>::iter_keys().filter(|(_, tier)| *tier == fee_tier).count() as u32 ==
+					count,
+				"every pool should be at the fee tier"
+			);
+		}
+		Ok(())
+	}
+}
@@ -1,6 +1,9 @@
-use frame_support::pallet_prelude::Get;
-use frame_support::traits::{AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64};
-use frame_system::{EnsureRoot, EnsureSigned};
+use frame_support::traits::{
+	AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64, ConstU8, EitherOfDiverse,
+};
+use frame_support::weights::{FixedFee, IdentityFee};
+use frame_support::{ord_parameter_types, parameter_types};
+use frame_system::{EnsureRoot, EnsureSigned, EnsureSignedBy};
 use sp_core::H256;
 use sp_runtime::traits::Convert;
 use sp_runtime::{
@@ -9,7 +12,7 @@ use sp_runtime::{
 };
 
 use crate as pallet_dex;
-use crate::AssetBalanceOf;
+use crate::{AssetBalanceOf, ConstantSum, CurveResolver, PricingCurve};
 
 type Block = frame_system::mocking::MockBlock<Test>;
 type Balance = u128;
@@ -20,7 +23,9 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system,
 		Balances: pallet_balances,
+		Timestamp: pallet_timestamp,
 		Assets: pallet_assets,
+		TransactionPayment: pallet_transaction_payment,
 		Dex: pallet_dex,
 	}
 );
@@ -67,6 +72,13 @@ impl pallet_balances::Config for Test {
 	type MaxFreezes = ConstU32<10>;
 }
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
 impl pallet_assets::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type Balance = Balance;
@@ -90,22 +102,66 @@ impl pallet_assets::Config for Test {
 	type BenchmarkHelper = ();
 }
 
+impl pallet_transaction_payment::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = FixedFee<500, Balance>;
+	type LengthToFee = IdentityFee<Balance>;
+	type FeeMultiplierUpdate = ();
+}
+
 impl pallet_dex::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
 	type NativeBalance = Balances;
 	type Fungibles = Assets;
+	type AdminOrigin = EitherOfDiverse<EnsureRoot<u64>, EnsureSignedBy<Governance, u64>>;
 	type LpTokenDust = ConstU128<1>;
+	type MinimumLiquidity = MinimumLiquidity;
+	type FlashFee = FlashFee;
 	type DexAssetId = u32;
-	type FeePct = Fee1Percent;
+	type NativeAssetId = ConstU32<0>;
+	type MaxFeeTiers = ConstU32<4>;
+	type ProtocolFeeShare = ProtocolFeeShare;
+	type MaxProtocolFeeShare = MaxProtocolFeeShare;
+	type ProtocolFeeRecipient = ProtocolFeeRecipient;
 	type AssetBalancePairToRatioConverter = AssetBalancePairToRatioConverter;
+	type MaxPathLength = ConstU32<4>;
+	type Time = Timestamp;
+	type MaxObservations = ConstU32<8>;
+	type MaxAmplification = ConstU32<1_000_000>;
+	type MinAmplificationRampDuration = ConstU64<86_400>;
+	type Curves = MockCurves;
+	type MaxWeightedPoolAssets = ConstU32<8>;
+	type MaxConcentratedTicks = ConstU32<8>;
+	type MaxOrdersPerBook = ConstU32<4>;
+	type MinOrderAmount = ConstU128<1_000>;
+	type LpTokenIdStart = ConstU32<1_000>;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
 }
 
-pub struct Fee1Percent;
+pub const PROTOCOL_FEE_RECIPIENT: u64 = 100;
+/// A signed account `AdminOrigin` accepts besides root, standing in for a governance body.
+pub const GOVERNANCE: u64 = 5;
+/// The fee tier allowed at genesis, which the pools of the tests are created at.
+pub const FEE_TIER: Perbill = Perbill::from_percent(1);
+/// The id of the only curve of the runtime's own, which is the constant sum under another name.
+pub const CUSTOM_CURVE_ID: u32 = 7;
 
-impl Get<Perbill> for Fee1Percent {
-	fn get() -> Perbill {
-		Perbill::from_rational(1u32, 100u32)
-	}
+parameter_types! {
+	pub ProtocolFeeShare: Perbill = Perbill::from_percent(20);
+	pub MaxProtocolFeeShare: Perbill = Perbill::from_percent(50);
+	pub FlashFee: Perbill = Perbill::from_perthousand(5);
+	pub const ProtocolFeeRecipient: u64 = PROTOCOL_FEE_RECIPIENT;
+	// Zero by default so that LP token amounts are easy to follow, tests of the lock set it
+	pub static MinimumLiquidity: u128 = 0;
+}
+
+ord_parameter_types! {
+	pub const Governance: u64 = GOVERNANCE;
 }
 
 pub struct AssetBalancePairToRatioConverter;
@@ -118,6 +174,14 @@ impl Convert<(AssetBalanceOf<Test>, AssetBalanceOf<Test>), FixedU128>
 	}
 }
 
+pub struct MockCurves;
+
+impl CurveResolver<Balance> for MockCurves {
+	fn with_custom_curve<R>(id: u32, f: impl FnOnce(&dyn PricingCurve<Balance>) -> R) -> Option<R> {
+		(id == CUSTOM_CURVE_ID).then(|| f(&ConstantSum))
+	}
+}
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext(initial_balances: Vec<(u64, u128)>) -> sp_io::TestExternalities {
 	let mut initial_test_state =
@@ -127,5 +191,9 @@ pub fn new_test_ext(initial_balances: Vec<(u64, u128)>) -> sp_io::TestExternalit
 		.assimilate_storage(&mut initial_test_state)
 		.unwrap();
 
+	pallet_dex::GenesisConfig::<Test> { fee_tiers: vec![FEE_TIER], pools: vec![] }
+		.assimilate_storage(&mut initial_test_state)
+		.unwrap();
+
 	initial_test_state.into()
 }
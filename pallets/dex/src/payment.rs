@@ -0,0 +1,201 @@
+//! Signed extension charging transaction fees in any asset pooled with the native currency, by
+//! swapping it for the fee through the DEX.
+
+use codec::{Decode, Encode};
+use frame_support::dispatch::{DispatchInfo, PostDispatchInfo};
+use frame_support::traits::{Get, IsType};
+use frame_support::{CloneNoBound, DebugNoBound, PartialEqNoBound};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension, Zero};
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+};
+use sp_runtime::{DispatchResult, FixedPointOperand, Saturating};
+
+use crate::math::{self, Rounding};
+use crate::{AssetBalanceOf, Config, NativeOrAssets, Pallet};
+
+/// Balance of the native currency fees are charged in.
+type BalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::Balance;
+
+/// Charges the fee of a transaction, and its tip, in the native currency like
+/// [`ChargeTransactionPayment`], which it takes the place of in the runtime's signed extensions,
+/// or in `asset_id` if given.
+///
+/// The fee is paid in `asset_id` by swapping as little of it as possible for the fee in the
+/// native currency before dispatch, through the pool of the pair at the fee tier requiring the
+/// least to be given to it, after which the runtime's `OnChargeTransaction` charges it as usual.
+/// If paying the fee would leave less than the existential deposit of the native currency, the
+/// shortfall is swapped for as well, so that accounts holding none of it can pay in assets.
+/// What is refunded of the fee after dispatch is swapped back for `asset_id`, for at least its
+/// share of what the whole fee would have swapped back for before dispatch, or left in the native
+/// currency if that swap fails, so that a call moving the price can't take the refund.
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, DebugNoBound, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeDexTxPayment<T: Config + pallet_transaction_payment::Config> {
+	#[codec(compact)]
+	tip: BalanceOf<T>,
+	asset_id: Option<T::DexAssetId>,
+}
+
+impl<T: Config + pallet_transaction_payment::Config> ChargeDexTxPayment<T> {
+	/// Creates a new `ChargeDexTxPayment` charging the fee and `tip` in `asset_id`, or in the
+	/// native currency if `None`.
+	pub fn new(tip: BalanceOf<T>, asset_id: Option<T::DexAssetId>) -> Self {
+		Self { tip, asset_id }
+	}
+
+	/// Returns the asset to swap for the fee, if it is paid in another than the native
+	/// currency.
+	fn swapped_asset_id(&self) -> Option<T::DexAssetId> {
+		self.asset_id.filter(|asset_id| !NativeOrAssets::<T>::is_native(asset_id))
+	}
+
+	/// Returns the amount of the native currency to swap for so that `who` can pay `fee` and
+	/// still hold the existential deposit, which the fee is withdrawn keeping alive.
+	fn native_amount_out(who: &T::AccountId, fee: BalanceOf<T>) -> AssetBalanceOf<T>
+	where
+		BalanceOf<T>: IsType<AssetBalanceOf<T>>,
+	{
+		let native_asset_id = T::NativeAssetId::get();
+		let shortfall = NativeOrAssets::<T>::minimum_balance(native_asset_id)
+			.saturating_sub(NativeOrAssets::<T>::balance(native_asset_id, who));
+		fee.into().saturating_add(shortfall)
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> From<BalanceOf<T>> for ChargeDexTxPayment<T> {
+	/// Charges the fee and `tip` in the native currency.
+	fn from(tip: BalanceOf<T>) -> Self {
+		Self::new(tip, None)
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> Eq for ChargeDexTxPayment<T> where
+	T::DexAssetId: Eq
+{
+}
+
+impl<T> SignedExtension for ChargeDexTxPayment<T>
+where
+	T: Config + pallet_transaction_payment::Config + Send + Sync,
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	BalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand + IsType<AssetBalanceOf<T>>,
+	T::DexAssetId: Send + Sync + Eq,
+{
+	const IDENTIFIER: &'static str = "ChargeDexTxPayment";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	/// What [`ChargeTransactionPayment`] charged, and the asset swapped for it with what the fee
+	/// would have swapped back for before dispatch, if any.
+	type Pre = (
+		<ChargeTransactionPayment<T> as SignedExtension>::Pre,
+		Option<(T::DexAssetId, AssetBalanceOf<T>)>,
+	);
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+		let asset_id = match self.swapped_asset_id() {
+			Some(asset_id) if !fee.is_zero() => asset_id,
+			_ => {
+				return ChargeTransactionPayment::<T>::from(self.tip).validate(who, call, info, len)
+			},
+		};
+
+		// the native fee is only withdrawn on dispatch, once swapped for, so only the asset can
+		// be checked here, which the swap leaves at least the minimum balance of
+		let amount_out = Self::native_amount_out(who, fee);
+		let amount_in = Pallet::<T>::quote_exact_out(asset_id, T::NativeAssetId::get(), amount_out)
+			.ok_or(InvalidTransaction::Payment)?;
+		let required = amount_in.saturating_add(NativeOrAssets::<T>::minimum_balance(asset_id));
+		if NativeOrAssets::<T>::balance(asset_id, who) < required {
+			return Err(InvalidTransaction::Payment.into());
+		}
+
+		let priority = ChargeTransactionPayment::<T>::get_priority(info, len, self.tip, fee);
+		Ok(ValidTransaction { priority, ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+		let mut swapped = None;
+		if let Some(asset_id) = self.swapped_asset_id().filter(|_| !fee.is_zero()) {
+			// quoted before the swap, which only makes swapping back cheaper
+			let fee_swapped_back =
+				Pallet::<T>::quote_exact_in(T::NativeAssetId::get(), asset_id, fee.into());
+			let max_amount_in = NativeOrAssets::<T>::balance(asset_id, who);
+			Pallet::<T>::swap_exact_out(
+				who.clone(),
+				asset_id,
+				T::NativeAssetId::get(),
+				Self::native_amount_out(who, fee),
+				max_amount_in,
+			)
+			.map_err(|_| InvalidTransaction::Payment)?;
+			swapped = fee_swapped_back.map(|amount| (asset_id, amount));
+		}
+
+		let pre =
+			ChargeTransactionPayment::<T>::from(self.tip).pre_dispatch(who, call, info, len)?;
+		Ok((pre, swapped))
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let (pre, swapped) = match pre {
+			Some((pre, swapped)) => (Some(pre), swapped),
+			None => (None, None),
+		};
+		let charged = pre.as_ref().map(|(tip, who, _)| (*tip, who.clone()));
+		ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)?;
+
+		if let (Some((asset_id, fee_swapped_back)), Some((tip, who))) = (swapped, charged) {
+			let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, tip);
+			let actual_fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+				len as u32, info, post_info, tip,
+			);
+			let refund = fee.saturating_sub(actual_fee);
+			if !refund.is_zero() {
+				// the refund is left in the native currency if it can't be swapped back for its
+				// share of what the fee would have been before dispatch, as the transaction was
+				// already paid for
+				let amount_out_min =
+					math::mul_div(fee_swapped_back, refund.into(), fee.into(), Rounding::Down);
+				if let Ok(amount_out_min) = amount_out_min {
+					let _ = Pallet::<T>::swap_exact_in(
+						who,
+						T::NativeAssetId::get(),
+						asset_id,
+						refund.into(),
+						amount_out_min,
+					);
+				}
+			}
+		}
+		Ok(())
+	}
+}
@@ -1,11 +1,13 @@
 mod tests {
 	use codec::Compact;
 	use frame_support::assert_ok;
-	use frame_support::pallet_prelude::Get;
 	use sp_io::TestExternalities;
 	use sp_runtime::traits::{EnsureAdd, EnsureDiv, EnsureMul, EnsureSub};
+	use sp_runtime::PerThing;
 
-	use crate::{mock::*, AssetAmount, AssetAmountPair, AssetIdPair, Config, LiquidityPool, Pools};
+	use crate::{
+		mock::*, AssetAmount, AssetAmountPair, AssetIdPair, Config, Curve, LiquidityPool, Pools,
+	};
 
 	type TestFungibles = <Test as Config>::Fungibles;
 
@@ -16,24 +18,30 @@ mod tests {
 
 	const ASSET_X: u32 = 3;
 	const ASSET_Y: u32 = 4;
-	const LP_TOKEN_ID: u32 = 2;
+	const ASSET_Z: u32 = 5;
+	const NATIVE: u32 = 0;
+	const LP_TOKEN_ID: u32 = 1_000;
 
 	const EXISTENTIAL_DEPOSIT: u128 = 1;
 	const TEN_K: u128 = 10_000;
 	const TEN_M: u128 = 10_000_000;
 	const TEN_B: u128 = 10_000_000_000;
 	const X_Y_ID: AssetIdPair<Test> = AssetIdPair { asset_x_id: ASSET_X, asset_y_id: ASSET_Y };
+	const Y_Z_ID: AssetIdPair<Test> = AssetIdPair { asset_x_id: ASSET_Y, asset_y_id: ASSET_Z };
 
 	mod unit_tests {
 		mod pool_tests {
 			use frame_support::{assert_noop, assert_ok};
 
-			use crate::mock::{Dex, RuntimeOrigin, System, Test};
+			use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
 			use crate::tests::tests::{
 				create_asset, create_empty_pool, init_test_ext, TestFungibles, ALICE, ASSET_X,
-				ASSET_Y, LP_TOKEN_ID, X_Y_ID,
+				ASSET_Y, ASSET_Z, LP_TOKEN_ID, X_Y_ID, Y_Z_ID,
+			};
+			use crate::{
+				AssetAmountPair, Curve, Error, Event, LiquidityPool, LpTokenToPair, NextLpTokenId,
+				Pools,
 			};
-			use crate::{AssetAmountPair, Error, Event, LiquidityPool, Pools};
 
 			#[test]
 			fn create_pool_should_work() {
@@ -49,23 +57,108 @@ mod tests {
 						RuntimeOrigin::signed(ALICE),
 						ASSET_X,
 						ASSET_Y,
-						LP_TOKEN_ID
+						FEE_TIER,
+						None
 					));
-					let created_pool = Pools::get(&X_Y_ID.clone());
+					let created_pool = Pools::get(&X_Y_ID.clone(), FEE_TIER);
 					let expected_pool = LiquidityPool {
 						asset_amounts: AssetAmountPair::<Test>::empty(X_Y_ID.clone()),
 						total_liquidity: 0,
 						lp_token_id: LP_TOKEN_ID,
+						fee_tier: FEE_TIER,
+						curve: Curve::ConstantProduct,
+						protocol_fee_on: false,
+						root_k_last: 0,
+						amplification_last: 0,
+						price_x_cumulative: 0,
+						price_y_cumulative: 0,
+						last_timestamp: 0,
 					};
 					assert!(
 						matches!(created_pool, Some(pool) if pool == expected_pool),
 						"Pool should be created and empty but was {:?}",
-						Pools::get(&X_Y_ID)
+						Pools::get(&X_Y_ID, FEE_TIER)
 					);
 					System::assert_last_event(
-						Event::LpTokenCreated { lp_token_id: LP_TOKEN_ID }.into(),
+						Event::LpTokenCreated { lp_token_id: LP_TOKEN_ID, fee_tier: FEE_TIER }
+							.into(),
 					);
 					assert_eq!(TestFungibles::balance(LP_TOKEN_ID, &Dex::dex_account_id()), 0);
+
+					// and the lp token should map back to the pool's pair
+					assert_eq!(LpTokenToPair::<Test>::get(LP_TOKEN_ID), Some((X_Y_ID, FEE_TIER)));
+				});
+			}
+
+			#[test]
+			fn create_pool_should_allocate_lp_token_ids_in_order() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_asset(ASSET_Z);
+
+					// each created pool should get the next lp token id
+					assert_ok!(Dex::create_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Y,
+						FEE_TIER,
+						None
+					));
+					assert_ok!(Dex::create_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_Z,
+						ASSET_Y,
+						FEE_TIER,
+						None
+					));
+					assert_eq!(
+						Pools::<Test>::get(&X_Y_ID, FEE_TIER).unwrap().lp_token_id,
+						LP_TOKEN_ID
+					);
+					assert_eq!(
+						Pools::<Test>::get(&Y_Z_ID, FEE_TIER).unwrap().lp_token_id,
+						LP_TOKEN_ID + 1
+					);
+					assert_eq!(LpTokenToPair::<Test>::get(LP_TOKEN_ID), Some((X_Y_ID, FEE_TIER)));
+					assert_eq!(
+						LpTokenToPair::<Test>::get(LP_TOKEN_ID + 1),
+						Some((Y_Z_ID, FEE_TIER))
+					);
+					assert_eq!(NextLpTokenId::<Test>::get(), Some(LP_TOKEN_ID + 2));
+				});
+			}
+
+			#[test]
+			fn create_pool_should_skip_lp_token_ids_of_existing_assets() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets, one of them taking the first lp token id
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_asset(LP_TOKEN_ID);
+
+					// the pool should get the first id not taken by an asset
+					assert_ok!(Dex::create_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Y,
+						FEE_TIER,
+						None
+					));
+					assert_eq!(
+						Pools::<Test>::get(&X_Y_ID, FEE_TIER).unwrap().lp_token_id,
+						LP_TOKEN_ID + 1
+					);
+					System::assert_last_event(
+						Event::LpTokenCreated { lp_token_id: LP_TOKEN_ID + 1, fee_tier: FEE_TIER }
+							.into(),
+					);
+					assert_eq!(LpTokenToPair::<Test>::get(LP_TOKEN_ID), None);
 				});
 			}
 
@@ -80,7 +173,7 @@ mod tests {
 
 					// pool creation should fail with invalid pair error
 					assert_noop!(
-						Dex::create_pool(RuntimeOrigin::signed(ALICE), x, y, LP_TOKEN_ID),
+						Dex::create_pool(RuntimeOrigin::signed(ALICE), x, y, FEE_TIER, None),
 						Error::<Test>::InvalidPair
 					);
 				});
@@ -104,7 +197,8 @@ mod tests {
 							RuntimeOrigin::signed(ALICE),
 							ASSET_X,
 							ASSET_Y,
-							LP_TOKEN_ID
+							FEE_TIER,
+							None
 						),
 						Error::<Test>::PoolAlreadyExists
 					);
@@ -129,7 +223,8 @@ mod tests {
 							RuntimeOrigin::signed(ALICE),
 							ASSET_Y,
 							ASSET_X,
-							LP_TOKEN_ID
+							FEE_TIER,
+							None
 						),
 						Error::<Test>::PoolAlreadyExists
 					);
@@ -140,14 +235,14 @@ mod tests {
 		mod provide_liquidity_tests {
 			use frame_support::{assert_noop, assert_ok};
 
-			use crate::mock::{Dex, RuntimeOrigin, System, Test};
+			use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
 			use crate::tests::tests::{
 				assert_account_has, create_asset, create_asset_amount_pair,
 				create_bad_asset_amount_pair, create_balanced_pool, create_empty_pool,
-				init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, BOB, EXISTENTIAL_DEPOSIT,
-				LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+				init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, ASSET_Z, BOB,
+				EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID, Y_Z_ID,
 			};
-			use crate::{Error, Event};
+			use crate::{Error, Event, Pools};
 
 			#[test]
 			fn provide_liquidity_works() {
@@ -169,7 +264,8 @@ mod tests {
 					assert_ok!(Dex::provide_liquidity(
 						RuntimeOrigin::signed(ALICE),
 						lp,
-						LP_TOKEN_ID
+						FEE_TIER,
+						None
 					));
 
 					// liquidity assets should be transferred to dex account,
@@ -185,6 +281,7 @@ mod tests {
 							who: ALICE,
 							provided: lp.clone(),
 							lp_tokens: TEN_M,
+							fee_tier: FEE_TIER,
 						}
 						.into(),
 					);
@@ -216,7 +313,8 @@ mod tests {
 					assert_ok!(Dex::provide_liquidity(
 						RuntimeOrigin::signed(ALICE),
 						alice_lp,
-						LP_TOKEN_ID
+						FEE_TIER,
+						None
 					));
 
 					// liquidity assets should be transferred to dex account,
@@ -232,6 +330,7 @@ mod tests {
 							who: ALICE,
 							provided: alice_lp.clone(),
 							lp_tokens: TEN_M,
+							fee_tier: FEE_TIER,
 						}
 						.into(),
 					);
@@ -240,7 +339,8 @@ mod tests {
 					assert_ok!(Dex::provide_liquidity(
 						RuntimeOrigin::signed(BOB),
 						bob_lp,
-						LP_TOKEN_ID
+						FEE_TIER,
+						None
 					));
 
 					// liquidity assets should be transferred to dex account,
@@ -256,6 +356,7 @@ mod tests {
 							who: BOB,
 							provided: bob_lp.clone(),
 							lp_tokens: TEN_K,
+							fee_tier: FEE_TIER,
 						}
 						.into(),
 					);
@@ -280,12 +381,85 @@ mod tests {
 
 					// provide liquidity should fail and immediate arbitrage error should be returned,
 					assert_noop!(
-						Dex::provide_liquidity(RuntimeOrigin::signed(ALICE), bad_lp, LP_TOKEN_ID),
+						Dex::provide_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							bad_lp,
+							FEE_TIER,
+							None
+						),
 						Error::<Test>::ImmediateArbitrage
 					);
 				});
 			}
 
+			#[test]
+			fn provide_liquidity_mints_only_the_pools_own_lp_token() {
+				let lp = create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y);
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given two created pools, each with its own lp token
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_asset(ASSET_Z);
+					assert_ok!(Dex::create_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Y,
+						FEE_TIER,
+						None
+					));
+					assert_ok!(Dex::create_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_Y,
+						ASSET_Z,
+						FEE_TIER,
+						None
+					));
+					let x_y_lp_token_id =
+						Pools::<Test>::get(&X_Y_ID, FEE_TIER).unwrap().lp_token_id;
+					let y_z_lp_token_id =
+						Pools::<Test>::get(&Y_Z_ID, FEE_TIER).unwrap().lp_token_id;
+					assert_ne!(x_y_lp_token_id, y_z_lp_token_id);
+
+					// and assets minted to alice
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// providing liquidity to the x/y pool should pass
+					assert_ok!(Dex::provide_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						lp,
+						FEE_TIER,
+						None
+					));
+
+					// and mint only the x/y pool's lp token, never the other pool's
+					assert_account_has(ALICE, x_y_lp_token_id, TEN_M);
+					assert_account_has(ALICE, y_z_lp_token_id, 0);
+				});
+			}
+
+			#[test]
+			fn provide_liquidity_to_missing_pool_fails() {
+				let lp = create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y);
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets but no pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// provide liquidity should fail with pool doesn't exist error
+					assert_noop!(
+						Dex::provide_liquidity(RuntimeOrigin::signed(ALICE), lp, FEE_TIER, None),
+						Error::<Test>::PoolDoesntExists
+					);
+				});
+			}
+
 			#[test]
 			fn provide_insufficient_liquidity_leading_to_zero_tokens_fails() {
 				let zero_lp = create_asset_amount_pair(0, ASSET_X, ASSET_Y);
@@ -304,21 +478,245 @@ mod tests {
 
 					// provide liquidity should fail and immediate arbitrage error should be returned,
 					assert_noop!(
-						Dex::provide_liquidity(RuntimeOrigin::signed(ALICE), zero_lp, LP_TOKEN_ID),
+						Dex::provide_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							zero_lp,
+							FEE_TIER,
+							None
+						),
+						Error::<Test>::InsufficientLiquidityProvided
+					);
+				});
+			}
+		}
+
+		mod add_liquidity_tests {
+			use frame_support::{assert_noop, assert_ok};
+
+			use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+			use crate::tests::tests::{
+				assert_account_has, create_asset, create_balanced_pool, create_empty_pool,
+				create_pool, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+				EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+			};
+			use crate::{AssetAmountPair, Error, Event, Pools};
+
+			fn setup_assets() {
+				// given created assets
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				create_asset(LP_TOKEN_ID);
+
+				// and assets minted to alice
+				mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+				mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+			}
+
+			#[test]
+			fn add_liquidity_to_empty_pool_provides_desired_amounts() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given an empty pool
+					setup_assets();
+					create_empty_pool(ASSET_X, ASSET_Y);
+
+					// add liquidity should pass with any ratio,
+					assert_ok!(Dex::add_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						TEN_K,
+						4 * TEN_K,
+						TEN_K,
+						4 * TEN_K,
+						None
+					));
+
+					// the desired amounts should be transferred from alice,
+					assert_account_has(ALICE, ASSET_X, TEN_M - TEN_K + EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, ASSET_Y, TEN_M - 4 * TEN_K + EXISTENTIAL_DEPOSIT);
+
+					// and the square root of their product minted to her
+					assert_account_has(ALICE, LP_TOKEN_ID, 2 * TEN_K);
+					let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+					assert_eq!(pool.asset_amounts.amount_x.balance, TEN_K);
+					assert_eq!(pool.asset_amounts.amount_y.balance, 4 * TEN_K);
+				});
+			}
+
+			#[test]
+			fn add_liquidity_provides_only_the_proportional_amount_of_y() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given a pool with equal reserves
+					setup_assets();
+					create_balanced_pool(X_Y_ID, TEN_K, TEN_K);
+
+					// add liquidity with more y than x should pass,
+					assert_ok!(Dex::add_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						1_000,
+						3_000,
+						0,
+						0,
+						None
+					));
+
+					// only the amount of y matching x should be transferred from alice,
+					assert_account_has(ALICE, ASSET_X, TEN_M - 1_000 + EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, ASSET_Y, TEN_M - 1_000 + EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, LP_TOKEN_ID, 1_000);
+
+					// and the provided amounts should be reported
+					System::assert_last_event(
+						Event::LiquidityProvided {
+							who: ALICE,
+							provided: AssetAmountPair::new(X_Y_ID, 1_000, 1_000),
+							lp_tokens: 1_000,
+							fee_tier: FEE_TIER,
+						}
+						.into(),
+					);
+				});
+			}
+
+			#[test]
+			fn add_liquidity_provides_only_the_proportional_amount_of_x() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given a pool with twice as much x as y
+					setup_assets();
+					create_pool(X_Y_ID, 2 * TEN_K, TEN_K, TEN_K);
+
+					// add liquidity with too much x for the desired y should pass,
+					assert_ok!(Dex::add_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						5_000,
+						1_000,
+						0,
+						0,
+						None
+					));
+
+					// only the amount of x matching y should be transferred from alice
+					assert_account_has(ALICE, ASSET_X, TEN_M - 2_000 + EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, ASSET_Y, TEN_M - 1_000 + EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, LP_TOKEN_ID, 1_000);
+					let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+					assert_eq!(pool.asset_amounts.amount_x.balance, 2 * TEN_K + 2_000);
+					assert_eq!(pool.asset_amounts.amount_y.balance, TEN_K + 1_000);
+				});
+			}
+
+			#[test]
+			fn add_liquidity_below_minimum_fails() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given a pool with equal reserves
+					setup_assets();
+					create_balanced_pool(X_Y_ID, TEN_K, TEN_K);
+
+					// add liquidity should fail if the proportional amount of y is below its minimum
+					assert_noop!(
+						Dex::add_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							1_000,
+							3_000,
+							0,
+							1_001,
+							None
+						),
+						Error::<Test>::MinimumProvisionNotReached
+					);
+
+					// and if the proportional amount of x is below its minimum
+					assert_noop!(
+						Dex::add_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							3_000,
+							1_000,
+							1_001,
+							0,
+							None
+						),
+						Error::<Test>::MinimumProvisionNotReached
+					);
+				});
+			}
+
+			#[test]
+			fn add_liquidity_rounding_to_zero_fails() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given a pool with a lot more y than x
+					setup_assets();
+					create_pool(X_Y_ID, 1_000, TEN_M, TEN_K);
+
+					// add liquidity should fail if the proportional amount of x rounds down to zero
+					assert_noop!(
+						Dex::add_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							TEN_K,
+							100,
+							0,
+							0,
+							None
+						),
 						Error::<Test>::InsufficientLiquidityProvided
 					);
 				});
 			}
+
+			#[test]
+			fn add_liquidity_to_missing_pool_fails() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets but no pool
+					setup_assets();
+
+					// add liquidity should fail
+					assert_noop!(
+						Dex::add_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							TEN_K,
+							TEN_K,
+							0,
+							0,
+							None
+						),
+						Error::<Test>::PoolDoesntExists
+					);
+				});
+			}
 		}
 
 		mod remove_liquidity {
 			use frame_support::{assert_noop, assert_ok};
+			use sp_runtime::TokenError;
 
-			use crate::mock::{Dex, RuntimeOrigin, System, Test};
+			use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
 			use crate::tests::tests::{
 				assert_account_has, create_asset, create_asset_amount_pair, create_balanced_pool,
-				init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, EXISTENTIAL_DEPOSIT,
-				LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+				create_pool, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, BOB,
+				EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
 			};
 			use crate::{Error, Event};
 
@@ -343,7 +741,16 @@ mod tests {
 					mint_asset(ALICE, lp_tokens, LP_TOKEN_ID);
 
 					// remove liquidity should pass
-					assert_ok!(Dex::remove_liquidity(RuntimeOrigin::signed(ALICE), X_Y_ID, TEN_K));
+					assert_ok!(Dex::remove_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						TEN_K,
+						0,
+						0,
+						None,
+						None
+					));
 
 					// liquidity assets should be transferred back to Alice,
 					assert_account_has(
@@ -366,6 +773,8 @@ mod tests {
 							who: ALICE,
 							removed: create_asset_amount_pair(TEN_K, ASSET_X, ASSET_Y),
 							lp_tokens,
+							reserves: create_asset_amount_pair(TEN_M - TEN_K, ASSET_X, ASSET_Y),
+							fee_tier: FEE_TIER,
 						}
 						.into(),
 					);
@@ -379,7 +788,16 @@ mod tests {
 
 					// remove liquidity should pass
 					assert_noop!(
-						Dex::remove_liquidity(RuntimeOrigin::signed(ALICE), X_Y_ID, 0),
+						Dex::remove_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							0,
+							0,
+							0,
+							None,
+							None
+						),
 						Error::<Test>::PoolDoesntExists
 					);
 				});
@@ -395,208 +813,5665 @@ mod tests {
 
 					// remove liquidity should fail with insufficient liquidity provided error
 					assert_noop!(
-						Dex::remove_liquidity(RuntimeOrigin::signed(ALICE), X_Y_ID, 0),
+						Dex::remove_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							0,
+							0,
+							0,
+							None,
+							None
+						),
 						Error::<Test>::InsufficientLiquidityProvided
 					);
 				});
 			}
-		}
-
-		mod swap_tests {
-			use frame_support::{assert_noop, assert_ok};
-
-			use crate::mock::{Dex, RuntimeEvent, RuntimeOrigin, System, Test};
-			use crate::tests::tests::{
-				assert_account_has, calculate_expected_give_amount,
-				calculate_expected_taken_amount, create_asset, create_balanced_pool, create_pool,
-				get_account_balance, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
-				EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID,
-			};
-			use crate::{AssetAmount, AssetAmountPair, Error, Event};
 
 			#[test]
-			fn swap_should_work() {
+			fn remove_liquidity_should_fail_below_minimum_amounts() {
 				init_test_ext().execute_with(|| {
 					System::set_block_number(1);
-					let reserve_x = TEN_M;
-					let reserve_y = TEN_M;
-					let liquidity = TEN_M;
-					let give = TEN_K;
 
-					// given created assets and pool
+					// given created assets and a pool with twice as much x as y
 					create_asset(ASSET_X);
 					create_asset(ASSET_Y);
-					create_pool(X_Y_ID, reserve_x, reserve_x, liquidity);
-
-					// and assets minted to Alice
-					mint_asset(ALICE, give + EXISTENTIAL_DEPOSIT, ASSET_X);
-					mint_asset(ALICE, EXISTENTIAL_DEPOSIT, ASSET_Y);
+					create_asset(LP_TOKEN_ID);
+					create_pool(X_Y_ID, 2 * TEN_M, TEN_M, TEN_M);
+					mint_asset(Dex::dex_account_id(), 2 * TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+					mint_asset(ALICE, TEN_K, LP_TOKEN_ID);
 
-					// and assets minted to dex account
-					mint_asset(Dex::dex_account_id(), reserve_x + EXISTENTIAL_DEPOSIT, ASSET_X);
-					mint_asset(Dex::dex_account_id(), reserve_y + EXISTENTIAL_DEPOSIT, ASSET_Y);
+					// remove liquidity should fail if either amount is below its minimum
+					assert_noop!(
+						Dex::remove_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							TEN_K,
+							2 * TEN_K + 1,
+							0,
+							None,
+							None
+						),
+						Error::<Test>::MinimumWithdrawalNotReached
+					);
+					assert_noop!(
+						Dex::remove_liquidity(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							TEN_K,
+							0,
+							TEN_K + 1,
+							None,
+							None
+						),
+						Error::<Test>::MinimumWithdrawalNotReached
+					);
 
-					// swap should work
-					let expected_take_amount =
-						calculate_expected_taken_amount(give, reserve_x, reserve_y);
-					let asset_amounts =
-						AssetAmountPair::<Test>::new(X_Y_ID, give, expected_take_amount);
-					assert_ok!(Dex::swap_limit_take(
+					// and pass if both are met exactly
+					assert_ok!(Dex::remove_liquidity(
 						RuntimeOrigin::signed(ALICE),
-						asset_amounts.amount_x,
-						expected_take_amount,
-						X_Y_ID
+						X_Y_ID,
+						FEE_TIER,
+						TEN_K,
+						2 * TEN_K,
+						TEN_K,
+						None,
+						None
 					));
+					assert_account_has(ALICE, ASSET_X, 2 * TEN_K);
+					assert_account_has(ALICE, ASSET_Y, TEN_K);
+				});
+			}
 
-					// and token issuance event should be emitted, with fee applied to taken amount
-					System::assert_last_event(
-						Event::TokenSwapped {
-							who: ALICE,
-							give: asset_amounts.amount_x,
-							take: asset_amounts.amount_y,
+			#[test]
+			fn remove_liquidity_should_withdraw_to_recipient() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool with provided liquidity
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_asset(LP_TOKEN_ID);
+					create_balanced_pool(X_Y_ID, TEN_M, TEN_M);
+					mint_asset(Dex::dex_account_id(), TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+					mint_asset(ALICE, TEN_K, LP_TOKEN_ID);
+
+					// remove liquidity to bob should pass
+					assert_ok!(Dex::remove_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						TEN_K,
+						0,
+						0,
+						Some(BOB),
+						None
+					));
+
+					// the assets should be transferred to bob, and the lp tokens burnt for alice
+					assert_account_has(BOB, ASSET_X, TEN_K);
+					assert_account_has(BOB, ASSET_Y, TEN_K);
+					assert_account_has(ALICE, ASSET_X, 0);
+					assert_account_has(ALICE, LP_TOKEN_ID, 0);
+
+					// and the event should be emitted for alice, with the remaining reserves
+					System::assert_last_event(
+						Event::LiquidityRemoved {
+							who: ALICE,
+							removed: create_asset_amount_pair(TEN_K, ASSET_X, ASSET_Y),
+							lp_tokens: TEN_K,
+							reserves: create_asset_amount_pair(TEN_M - TEN_K, ASSET_X, ASSET_Y),
+							fee_tier: FEE_TIER,
+						}
+						.into(),
+					);
+				});
+			}
+
+			#[test]
+			fn remove_liquidity_should_fail_if_lp_tokens_exceed_balance() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool with provided liquidity
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_asset(LP_TOKEN_ID);
+					create_balanced_pool(X_Y_ID, TEN_M, TEN_M);
+					mint_asset(Dex::dex_account_id(), TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+					mint_asset(ALICE, TEN_K, LP_TOKEN_ID);
+
+					// removing more lp tokens than alice holds should fail without changing state,
+					// whether the assets go to her or to bob
+					for withdraw_to in [None, Some(BOB)] {
+						assert_noop!(
+							Dex::remove_liquidity(
+								RuntimeOrigin::signed(ALICE),
+								X_Y_ID,
+								FEE_TIER,
+								TEN_M / 2,
+								0,
+								0,
+								withdraw_to,
+								None
+							),
+							TokenError::FundsUnavailable
+						);
+					}
+					assert_account_has(ALICE, LP_TOKEN_ID, TEN_K);
+					assert_account_has(BOB, ASSET_X, 0);
+					assert_account_has(BOB, ASSET_Y, 0);
+				});
+			}
+		}
+
+		mod swap_tests {
+			use frame_support::{assert_noop, assert_ok};
+
+			use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+			use crate::tests::tests::{
+				assert_account_has, calculate_expected_taken_amount, create_asset,
+				create_balanced_pool, create_pool, get_account_balance, init_test_ext, mint_asset,
+				ALICE, ASSET_X, ASSET_Y, EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID,
+			};
+			use crate::{AssetAmount, AssetAmountPair, Error, Event};
+
+			#[test]
+			fn swap_should_work() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve_x = TEN_M;
+					let reserve_y = TEN_M;
+					let liquidity = TEN_M;
+					let give = TEN_K;
+
+					// given created assets and pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, reserve_x, reserve_x, liquidity);
+
+					// and assets minted to Alice
+					mint_asset(ALICE, give + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// and assets minted to dex account
+					mint_asset(Dex::dex_account_id(), reserve_x + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), reserve_y + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// swap should work
+					let expected_take_amount =
+						calculate_expected_taken_amount(give, reserve_x, reserve_y);
+					let asset_amounts =
+						AssetAmountPair::<Test>::new(X_Y_ID, give, expected_take_amount);
+					assert_ok!(Dex::swap_limit_take(
+						RuntimeOrigin::signed(ALICE),
+						asset_amounts.amount_x,
+						expected_take_amount,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					));
+
+					// and token issuance event should be emitted, with fee applied to taken amount
+					System::assert_last_event(
+						Event::TokenSwapped {
+							who: ALICE,
+							give: asset_amounts.amount_x,
+							take: asset_amounts.amount_y,
+							fee_tier: FEE_TIER,
+						}
+						.into(),
+					);
+
+					assert_account_has(ALICE, ASSET_X, EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, ASSET_Y, EXISTENTIAL_DEPOSIT + expected_take_amount);
+				});
+			}
+
+			#[test]
+			fn swap_limit_give_should_work() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve_x = TEN_M;
+					let reserve_y = TEN_M;
+					let liquidity = TEN_M;
+					let take = TEN_K;
+
+					// given created assets and pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, reserve_x, reserve_y, liquidity);
+
+					// and assets minted to Alice
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// and assets minted to dex account
+					mint_asset(Dex::dex_account_id(), reserve_x + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), reserve_y + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// the pool needs x such that `(reserve_x + x * 0.99) * (reserve_y - take)` is at
+					// least `reserve_x * reserve_y`, which is `10m * 10k / (9.99m * 0.99) = 10_111.12`
+					// rounded up
+					let expected_give = 10_112;
+
+					// swap should work
+					let take_amount = AssetAmount::<Test>::new(ASSET_Y, take);
+					assert_ok!(Dex::swap_limit_give(
+						RuntimeOrigin::signed(ALICE),
+						take_amount,
+						expected_give,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					));
+
+					// and token issuance event should be emitted
+					System::assert_last_event(
+						Event::TokenSwapped {
+							who: ALICE,
+							give: AssetAmount::new(ASSET_X, expected_give),
+							take: take_amount,
+							fee_tier: FEE_TIER,
 						}
 						.into(),
 					);
 
-					assert_account_has(ALICE, ASSET_X, EXISTENTIAL_DEPOSIT);
-					assert_account_has(ALICE, ASSET_Y, EXISTENTIAL_DEPOSIT + expected_take_amount);
-				});
+					// and alice should have exactly the amount of asset y taken increased
+					assert_account_has(ALICE, ASSET_Y, TEN_M + EXISTENTIAL_DEPOSIT + take);
+
+					// and exactly the amount of asset x given decreased
+					assert_account_has(ALICE, ASSET_X, TEN_M + EXISTENTIAL_DEPOSIT - expected_give);
+				});
+			}
+
+			#[test]
+			fn swap_limit_give_should_charge_the_least_amount_keeping_k() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve_x = TEN_M;
+					let reserve_y = TEN_M * 2;
+					let take = 1_234_567;
+
+					// given created assets and a pool with uneven reserves
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, reserve_x, reserve_y, TEN_M);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), reserve_x + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), reserve_y + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// when alice takes an amount of y
+					assert_ok!(Dex::swap_limit_give(
+						RuntimeOrigin::signed(ALICE),
+						AssetAmount::<Test>::new(ASSET_Y, take),
+						TEN_M,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					));
+					let give = TEN_M + EXISTENTIAL_DEPOSIT - get_account_balance(ALICE, ASSET_X);
+
+					// then what is left of the amount given after the 1% fee should keep k
+					let k_after = |give: u128| (reserve_x * 100 + give * 99) * (reserve_y - take);
+					assert!(k_after(give) >= reserve_x * reserve_y * 100);
+					// and one less should not
+					assert!(k_after(give - 1) < reserve_x * reserve_y * 100);
+				});
+			}
+
+			#[test]
+			fn swap_limit_give_exceeding_maximum_input_should_fail() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, TEN_M, TEN_M, TEN_M);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+					// taking 10k costs 10_112, see `swap_limit_give_should_work`
+					assert_noop!(
+						Dex::swap_limit_give(
+							RuntimeOrigin::signed(ALICE),
+							AssetAmount::<Test>::new(ASSET_Y, TEN_K),
+							10_111,
+							X_Y_ID,
+							FEE_TIER,
+							None
+						),
+						Error::<Test>::MaximumInputExceeded
+					);
+				});
+			}
+
+			#[test]
+			fn swap_limit_give_taking_whole_reserve_should_fail() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool with 10k
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_balanced_pool(X_Y_ID, TEN_K, TEN_K);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+					// no amount can buy the whole reserve, or more
+					for take in [TEN_K, TEN_K + 1] {
+						assert_noop!(
+							Dex::swap_limit_give(
+								RuntimeOrigin::signed(ALICE),
+								AssetAmount::<Test>::new(ASSET_Y, take),
+								TEN_M,
+								X_Y_ID,
+								FEE_TIER,
+								None
+							),
+							Error::<Test>::SwapCannotBeSatisfied
+						);
+					}
+				});
+			}
+
+			#[test]
+			fn swapping_giving_zero_amount_should_fail() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					assert_noop!(
+						Dex::swap_limit_take(
+							RuntimeOrigin::signed(ALICE),
+							AssetAmount::<Test>::new(ASSET_X, 0u128),
+							0u128,
+							X_Y_ID,
+							FEE_TIER,
+							None
+						),
+						Error::<Test>::ZeroSwapAmountRequested
+					);
+				});
+			}
+
+			#[test]
+			fn unsatisfiable_swap_should_fail() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool with 10k
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_balanced_pool(X_Y_ID, TEN_K, TEN_K);
+
+					// and assets minted to Alice, value 10m
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// and assets minted to dex account, value 10k
+					mint_asset(Dex::dex_account_id(), TEN_K + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(Dex::dex_account_id(), TEN_K + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+					// swap should fail with excessive input amount when asking to swap 10m, and expecting 1m-10k
+					let give = AssetAmount::<Test>::new(ASSET_X, TEN_M);
+					assert_noop!(
+						Dex::swap_limit_take(
+							RuntimeOrigin::signed(ALICE),
+							give,
+							TEN_M - TEN_K,
+							X_Y_ID,
+							FEE_TIER,
+							None
+						),
+						Error::<Test>::MinimumOutputNotReached
+					);
+				});
+			}
+		}
+		mod path_swap_tests {
+			use frame_support::{assert_noop, assert_ok, BoundedVec};
+
+			use crate::mock::{Dex, RuntimeEvent, RuntimeOrigin, System, Test, FEE_TIER};
+			use crate::tests::tests::{
+				assert_account_has, calculate_expected_taken_amount, create_asset, create_pool,
+				get_account_balance, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, ASSET_Z,
+				EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID, Y_Z_ID,
+			};
+			use crate::{AssetAmount, Error, Event, Pools};
+
+			fn path(assets: Vec<u32>) -> BoundedVec<u32, <Test as crate::Config>::MaxPathLength> {
+				assets.try_into().expect("path should fit")
+			}
+
+			fn setup_x_y_z_pools(reserve: u128) {
+				// given created assets and pools x/y and y/z, but no x/z pool
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				create_asset(ASSET_Z);
+				create_pool(X_Y_ID, reserve, reserve, reserve);
+				create_pool(Y_Z_ID, reserve, reserve, reserve);
+
+				// and assets minted to dex account, y is shared by both pools
+				mint_asset(Dex::dex_account_id(), reserve + EXISTENTIAL_DEPOSIT, ASSET_X);
+				mint_asset(Dex::dex_account_id(), reserve * 2 + EXISTENTIAL_DEPOSIT, ASSET_Y);
+				mint_asset(Dex::dex_account_id(), reserve + EXISTENTIAL_DEPOSIT, ASSET_Z);
+			}
+
+			#[test]
+			fn swap_exact_in_along_path_should_work() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve = TEN_M;
+					let give = TEN_K;
+					setup_x_y_z_pools(reserve);
+
+					// and assets minted to Alice
+					mint_asset(ALICE, give + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, EXISTENTIAL_DEPOSIT, ASSET_Z);
+
+					// swap x for z through y should work
+					let take_y = calculate_expected_taken_amount(give, reserve, reserve);
+					let take_z = calculate_expected_taken_amount(take_y, reserve, reserve);
+					assert_ok!(Dex::swap_exact_in_along_path(
+						RuntimeOrigin::signed(ALICE),
+						path(vec![ASSET_X, ASSET_Y, ASSET_Z]),
+						give,
+						take_z,
+						None
+					));
+
+					// alice should have given x and taken z, without holding any y
+					assert_account_has(ALICE, ASSET_X, EXISTENTIAL_DEPOSIT);
+					assert_account_has(ALICE, ASSET_Y, 0);
+					assert_account_has(ALICE, ASSET_Z, EXISTENTIAL_DEPOSIT + take_z);
+
+					// and both pools reserves should have been updated
+					let x_y_pool =
+						Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+					assert_eq!(x_y_pool.asset_amounts.amount_x.balance, reserve + give);
+					assert_eq!(x_y_pool.asset_amounts.amount_y.balance, reserve - take_y);
+					let y_z_pool =
+						Pools::<Test>::get(&Y_Z_ID, FEE_TIER).expect("pool should exist");
+					assert_eq!(y_z_pool.asset_amounts.amount_x.balance, reserve + take_y);
+					assert_eq!(y_z_pool.asset_amounts.amount_y.balance, reserve - take_z);
+
+					// and an event per hop should be emitted, followed by a summary event
+					let give = AssetAmount::<Test>::new(ASSET_X, give);
+					let via = AssetAmount::<Test>::new(ASSET_Y, take_y);
+					let take = AssetAmount::<Test>::new(ASSET_Z, take_z);
+					let events: Vec<RuntimeEvent> =
+						System::events().into_iter().rev().take(3).map(|r| r.event).collect();
+					let expected_events: Vec<RuntimeEvent> = vec![
+						Event::PathSwapped {
+							who: ALICE,
+							path: path(vec![ASSET_X, ASSET_Y, ASSET_Z]),
+							give,
+							take,
+						}
+						.into(),
+						Event::TokenSwapped { who: ALICE, give: via, take, fee_tier: FEE_TIER }
+							.into(),
+						Event::TokenSwapped { who: ALICE, give, take: via, fee_tier: FEE_TIER }
+							.into(),
+					];
+					assert_eq!(events, expected_events);
+				});
+			}
+
+			#[test]
+			fn swap_exact_out_along_path_should_work() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve = TEN_M;
+					let take = TEN_K;
+					setup_x_y_z_pools(reserve);
+
+					// and assets minted to Alice
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(ALICE, EXISTENTIAL_DEPOSIT, ASSET_Z);
+
+					// swap x for exactly z through y should work
+					assert_ok!(Dex::swap_exact_out_along_path(
+						RuntimeOrigin::signed(ALICE),
+						path(vec![ASSET_X, ASSET_Y, ASSET_Z]),
+						take,
+						TEN_M,
+						None
+					));
+
+					// alice should have taken exactly the requested z
+					assert_account_has(ALICE, ASSET_Z, EXISTENTIAL_DEPOSIT + take);
+					assert_account_has(ALICE, ASSET_Y, 0);
+
+					// and the summary event should carry what she gave
+					let given = TEN_M + EXISTENTIAL_DEPOSIT - get_account_balance(ALICE, ASSET_X);
+					assert!(given > take, "two fees should make the swap cost more than it takes");
+					System::assert_last_event(
+						Event::PathSwapped {
+							who: ALICE,
+							path: path(vec![ASSET_X, ASSET_Y, ASSET_Z]),
+							give: AssetAmount::<Test>::new(ASSET_X, given),
+							take: AssetAmount::<Test>::new(ASSET_Z, take),
+						}
+						.into(),
+					);
+
+					// and the y taken from the first pool should be what was given to the second
+					let x_y_pool =
+						Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+					let y_z_pool =
+						Pools::<Test>::get(&Y_Z_ID, FEE_TIER).expect("pool should exist");
+					assert_eq!(
+						reserve - x_y_pool.asset_amounts.amount_y.balance,
+						y_z_pool.asset_amounts.amount_x.balance - reserve
+					);
+					assert_eq!(y_z_pool.asset_amounts.amount_y.balance, reserve - take);
+				});
+			}
+
+			#[test]
+			fn swap_along_path_should_fail_if_minimum_output_not_reached() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve = TEN_M;
+					let give = TEN_K;
+					setup_x_y_z_pools(reserve);
+					mint_asset(ALICE, give + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+					// expecting as much z as x given can't be satisfied with two fees on the way
+					assert_noop!(
+						Dex::swap_exact_in_along_path(
+							RuntimeOrigin::signed(ALICE),
+							path(vec![ASSET_X, ASSET_Y, ASSET_Z]),
+							give,
+							give,
+							None
+						),
+						Error::<Test>::MinimumOutputNotReached
+					);
+				});
+			}
+
+			#[test]
+			fn swap_along_path_should_fail_if_maximum_input_exceeded() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					let reserve = TEN_M;
+					let take = TEN_K;
+					setup_x_y_z_pools(reserve);
+					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+					// giving as much x as z taken can't be enough with two fees on the way
+					assert_noop!(
+						Dex::swap_exact_out_along_path(
+							RuntimeOrigin::signed(ALICE),
+							path(vec![ASSET_X, ASSET_Y, ASSET_Z]),
+							take,
+							take,
+							None
+						),
+						Error::<Test>::MaximumInputExceeded
+					);
+				});
+			}
+
+			#[test]
+			fn swap_along_invalid_path_should_fail() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					setup_x_y_z_pools(TEN_M);
+
+					// a path needs at least two assets
+					assert_noop!(
+						Dex::swap_exact_in_along_path(
+							RuntimeOrigin::signed(ALICE),
+							path(vec![ASSET_X]),
+							TEN_K,
+							0,
+							None
+						),
+						Error::<Test>::InvalidPath
+					);
+
+					// and can't go through the same asset twice
+					assert_noop!(
+						Dex::swap_exact_in_along_path(
+							RuntimeOrigin::signed(ALICE),
+							path(vec![ASSET_X, ASSET_Y, ASSET_X]),
+							TEN_K,
+							0,
+							None
+						),
+						Error::<Test>::InvalidPath
+					);
+				});
+			}
+
+			#[test]
+			fn swap_along_path_without_pool_should_fail() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+					setup_x_y_z_pools(TEN_M);
+
+					// there is no x/z pool
+					assert_noop!(
+						Dex::swap_exact_in_along_path(
+							RuntimeOrigin::signed(ALICE),
+							path(vec![ASSET_Y, ASSET_X, ASSET_Z]),
+							TEN_K,
+							0,
+							None
+						),
+						Error::<Test>::PoolDoesntExists
+					);
+				});
+			}
+		}
+
+		mod get_asset_price_tests {
+			use frame_support::assert_ok;
+			use sp_runtime::FixedU128;
+
+			use crate::mock::{Dex, RuntimeOrigin, System, FEE_TIER};
+			use crate::tests::tests::{
+				create_asset, create_pool, init_test_ext, ALICE, ASSET_X, ASSET_Y, TEN_M, X_Y_ID,
+			};
+			use crate::Event::AssetPrice;
+
+			#[test]
+			fn get_price_of_should_work() {
+				let x_vs_y = 2;
+				let price_of_x_in_y = FixedU128::from_rational(x_vs_y, 1);
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, TEN_M * x_vs_y, TEN_M, TEN_M);
+
+					// get price should work
+					assert_ok!(Dex::get_asset_price(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						ASSET_X
+					));
+
+					// and token asset price event should be emitted
+					System::assert_last_event(AssetPrice { price: price_of_x_in_y }.into());
+				});
+			}
+		}
+
+		mod runtime_api_tests {
+			use crate::mock::{Dex, System, FEE_TIER};
+			use crate::tests::tests::{
+				calculate_expected_taken_amount, create_asset, create_pool, init_test_ext, ASSET_X,
+				ASSET_Y, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+			};
+			use crate::{Curve, PoolInfo};
+
+			#[test]
+			fn quote_exact_in_should_match_swap_calculation() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, TEN_M, TEN_M * 2, TEN_M);
+
+					// quote should be the amount a swap would take, in both directions
+					assert_eq!(
+						Dex::quote_exact_in(ASSET_X, ASSET_Y, TEN_K),
+						Some(calculate_expected_taken_amount(TEN_K, TEN_M, TEN_M * 2))
+					);
+					assert_eq!(
+						Dex::quote_exact_in(ASSET_Y, ASSET_X, TEN_K),
+						Some(calculate_expected_taken_amount(TEN_K, TEN_M * 2, TEN_M))
+					);
+				});
+			}
+
+			#[test]
+			fn quote_exact_out_should_match_swap_calculation() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given created assets and pool
+					create_asset(ASSET_X);
+					create_asset(ASSET_Y);
+					create_pool(X_Y_ID, TEN_M, TEN_M, TEN_M);
+
+					// quote should be what `swap_limit_give` charges, see `swap_limit_give_should_work`
+					assert_eq!(Dex::quote_exact_out(ASSET_X, ASSET_Y, TEN_K), Some(10_112));
+
+					// and none for the whole reserve
+					assert_eq!(Dex::quote_exact_out(ASSET_X, ASSET_Y, TEN_M), None);
+				});
+			}
+
+			#[test]
+			fn quotes_should_be_none_if_pool_doesnt_exist() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					assert_eq!(Dex::quote_exact_in(ASSET_X, ASSET_Y, TEN_K), None);
+					assert_eq!(Dex::quote_exact_out(ASSET_X, ASSET_Y, TEN_K), None);
+					assert_eq!(Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER), None);
+					assert_eq!(Dex::lp_token_of(ASSET_X, ASSET_Y, FEE_TIER), None);
+				});
+			}
+
+			#[test]
+			fn get_reserves_should_follow_argument_order() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given a pool with uneven reserves
+					create_pool(X_Y_ID, TEN_M, TEN_K, TEN_K);
+
+					// reserves should be returned in the order the assets were asked for
+					assert_eq!(Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER), Some((TEN_M, TEN_K)));
+					assert_eq!(Dex::get_reserves(ASSET_Y, ASSET_X, FEE_TIER), Some((TEN_K, TEN_M)));
+				});
+			}
+
+			#[test]
+			fn list_pools_and_lp_token_of_should_work() {
+				init_test_ext().execute_with(|| {
+					System::set_block_number(1);
+
+					// given a pool
+					create_pool(X_Y_ID, TEN_M, TEN_K, TEN_K);
+
+					// it should be listed
+					assert_eq!(
+						Dex::list_pools(),
+						vec![PoolInfo {
+							asset_x_id: ASSET_X,
+							asset_y_id: ASSET_Y,
+							reserve_x: TEN_M,
+							reserve_y: TEN_K,
+							total_liquidity: TEN_K,
+							lp_token_id: LP_TOKEN_ID,
+							fee_tier: FEE_TIER,
+							curve: Curve::ConstantProduct,
+						}]
+					);
+
+					// and its lp token should be found regardless of asset order
+					assert_eq!(Dex::lp_token_of(ASSET_X, ASSET_Y, FEE_TIER), Some(LP_TOKEN_ID));
+					assert_eq!(Dex::lp_token_of(ASSET_Y, ASSET_X, FEE_TIER), Some(LP_TOKEN_ID));
+				});
+			}
+		}
+	}
+
+	mod native_asset_tests {
+		use frame_support::assert_ok;
+
+		use crate::mock::{Balances, Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, calculate_expected_taken_amount, create_asset,
+			create_asset_amount_pair, init_test_ext, mint_asset, ALICE, ASSET_X, BOB,
+			EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, NATIVE, TEN_B, TEN_K, TEN_M,
+		};
+		use crate::{AssetAmount, AssetIdPair, NativeOrAssets, Pools};
+
+		fn native_x_id() -> AssetIdPair<Test> {
+			AssetIdPair::new(NATIVE, ASSET_X).expect("id pair should be valid")
+		}
+
+		fn setup_native_x_pool(reserve: u128) {
+			// given a created asset and a native/x pool
+			create_asset(ASSET_X);
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				NATIVE,
+				ASSET_X,
+				FEE_TIER,
+				None
+			));
+
+			// and liquidity provided by alice in both native currency and x
+			mint_asset(ALICE, reserve + EXISTENTIAL_DEPOSIT, ASSET_X);
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				create_asset_amount_pair(reserve, NATIVE, ASSET_X),
+				FEE_TIER,
+				None
+			));
+		}
+
+		#[test]
+		fn native_currency_should_always_exist() {
+			init_test_ext().execute_with(|| {
+				assert!(NativeOrAssets::<Test>::asset_exists(NATIVE));
+				assert!(!NativeOrAssets::<Test>::asset_exists(ASSET_X));
+				assert_eq!(NativeOrAssets::<Test>::balance(NATIVE, &ALICE), TEN_B);
+			});
+		}
+
+		#[test]
+		fn provide_and_remove_native_liquidity_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				let dex_native = Balances::free_balance(Dex::dex_account_id());
+				setup_native_x_pool(TEN_M);
+
+				// native currency should have moved from alice to the dex
+				assert_eq!(Balances::free_balance(ALICE), TEN_B - TEN_M);
+				assert_eq!(Balances::free_balance(Dex::dex_account_id()), dex_native + TEN_M);
+				assert_account_has(ALICE, LP_TOKEN_ID, TEN_M);
+
+				// removing half of the liquidity should return half of the native reserve
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					native_x_id(),
+					FEE_TIER,
+					TEN_M / 2,
+					0,
+					0,
+					None,
+					None
+				));
+				assert_eq!(Balances::free_balance(ALICE), TEN_B - TEN_M / 2);
+				assert_account_has(ALICE, ASSET_X, TEN_M / 2 + EXISTENTIAL_DEPOSIT);
+
+				let pool = Pools::<Test>::get(native_x_id(), FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.asset_amounts.amount_x.balance, TEN_M / 2);
+				assert_eq!(pool.asset_amounts.amount_y.balance, TEN_M / 2);
+			});
+		}
+
+		#[test]
+		fn swap_native_for_asset_and_back_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_native_x_pool(TEN_M);
+				mint_asset(BOB, EXISTENTIAL_DEPOSIT, ASSET_X);
+
+				// swapping native currency for x should work
+				let take_x = calculate_expected_taken_amount(TEN_K, TEN_M, TEN_M);
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(NATIVE, TEN_K),
+					take_x,
+					native_x_id(),
+					FEE_TIER,
+					None
+				));
+				assert_eq!(Balances::free_balance(BOB), TEN_B - TEN_K);
+				assert_account_has(BOB, ASSET_X, EXISTENTIAL_DEPOSIT + take_x);
+
+				// and swapping x back for native currency should work
+				let take_native =
+					calculate_expected_taken_amount(take_x, TEN_M - take_x, TEN_M + TEN_K);
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_X, take_x),
+					take_native,
+					native_x_id(),
+					FEE_TIER,
+					None
+				));
+				assert_eq!(Balances::free_balance(BOB), TEN_B - TEN_K + take_native);
+				assert_account_has(BOB, ASSET_X, EXISTENTIAL_DEPOSIT);
+			});
+		}
+	}
+
+	mod protocol_fee_tests {
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::traits::IntegerSquareRoot;
+		use sp_runtime::DispatchError;
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER, PROTOCOL_FEE_RECIPIENT};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, create_asset_amount_pair, get_account_balance,
+			init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, BOB, EXISTENTIAL_DEPOSIT,
+			LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, Error, Event, Pools};
+
+		fn setup_x_y_pool(reserve: u128) {
+			// given created assets and pool
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+
+			// and liquidity provided by alice
+			mint_asset(ALICE, reserve + EXISTENTIAL_DEPOSIT, ASSET_X);
+			mint_asset(ALICE, reserve + EXISTENTIAL_DEPOSIT, ASSET_Y);
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				create_asset_amount_pair(reserve, ASSET_X, ASSET_Y),
+				FEE_TIER,
+				None
+			));
+		}
+
+		fn swap_x_for_y_by_bob(give: u128) {
+			mint_asset(BOB, give + EXISTENTIAL_DEPOSIT, ASSET_X);
+			mint_asset(BOB, EXISTENTIAL_DEPOSIT, ASSET_Y);
+			assert_ok!(Dex::swap_limit_take(
+				RuntimeOrigin::signed(BOB),
+				AssetAmount::new(ASSET_X, give),
+				0,
+				X_Y_ID,
+				FEE_TIER,
+				None
+			));
+		}
+
+		/// Uniswap V2's protocol fee, `total * (root_k - root_k_last) / (4 * root_k + root_k_last)`
+		/// for the mock's share of a fifth of the fees.
+		fn expected_protocol_fee(total: u128, k: u128, k_last: u128) -> u128 {
+			let (root_k, root_k_last) = (k.integer_sqrt(), k_last.integer_sqrt());
+			total * (root_k - root_k_last) / (4 * root_k + root_k_last)
+		}
+
+		fn assert_close(actual: u128, expected: u128) {
+			assert!(
+				actual.abs_diff(expected) <= 1,
+				"Expected {} to be within 1 of {}",
+				actual,
+				expected
+			);
+		}
+
+		#[test]
+		fn set_protocol_fee_should_require_root() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(TEN_M);
+
+				// signed origins can't switch the fee
+				assert_noop!(
+					Dex::set_protocol_fee(RuntimeOrigin::signed(ALICE), X_Y_ID, FEE_TIER, true),
+					DispatchError::BadOrigin
+				);
+
+				// root can
+				assert_ok!(Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, true));
+				System::assert_last_event(
+					Event::ProtocolFeeSwitched { pair: X_Y_ID, enabled: true, fee_tier: FEE_TIER }
+						.into(),
+				);
+
+				// and the pool should record sqrt(k) from now on
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert!(pool.protocol_fee_on);
+				assert_eq!(pool.root_k_last, TEN_M);
+			});
+		}
+
+		#[test]
+		fn set_protocol_fee_for_missing_pool_fails() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				assert_noop!(
+					Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, true),
+					Error::<Test>::PoolDoesntExists
+				);
+			});
+		}
+
+		#[test]
+		fn protocol_fee_should_be_minted_on_next_liquidity_event() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(TEN_M);
+
+				// given the protocol fee is turned on and a swap grew the pool
+				assert_ok!(Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, true));
+				swap_x_for_y_by_bob(TEN_K);
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				let k = pool.asset_amounts.amount_x.balance * pool.asset_amounts.amount_y.balance;
+
+				// no fee is minted on swaps
+				assert_account_has(PROTOCOL_FEE_RECIPIENT, LP_TOKEN_ID, 0);
+
+				// removing liquidity should mint the fee first
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					X_Y_ID,
+					FEE_TIER,
+					TEN_K,
+					0,
+					0,
+					None,
+					None
+				));
+				let fee = get_account_balance(PROTOCOL_FEE_RECIPIENT, LP_TOKEN_ID);
+				assert_close(fee, expected_protocol_fee(TEN_M, k, TEN_M * TEN_M));
+				assert!(fee > 0);
+
+				// the fee should be part of the total liquidity, and sqrt(k) recorded again
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.total_liquidity, TEN_M + fee - TEN_K);
+				assert_eq!(
+					pool.root_k_last,
+					(pool.asset_amounts.amount_x.balance * pool.asset_amounts.amount_y.balance)
+						.integer_sqrt()
+				);
+			});
+		}
+
+		#[test]
+		fn protocol_fee_should_not_be_minted_while_off() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(TEN_M);
+
+				// given a swap grew the pool while the protocol fee is off
+				swap_x_for_y_by_bob(TEN_K);
+
+				// removing liquidity should mint nothing to the recipient
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					X_Y_ID,
+					FEE_TIER,
+					TEN_K,
+					0,
+					0,
+					None,
+					None
+				));
+				assert_account_has(PROTOCOL_FEE_RECIPIENT, LP_TOKEN_ID, 0);
+
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.total_liquidity, TEN_M - TEN_K);
+				assert_eq!(pool.root_k_last, 0);
+			});
+		}
+
+		#[test]
+		fn turning_protocol_fee_off_should_mint_accrued_fee() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(TEN_M);
+
+				// given the protocol fee is turned on and a swap grew the pool
+				assert_ok!(Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, true));
+				swap_x_for_y_by_bob(TEN_K);
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				let k = pool.asset_amounts.amount_x.balance * pool.asset_amounts.amount_y.balance;
+
+				// turning the fee off should mint the fee accrued so far
+				assert_ok!(Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, false));
+				let fee = get_account_balance(PROTOCOL_FEE_RECIPIENT, LP_TOKEN_ID);
+				assert_close(fee, expected_protocol_fee(TEN_M, k, TEN_M * TEN_M));
+				System::assert_has_event(
+					Event::ProtocolFeeMinted {
+						recipient: PROTOCOL_FEE_RECIPIENT,
+						lp_token_id: LP_TOKEN_ID,
+						lp_tokens: fee,
+					}
+					.into(),
+				);
+
+				// and stop recording sqrt(k)
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert!(!pool.protocol_fee_on);
+				assert_eq!(pool.root_k_last, 0);
+				assert_eq!(pool.total_liquidity, TEN_M + fee);
+			});
+		}
+	}
+
+	mod twap_tests {
+		use frame_support::assert_ok;
+		use frame_support::traits::Get;
+		use sp_runtime::traits::Convert;
+		use sp_runtime::{FixedPointNumber, FixedU128};
+
+		use crate::mock::{
+			AssetBalancePairToRatioConverter, Dex, RuntimeOrigin, System, Test, Timestamp, FEE_TIER,
+		};
+		use crate::tests::tests::{
+			create_asset, create_asset_amount_pair, init_test_ext, mint_asset, ALICE, ASSET_X,
+			ASSET_Y, BOB, EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, Observations, Pools};
+
+		const ONE: u128 = 1_000_000_000_000_000_000;
+
+		fn set_time_in_secs(secs: u64) {
+			Timestamp::set_timestamp(secs * 1_000);
+		}
+
+		fn setup_x_y_pool_at(secs: u64) {
+			// given created assets and pool, provided with liquidity at the given time
+			set_time_in_secs(secs);
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+			mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+			mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+				FEE_TIER,
+				None
+			));
+
+			// and assets minted to bob to swap with
+			mint_asset(BOB, TEN_M, ASSET_X);
+			mint_asset(BOB, TEN_M, ASSET_Y);
+		}
+
+		fn swap_x_for_y_at(secs: u64) {
+			set_time_in_secs(secs);
+			assert_ok!(Dex::swap_limit_take(
+				RuntimeOrigin::signed(BOB),
+				AssetAmount::new(ASSET_X, TEN_K),
+				0,
+				X_Y_ID,
+				FEE_TIER,
+				None
+			));
+		}
+
+		fn current_price_of_x() -> u128 {
+			let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+			AssetBalancePairToRatioConverter::convert((
+				pool.asset_amounts.amount_x.balance,
+				pool.asset_amounts.amount_y.balance,
+			))
+			.into_inner()
+		}
+
+		#[test]
+		fn price_cumulatives_should_only_update_on_first_touch_in_a_block() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool_at(1);
+
+				// the first provision should be observed, with nothing accumulated while empty
+				let observations = Observations::<Test>::get(&X_Y_ID, FEE_TIER);
+				assert_eq!(observations.len(), 1);
+				assert_eq!(observations[0].timestamp, 1);
+				assert_eq!(observations[0].price_x_cumulative, 0);
+
+				// a swap 10 seconds later should accumulate the initial price of 1 for 10 seconds
+				swap_x_for_y_at(11);
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.price_x_cumulative, 10 * ONE);
+				assert_eq!(pool.price_y_cumulative, 10 * ONE);
+				assert_eq!(pool.last_timestamp, 11);
+
+				// but another swap at the same time should not accumulate anything
+				swap_x_for_y_at(11);
+				let pool = Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.price_x_cumulative, 10 * ONE);
+				assert_eq!(Observations::<Test>::get(&X_Y_ID, FEE_TIER).len(), 2);
+			});
+		}
+
+		#[test]
+		fn get_twap_should_average_prices_over_time() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool_at(1);
+
+				// given the price of x was 1 for 10 seconds, then moved by a swap for 20 seconds
+				swap_x_for_y_at(11);
+				let moved_price = current_price_of_x();
+				assert_ne!(moved_price, ONE);
+				swap_x_for_y_at(31);
+
+				// the average over the whole period should be weighted by time
+				assert_eq!(
+					Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 1, 31),
+					Some(FixedU128::from_inner((10 * ONE + 20 * moved_price) / 30))
+				);
+
+				// and the average over the second period alone should be the moved price
+				assert_eq!(
+					Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 11, 31),
+					Some(FixedU128::from_inner(moved_price))
+				);
+
+				// bounds between observations should resolve to the newest observation before them
+				assert_eq!(
+					Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 15, 35),
+					Some(FixedU128::from_inner(moved_price))
+				);
+			});
+		}
+
+		#[test]
+		fn get_twap_without_enough_observations_should_be_none() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				// no pool
+				assert_eq!(Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 0, 10), None);
+
+				// a single observation
+				setup_x_y_pool_at(1);
+				assert_eq!(Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 1, 10), None);
+
+				// nothing observed at or before the start
+				swap_x_for_y_at(11);
+				assert_eq!(Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 0, 11), None);
+			});
+		}
+
+		#[test]
+		fn observations_should_drop_the_oldest_when_full() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool_at(1);
+				let max_observations = <Test as crate::Config>::MaxObservations::get();
+
+				// given more swaps in distinct blocks than observations kept
+				for i in 1..=max_observations as u64 {
+					swap_x_for_y_at(1 + i * 10);
+				}
+
+				// the first observation should have been dropped, newest first
+				let observations = Observations::<Test>::get(&X_Y_ID, FEE_TIER);
+				assert_eq!(observations.len(), max_observations as usize);
+				assert_eq!(observations[0].timestamp, 1 + max_observations as u64 * 10);
+				assert_eq!(observations[observations.len() - 1].timestamp, 11);
+				assert_eq!(Dex::get_twap(ASSET_X, ASSET_Y, FEE_TIER, 1, 21), None);
+			});
+		}
+	}
+
+	mod minimum_liquidity_tests {
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::TokenError;
+
+		use crate::mock::{Dex, MinimumLiquidity, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, create_asset_amount_pair, get_account_balance,
+			init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, BOB, CHARLIE,
+			EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, Error, Event, Pools};
+
+		const MINIMUM_LIQUIDITY: u128 = 1_000;
+		/// Amount of x swapped into the pool by the attacker for nothing in return.
+		const DONATION: u128 = 10_000_000;
+		/// Amount of x the victim wants to provide.
+		const VICTIM_X: u128 = 15_000_000;
+
+		fn setup_x_y_pool() {
+			// given created assets and pool
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+
+			// and assets minted to alice and bob
+			for who in [ALICE, BOB] {
+				mint_asset(who, 2 * TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+				mint_asset(who, 2 * TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+			}
+		}
+
+		/// Bob provides the first liquidity and swaps `DONATION` of x into the pool, too little y
+		/// is left in it to give anything in return, after which alice provides `VICTIM_X` of x.
+		fn inflate_lp_token_before_alice_provides(first_x: u128, first_y: u128) {
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(BOB),
+				AssetAmountPair::new(X_Y_ID, first_x, first_y),
+				FEE_TIER,
+				None
+			));
+			assert_account_has(BOB, LP_TOKEN_ID, 1);
+
+			assert_ok!(Dex::swap_limit_take(
+				RuntimeOrigin::signed(BOB),
+				AssetAmount::new(ASSET_X, DONATION),
+				0,
+				X_Y_ID,
+				FEE_TIER,
+				None
+			));
+			System::assert_last_event(
+				Event::TokenSwapped {
+					who: BOB,
+					give: AssetAmount::new(ASSET_X, DONATION),
+					take: AssetAmount::new(ASSET_Y, 0),
+					fee_tier: FEE_TIER,
+				}
+				.into(),
+			);
+
+			assert_ok!(Dex::add_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				X_Y_ID,
+				FEE_TIER,
+				VICTIM_X,
+				TEN_M,
+				0,
+				0,
+				None
+			));
+		}
+
+		#[test]
+		fn first_provision_should_lock_minimum_liquidity() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				MinimumLiquidity::set(MINIMUM_LIQUIDITY);
+				setup_x_y_pool();
+
+				// the first provision should mint the minimum liquidity to the dex account
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+					FEE_TIER,
+					None
+				));
+				assert_account_has(ALICE, LP_TOKEN_ID, TEN_M - MINIMUM_LIQUIDITY);
+				assert_account_has(Dex::dex_account_id(), LP_TOKEN_ID, MINIMUM_LIQUIDITY);
+				let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.total_liquidity, TEN_M);
+
+				// and later provisions should mint the full amount
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(BOB),
+					create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+					FEE_TIER,
+					None
+				));
+				assert_account_has(BOB, LP_TOKEN_ID, TEN_M);
+				assert_account_has(Dex::dex_account_id(), LP_TOKEN_ID, MINIMUM_LIQUIDITY);
+			});
+		}
+
+		#[test]
+		fn first_provision_not_above_minimum_liquidity_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				MinimumLiquidity::set(MINIMUM_LIQUIDITY);
+				setup_x_y_pool();
+
+				assert_noop!(
+					Dex::provide_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						create_asset_amount_pair(MINIMUM_LIQUIDITY, ASSET_X, ASSET_Y),
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::InsufficientLiquidityProvided
+				);
+			});
+		}
+
+		#[test]
+		fn minimum_liquidity_should_stay_in_pool() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				MinimumLiquidity::set(MINIMUM_LIQUIDITY);
+				setup_x_y_pool();
+
+				// given liquidity provided by alice only
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+					FEE_TIER,
+					None
+				));
+
+				// removing all of her liquidity should leave the locked share of the reserves
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					X_Y_ID,
+					FEE_TIER,
+					TEN_M - MINIMUM_LIQUIDITY,
+					0,
+					0,
+					None,
+					None
+				));
+				let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.total_liquidity, MINIMUM_LIQUIDITY);
+				assert_eq!(
+					pool.asset_amounts,
+					create_asset_amount_pair(MINIMUM_LIQUIDITY, ASSET_X, ASSET_Y)
+				);
+			});
+		}
+
+		#[test]
+		fn inflation_attack_should_be_profitable_without_minimum_liquidity() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				let bob_x = get_account_balance(BOB, ASSET_X);
+				let bob_y = get_account_balance(BOB, ASSET_Y);
+
+				// given bob holds the only LP token, worth all of the donated x
+				inflate_lp_token_before_alice_provides(1, 1);
+
+				// alice's provision should be rounded down to a single LP token as well
+				assert_account_has(ALICE, LP_TOKEN_ID, 1);
+
+				// and bob should take half of the pool's x, more than he spent, by removing his
+				// liquidity
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(BOB),
+					X_Y_ID,
+					FEE_TIER,
+					1,
+					0,
+					0,
+					None,
+					None
+				));
+				assert_eq!(get_account_balance(BOB, ASSET_Y), bob_y);
+				let pool_x = 1 + DONATION + VICTIM_X;
+				assert_eq!(get_account_balance(BOB, ASSET_X), bob_x - 1 - DONATION + pool_x / 2);
+				assert!(get_account_balance(BOB, ASSET_X) > bob_x);
+			});
+		}
+
+		#[test]
+		fn inflation_attack_should_not_be_profitable_with_minimum_liquidity() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				MinimumLiquidity::set(MINIMUM_LIQUIDITY);
+				setup_x_y_pool();
+
+				// given bob holds a single LP token over the locked ones, provided as cheaply as
+				// possible while leaving too little y in the pool to pay for the donation
+				let (first_x, first_y) = ((MINIMUM_LIQUIDITY + 1).pow(2), 1);
+				inflate_lp_token_before_alice_provides(first_x, first_y);
+
+				// bob's share of the pool should be worth less than a hundredth of what he spent
+				// on it, as the locked LP tokens hold most of the donation
+				let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+				let bob_x = pool.asset_amounts.amount_x.balance / pool.total_liquidity;
+				let bob_y = pool.asset_amounts.amount_y.balance / pool.total_liquidity;
+				assert!(bob_x < (first_x + DONATION) / 100);
+				assert!(bob_y <= first_y);
+			});
+		}
+
+		#[test]
+		fn inflation_attack_should_not_remove_more_liquidity_than_held() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				MinimumLiquidity::set(MINIMUM_LIQUIDITY);
+				setup_x_y_pool();
+
+				// given bob holds a single LP token over the locked ones after donating
+				let (first_x, first_y) = ((MINIMUM_LIQUIDITY + 1).pow(2), 1);
+				inflate_lp_token_before_alice_provides(first_x, first_y);
+				let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+
+				// removing the locked LP tokens and alice's along with bob's should fail, whether
+				// the assets go to bob or to another account
+				for withdraw_to in [None, Some(CHARLIE)] {
+					assert_noop!(
+						Dex::remove_liquidity(
+							RuntimeOrigin::signed(BOB),
+							X_Y_ID,
+							FEE_TIER,
+							pool.total_liquidity,
+							0,
+							0,
+							withdraw_to,
+							None
+						),
+						TokenError::FundsUnavailable
+					);
+				}
+
+				// so the locked LP tokens should still back the donation
+				assert_eq!(Pools::<Test>::get(X_Y_ID, FEE_TIER), Some(pool));
+				assert_account_has(BOB, LP_TOKEN_ID, 1);
+				assert_account_has(Dex::dex_account_id(), LP_TOKEN_ID, MINIMUM_LIQUIDITY);
+				assert_account_has(CHARLIE, ASSET_X, 0);
+			});
+		}
+	}
+
+	mod genesis_tests {
+		use frame_support::assert_ok;
+		use sp_io::TestExternalities;
+		use sp_runtime::{BuildStorage, Perbill};
+
+		use crate::mock::{Dex, RuntimeOrigin, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, ADMIN, ALICE, ASSET_X, ASSET_Y, ASSET_Z, BOB, LP_TOKEN_ID, TEN_B,
+			TEN_K, TEN_M, X_Y_ID, Y_Z_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, LpTokenToPair, NextLpTokenId, Pools};
+
+		type GenesisPool = (u32, u32, Perbill, u32, u128, u128, u64);
+
+		fn new_genesis_ext(pools: Vec<GenesisPool>) -> TestExternalities {
+			let mut storage =
+				frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+			pallet_balances::GenesisConfig::<Test> {
+				balances: vec![(Dex::dex_account_id(), TEN_B), (ALICE, TEN_B), (BOB, TEN_B)],
+			}
+			.assimilate_storage(&mut storage)
+			.unwrap();
+			pallet_assets::GenesisConfig::<Test> {
+				assets: [ASSET_X, ASSET_Y, ASSET_Z].map(|asset| (asset, ADMIN, false, 1)).to_vec(),
+				metadata: vec![],
+				accounts: vec![(ASSET_X, BOB, TEN_M)],
+			}
+			.assimilate_storage(&mut storage)
+			.unwrap();
+			crate::GenesisConfig::<Test> { fee_tiers: vec![FEE_TIER], pools }
+				.assimilate_storage(&mut storage)
+				.unwrap();
+			storage.into()
+		}
+
+		#[test]
+		fn genesis_pools_should_be_created_with_liquidity() {
+			new_genesis_ext(vec![
+				(ASSET_Y, ASSET_X, FEE_TIER, LP_TOKEN_ID + 5, 4 * TEN_M, TEN_M, ALICE),
+				(ASSET_Y, ASSET_Z, FEE_TIER, LP_TOKEN_ID, TEN_M, TEN_M, BOB),
+			])
+			.execute_with(|| {
+				// the reserves should be sorted with the pair, and held by the dex account
+				let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.asset_amounts, AssetAmountPair::new(X_Y_ID, TEN_M, 4 * TEN_M));
+				assert_eq!(pool.lp_token_id, LP_TOKEN_ID + 5);
+				assert_account_has(Dex::dex_account_id(), ASSET_X, TEN_M);
+				assert_account_has(Dex::dex_account_id(), ASSET_Y, 5 * TEN_M);
+
+				// the LP tokens should be minted to the recipients
+				assert_eq!(pool.total_liquidity, 2 * TEN_M);
+				assert_account_has(ALICE, LP_TOKEN_ID + 5, 2 * TEN_M);
+				assert_account_has(BOB, LP_TOKEN_ID, TEN_M);
+
+				// the LP tokens should map back to their pools
+				assert_eq!(LpTokenToPair::<Test>::get(LP_TOKEN_ID + 5), Some((X_Y_ID, FEE_TIER)));
+				assert_eq!(LpTokenToPair::<Test>::get(LP_TOKEN_ID), Some((Y_Z_ID, FEE_TIER)));
+
+				// and new pools should get an LP token id after the highest one
+				assert_eq!(NextLpTokenId::<Test>::get(), Some(LP_TOKEN_ID + 6));
+			});
+		}
+
+		#[test]
+		fn genesis_pools_should_be_tradable() {
+			new_genesis_ext(vec![(ASSET_X, ASSET_Y, FEE_TIER, LP_TOKEN_ID, TEN_M, TEN_M, ALICE)])
+				.execute_with(|| {
+					assert_ok!(Dex::swap_limit_take(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_X, TEN_K),
+						1,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					));
+					assert!(pallet_assets::Pallet::<Test>::balance(ASSET_Y, BOB) > 0);
+				});
+		}
+
+		#[test]
+		#[should_panic(expected = "LP token ids of genesis pools must not be in use")]
+		fn genesis_pool_with_lp_token_id_in_use_should_panic() {
+			new_genesis_ext(vec![(ASSET_X, ASSET_Y, FEE_TIER, ASSET_Z, TEN_M, TEN_M, ALICE)]);
+		}
+
+		#[test]
+		#[should_panic(expected = "genesis pools must be unique")]
+		fn duplicate_genesis_pool_should_panic() {
+			new_genesis_ext(vec![
+				(ASSET_X, ASSET_Y, FEE_TIER, LP_TOKEN_ID, TEN_M, TEN_M, ALICE),
+				(ASSET_Y, ASSET_X, FEE_TIER, LP_TOKEN_ID + 1, TEN_M, TEN_M, ALICE),
+			]);
+		}
+	}
+
+	mod fee_tier_tests {
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::{DispatchError, Perbill};
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y, BOB,
+			EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, Error, Event, FeeTiers, LpTokenToPair};
+
+		const LOW_FEE_TIER: Perbill = Perbill::from_perthousand(3);
+
+		fn setup_assets() {
+			// given created assets, held by alice to provide and by bob to swap
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, 4 * TEN_M, ASSET_X);
+			mint_asset(ALICE, 4 * TEN_M, ASSET_Y);
+			mint_asset(BOB, TEN_K + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+			// and the low fee tier allowed besides the mock's
+			assert_ok!(Dex::set_fee_tier(RuntimeOrigin::root(), LOW_FEE_TIER, true));
+		}
+
+		fn setup_x_y_pool(fee_tier: Perbill, reserve_x: u128, reserve_y: u128) {
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				fee_tier,
+				None
+			));
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				AssetAmountPair::new(X_Y_ID, reserve_x, reserve_y),
+				fee_tier,
+				None
+			));
+		}
+
+		fn swap_x_for_y_along_path_by_bob(expect_min_take: u128) {
+			assert_ok!(Dex::swap_exact_in_along_path(
+				RuntimeOrigin::signed(BOB),
+				vec![ASSET_X, ASSET_Y].try_into().expect("path should fit"),
+				TEN_K,
+				expect_min_take,
+				None
+			));
+		}
+
+		#[test]
+		fn set_fee_tier_should_require_root() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				// signed origins can't allow fee tiers
+				assert_noop!(
+					Dex::set_fee_tier(RuntimeOrigin::signed(ALICE), LOW_FEE_TIER, true),
+					DispatchError::BadOrigin
+				);
+
+				// root can, and the fee tiers should be kept sorted
+				assert_ok!(Dex::set_fee_tier(RuntimeOrigin::root(), LOW_FEE_TIER, true));
+				System::assert_last_event(
+					Event::FeeTierSet { fee_tier: LOW_FEE_TIER, allowed: true }.into(),
+				);
+				assert_eq!(FeeTiers::<Test>::get().into_inner(), vec![LOW_FEE_TIER, FEE_TIER]);
+
+				// and disallow them
+				assert_ok!(Dex::set_fee_tier(RuntimeOrigin::root(), FEE_TIER, false));
+				System::assert_last_event(
+					Event::FeeTierSet { fee_tier: FEE_TIER, allowed: false }.into(),
+				);
+				assert_eq!(FeeTiers::<Test>::get().into_inner(), vec![LOW_FEE_TIER]);
+			});
+		}
+
+		#[test]
+		fn set_fee_tier_should_be_bounded() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				// a fee tier can't keep the whole amount given
+				assert_noop!(
+					Dex::set_fee_tier(RuntimeOrigin::root(), Perbill::from_percent(100), true),
+					Error::<Test>::InvalidFeeTier
+				);
+
+				// and no more than the mock's 4 fee tiers can be allowed
+				for permille in 1..=3 {
+					assert_ok!(Dex::set_fee_tier(
+						RuntimeOrigin::root(),
+						Perbill::from_perthousand(permille),
+						true
+					));
+				}
+				assert_noop!(
+					Dex::set_fee_tier(RuntimeOrigin::root(), Perbill::from_perthousand(5), true),
+					Error::<Test>::TooManyFeeTiers
+				);
+			});
+		}
+
+		#[test]
+		fn create_pool_at_disallowed_fee_tier_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+
+				assert_noop!(
+					Dex::create_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Y,
+						LOW_FEE_TIER,
+						None
+					),
+					Error::<Test>::FeeTierNotAllowed
+				);
+			});
+		}
+
+		#[test]
+		fn pools_of_a_pair_at_different_fee_tiers_should_coexist() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool(FEE_TIER, TEN_M, TEN_M);
+				setup_x_y_pool(LOW_FEE_TIER, TEN_M, TEN_M);
+
+				// each pool should have its own LP token
+				assert_eq!(Dex::lp_token_of(ASSET_X, ASSET_Y, FEE_TIER), Some(LP_TOKEN_ID));
+				assert_eq!(Dex::lp_token_of(ASSET_X, ASSET_Y, LOW_FEE_TIER), Some(LP_TOKEN_ID + 1));
+				assert_eq!(
+					LpTokenToPair::<Test>::get(LP_TOKEN_ID + 1),
+					Some((X_Y_ID, LOW_FEE_TIER))
+				);
+
+				// a swap in one pool should keep its own fee, 0.3% of 10k
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_X, TEN_K),
+					0,
+					X_Y_ID,
+					LOW_FEE_TIER,
+					None
+				));
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, TEN_K),
+						take: AssetAmount::new(ASSET_Y, 9_960),
+						fee_tier: LOW_FEE_TIER,
+					}
+					.into(),
+				);
+
+				// and leave the other pool be
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, LOW_FEE_TIER),
+					Some((TEN_M + TEN_K, TEN_M - 9_960))
+				);
+				assert_eq!(Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER), Some((TEN_M, TEN_M)));
+			});
+		}
+
+		#[test]
+		fn swap_along_path_should_choose_the_fee_tier_taking_the_most() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool(FEE_TIER, TEN_M, TEN_M);
+				setup_x_y_pool(LOW_FEE_TIER, TEN_M, TEN_M);
+
+				// at the same reserves, the pool with the lower fee should be quoted
+				assert_eq!(Dex::quote_exact_in(ASSET_X, ASSET_Y, TEN_K), Some(9_960));
+				assert_eq!(Dex::quote_exact_out(ASSET_X, ASSET_Y, TEN_K), Some(10_041));
+
+				// and swapped through
+				swap_x_for_y_along_path_by_bob(9_960);
+				System::assert_has_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, TEN_K),
+						take: AssetAmount::new(ASSET_Y, 9_960),
+						fee_tier: LOW_FEE_TIER,
+					}
+					.into(),
+				);
+				assert_eq!(Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER), Some((TEN_M, TEN_M)));
+			});
+		}
+
+		#[test]
+		fn swap_along_path_should_prefer_a_better_price_over_a_lower_fee() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool(FEE_TIER, TEN_M, TEN_M);
+				// the pool with the lower fee prices y at twice as much x
+				setup_x_y_pool(LOW_FEE_TIER, TEN_M, TEN_M / 2);
+
+				// the pool with the higher fee should be quoted
+				assert_eq!(Dex::quote_exact_in(ASSET_X, ASSET_Y, TEN_K), Some(9_890));
+
+				// and swapped through
+				swap_x_for_y_along_path_by_bob(9_890);
+				System::assert_has_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, TEN_K),
+						take: AssetAmount::new(ASSET_Y, 9_890),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, LOW_FEE_TIER),
+					Some((TEN_M, TEN_M / 2))
+				);
+			});
+		}
+	}
+
+	mod parameter_tests {
+		use frame_support::traits::fungibles::Inspect;
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::{DispatchError, Perbill};
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER, GOVERNANCE};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, create_asset_amount_pair, init_test_ext, mint_asset,
+			TestFungibles, ALICE, ASSET_X, ASSET_Y, EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_M,
+			X_Y_ID,
+		};
+		use crate::{DexParameters, Error, Event, Parameters};
+
+		fn setup_x_y_pool() {
+			// given created assets and pool, and assets minted to alice
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+			mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+			mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+		}
+
+		#[test]
+		fn parameters_should_default_to_the_runtime_configuration() {
+			init_test_ext().execute_with(|| {
+				assert_eq!(
+					DexParameters::<Test>::get(),
+					Parameters {
+						protocol_share: Perbill::from_percent(20),
+						minimum_liquidity: 0,
+						lp_token_dust: 1,
+						flash_fee: Perbill::from_perthousand(5),
+					}
+				);
+			});
+		}
+
+		#[test]
+		fn admin_calls_should_require_admin_origin() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				// signed origins other than governance can't call them
+				let alice = || RuntimeOrigin::signed(ALICE);
+				assert_noop!(
+					Dex::set_protocol_share(alice(), Perbill::from_percent(10)),
+					DispatchError::BadOrigin
+				);
+				assert_noop!(Dex::set_min_liquidity(alice(), 1_000), DispatchError::BadOrigin);
+				assert_noop!(Dex::set_lp_token_dust(alice(), 10), DispatchError::BadOrigin);
+				assert_noop!(Dex::set_fee_tier(alice(), FEE_TIER, false), DispatchError::BadOrigin);
+				assert_noop!(
+					Dex::set_protocol_fee(alice(), X_Y_ID, FEE_TIER, true),
+					DispatchError::BadOrigin
+				);
+
+				// root can
+				assert_ok!(Dex::set_protocol_share(
+					RuntimeOrigin::root(),
+					Perbill::from_percent(10)
+				));
+				System::assert_last_event(
+					Event::ProtocolShareSet { share: Perbill::from_percent(10) }.into(),
+				);
+
+				// and so can governance
+				assert_ok!(Dex::set_min_liquidity(RuntimeOrigin::signed(GOVERNANCE), 1_000));
+				System::assert_last_event(
+					Event::MinimumLiquiditySet { minimum_liquidity: 1_000 }.into(),
+				);
+				assert_ok!(Dex::set_lp_token_dust(RuntimeOrigin::signed(GOVERNANCE), 10));
+				System::assert_last_event(Event::LpTokenDustSet { lp_token_dust: 10 }.into());
+
+				assert_eq!(
+					DexParameters::<Test>::get(),
+					Parameters {
+						protocol_share: Perbill::from_percent(10),
+						minimum_liquidity: 1_000,
+						lp_token_dust: 10,
+						flash_fee: Perbill::from_perthousand(5),
+					}
+				);
+			});
+		}
+
+		#[test]
+		fn parameters_should_be_bounded() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				// the protocol's share can't be above the mock's maximum of half the fees
+				assert_noop!(
+					Dex::set_protocol_share(RuntimeOrigin::root(), Perbill::from_percent(51)),
+					Error::<Test>::ProtocolShareTooHigh
+				);
+				assert_ok!(Dex::set_protocol_share(
+					RuntimeOrigin::root(),
+					Perbill::from_percent(50)
+				));
+
+				// the LP token dust can't be zero
+				assert_noop!(
+					Dex::set_lp_token_dust(RuntimeOrigin::root(), 0),
+					Error::<Test>::InvalidLpTokenDust
+				);
+				assert_ok!(Dex::set_lp_token_dust(RuntimeOrigin::root(), 10));
+
+				// the minimum liquidity can't be positive but below the dust
+				assert_noop!(
+					Dex::set_min_liquidity(RuntimeOrigin::root(), 9),
+					Error::<Test>::InvalidMinimumLiquidity
+				);
+				assert_ok!(Dex::set_min_liquidity(RuntimeOrigin::root(), 10));
+
+				// and the dust can't be raised above a positive minimum liquidity
+				assert_noop!(
+					Dex::set_lp_token_dust(RuntimeOrigin::root(), 11),
+					Error::<Test>::InvalidLpTokenDust
+				);
+			});
+		}
+
+		#[test]
+		fn set_min_liquidity_should_be_locked_on_first_provision() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				assert_ok!(Dex::set_min_liquidity(RuntimeOrigin::root(), 1_000));
+				setup_x_y_pool();
+
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+					FEE_TIER,
+					None
+				));
+
+				// the new minimum liquidity should be locked in the dex account
+				assert_account_has(Dex::dex_account_id(), LP_TOKEN_ID, 1_000);
+				assert_account_has(ALICE, LP_TOKEN_ID, TEN_M - 1_000);
+			});
+		}
+
+		#[test]
+		fn set_lp_token_dust_should_apply_to_new_pools() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				assert_ok!(Dex::set_lp_token_dust(RuntimeOrigin::root(), 10));
+				setup_x_y_pool();
+
+				assert_eq!(<TestFungibles as Inspect<u64>>::minimum_balance(LP_TOKEN_ID), 10);
+			});
+		}
+	}
+
+	mod stable_swap_tests {
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::{DispatchError, Perbill};
+
+		use crate::mock::{
+			Dex, RuntimeOrigin, System, Test, Timestamp, FEE_TIER, PROTOCOL_FEE_RECIPIENT,
+		};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			BOB, EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{
+			math, Amplification, AssetAmount, AssetAmountPair, Curve, Error, Event, Pools,
+		};
+
+		const AMPLIFICATION: u32 = 100;
+		const LOW_FEE_TIER: Perbill = Perbill::from_perthousand(3);
+		const DAY_IN_SECS: u64 = 86_400;
+
+		fn setup_assets() {
+			// given created assets, held by alice to provide and by bob to swap
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, 4 * TEN_M, ASSET_X);
+			mint_asset(ALICE, 4 * TEN_M, ASSET_Y);
+			mint_asset(BOB, 10 * TEN_K + EXISTENTIAL_DEPOSIT, ASSET_X);
+		}
+
+		fn setup_stable_x_y_pool(fee_tier: Perbill) {
+			assert_ok!(Dex::create_stable_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				fee_tier,
+				AMPLIFICATION,
+				None
+			));
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+				fee_tier,
+				None
+			));
+		}
+
+		#[test]
+		fn create_stable_pool_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+
+				assert_ok!(Dex::create_stable_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					AMPLIFICATION,
+					None
+				));
+				System::assert_last_event(
+					Event::LpTokenCreated { lp_token_id: LP_TOKEN_ID, fee_tier: FEE_TIER }.into(),
+				);
+
+				// the pool should price swaps by the StableSwap invariant
+				assert_eq!(
+					Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist").curve,
+					Curve::StableSwap(Amplification::constant(AMPLIFICATION))
+				);
+			});
+		}
+
+		#[test]
+		fn create_stable_pool_with_invalid_amplification_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+
+				// zero, or above the mock's maximum of a million
+				for amplification in [0, 1_000_001] {
+					assert_noop!(
+						Dex::create_stable_pool(
+							RuntimeOrigin::signed(ALICE),
+							ASSET_X,
+							ASSET_Y,
+							FEE_TIER,
+							amplification,
+							None
+						),
+						Error::<Test>::InvalidAmplification
+					);
+				}
+			});
+		}
+
+		#[test]
+		fn provide_and_remove_liquidity_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_stable_x_y_pool(FEE_TIER);
+
+				// the first provision should mint D, the sum of balanced reserves
+				assert_account_has(ALICE, LP_TOKEN_ID, 2 * TEN_M);
+
+				// and removing a quarter of it should return a quarter of each reserve
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					X_Y_ID,
+					FEE_TIER,
+					TEN_M / 2,
+					TEN_M / 4,
+					TEN_M / 4,
+					None,
+					None
+				));
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((3 * TEN_M / 4, 3 * TEN_M / 4))
+				);
+			});
+		}
+
+		#[test]
+		fn swaps_near_the_peg_should_slip_less_than_constant_product() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_stable_x_y_pool(FEE_TIER);
+
+				// a constant product pool with the same reserves would give 98_029 for 100_000
+				assert_eq!(math::get_amount_out(10 * TEN_K, TEN_M, TEN_M, FEE_TIER), Ok(98_029));
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_X, 10 * TEN_K),
+					98_995,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 10 * TEN_K),
+						take: AssetAmount::new(ASSET_Y, 98_995),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+			});
+		}
+
+		#[test]
+		fn exact_output_swaps_near_the_peg_should_cost_less_than_constant_product() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_stable_x_y_pool(FEE_TIER);
+
+				// a constant product pool with the same reserves would charge 99_970 for 98_000
+				assert_eq!(math::get_amount_in(98_000, TEN_M, TEN_M, FEE_TIER), Ok(99_970));
+				assert_ok!(Dex::swap_limit_give(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_Y, 98_000),
+					98_995,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 98_995),
+						take: AssetAmount::new(ASSET_Y, 98_000),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+			});
+		}
+
+		#[test]
+		fn quotes_should_prefer_a_stable_pool_giving_more() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				assert_ok!(Dex::set_fee_tier(RuntimeOrigin::root(), LOW_FEE_TIER, true));
+				setup_stable_x_y_pool(FEE_TIER);
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					LOW_FEE_TIER,
+					None
+				));
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+					LOW_FEE_TIER,
+					None
+				));
+
+				// the constant product pool would give 98_715 despite its lower fee
+				assert_eq!(Dex::quote_exact_in(ASSET_X, ASSET_Y, 10 * TEN_K), Some(98_995));
+			});
+		}
+
+		#[test]
+		fn ramp_amplification_should_be_validated() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_stable_x_y_pool(FEE_TIER);
+				let ramp = |origin, future, future_time| {
+					Dex::ramp_amplification(origin, X_Y_ID, FEE_TIER, future, future_time)
+				};
+
+				// only the admin origin can ramp
+				assert_noop!(
+					ramp(RuntimeOrigin::signed(ALICE), 2 * AMPLIFICATION, DAY_IN_SECS),
+					DispatchError::BadOrigin
+				);
+				// over no less than the mock's minimum duration of a day
+				assert_noop!(
+					ramp(RuntimeOrigin::root(), 2 * AMPLIFICATION, DAY_IN_SECS - 1),
+					Error::<Test>::AmplificationRampTooShort
+				);
+				// by no more than a factor of 10 either way
+				assert_noop!(
+					ramp(RuntimeOrigin::root(), 10 * AMPLIFICATION + 1, DAY_IN_SECS),
+					Error::<Test>::AmplificationChangeTooLarge
+				);
+				assert_noop!(
+					ramp(RuntimeOrigin::root(), AMPLIFICATION / 10 - 1, DAY_IN_SECS),
+					Error::<Test>::AmplificationChangeTooLarge
+				);
+				assert_noop!(
+					ramp(RuntimeOrigin::root(), 0, DAY_IN_SECS),
+					Error::<Test>::InvalidAmplification
+				);
+			});
+		}
+
+		#[test]
+		fn ramp_amplification_of_constant_product_pool_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					None
+				));
+
+				assert_noop!(
+					Dex::ramp_amplification(
+						RuntimeOrigin::root(),
+						X_Y_ID,
+						FEE_TIER,
+						AMPLIFICATION,
+						DAY_IN_SECS
+					),
+					Error::<Test>::NotStableSwapPool
+				);
+			});
+		}
+
+		#[test]
+		fn ramp_amplification_should_move_the_coefficient_linearly() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_stable_x_y_pool(FEE_TIER);
+
+				assert_ok!(Dex::ramp_amplification(
+					RuntimeOrigin::root(),
+					X_Y_ID,
+					FEE_TIER,
+					2 * AMPLIFICATION,
+					DAY_IN_SECS
+				));
+				System::assert_last_event(
+					Event::AmplificationRamped {
+						pair: X_Y_ID,
+						fee_tier: FEE_TIER,
+						initial: AMPLIFICATION,
+						future: 2 * AMPLIFICATION,
+						future_time: DAY_IN_SECS,
+					}
+					.into(),
+				);
+
+				// halfway through the ramp, swaps should be priced by the coefficient halfway
+				let amplification =
+					match Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist").curve {
+						Curve::StableSwap(amplification) => amplification,
+						_ => panic!("pool should be a StableSwap pool"),
+					};
+				assert_eq!(amplification.at(DAY_IN_SECS / 2), 3 * AMPLIFICATION / 2);
+				assert_eq!(amplification.at(2 * DAY_IN_SECS), 2 * AMPLIFICATION);
+
+				Timestamp::set_timestamp(DAY_IN_SECS / 2 * 1_000);
+				assert_eq!(Dex::quote_exact_in(ASSET_X, ASSET_Y, 10 * TEN_K), Some(98_996));
+			});
+		}
+
+		#[test]
+		fn ramping_amplification_should_not_mint_protocol_fee() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_stable_x_y_pool(FEE_TIER);
+
+				// given reserves far from the peg, where D grows with the coefficient
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(ALICE),
+					AssetAmount::new(ASSET_X, 2 * TEN_M),
+					0,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				// and the protocol fee turned on after that swap, then a ramp and no swaps
+				assert_ok!(Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, true));
+				assert_ok!(Dex::ramp_amplification(
+					RuntimeOrigin::root(),
+					X_Y_ID,
+					FEE_TIER,
+					10 * AMPLIFICATION,
+					DAY_IN_SECS
+				));
+				Timestamp::set_timestamp(DAY_IN_SECS * 1_000);
+
+				let pool = Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+				assert_eq!(pool.amplification_last, AMPLIFICATION);
+				let (x, y) =
+					(pool.asset_amounts.amount_x.balance, pool.asset_amounts.amount_y.balance);
+				let ramped_d =
+					math::stable_swap_d(x, y, 10 * AMPLIFICATION).expect("D should converge");
+				assert!(ramped_d > pool.root_k_last, "the ramp should have grown D");
+
+				// turning the fee off should mint nothing, as no swap paid a fee since
+				assert_ok!(Dex::set_protocol_fee(RuntimeOrigin::root(), X_Y_ID, FEE_TIER, false));
+				assert_account_has(PROTOCOL_FEE_RECIPIENT, LP_TOKEN_ID, 0);
+			});
+		}
+	}
+
+	mod constant_sum_tests {
+		use frame_support::{assert_noop, assert_ok};
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			BOB, EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, Curve, Error, Event, Pools};
+
+		fn setup_constant_sum_x_y_pool() {
+			// given created assets, held by alice to provide and by bob to swap
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_X);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_Y);
+			mint_asset(BOB, 10 * TEN_K + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+			assert_ok!(Dex::create_constant_sum_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+				FEE_TIER,
+				None
+			));
+		}
+
+		#[test]
+		fn create_constant_sum_pool_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_constant_sum_x_y_pool();
+
+				assert_eq!(
+					Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist").curve,
+					Curve::ConstantSum
+				);
+				// the first provision should mint the sum of the reserves
+				assert_account_has(ALICE, LP_TOKEN_ID, 2 * TEN_M);
+			});
+		}
+
+		#[test]
+		fn swaps_should_be_one_for_one_less_the_fee() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_constant_sum_x_y_pool();
+
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_X, 5 * TEN_K),
+					49_500,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				assert_account_has(BOB, ASSET_Y, 49_500);
+
+				// and the amount given for an exact output is rounded up
+				assert_ok!(Dex::swap_limit_give(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_Y, 4 * TEN_K),
+					40_405,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 40_405),
+						take: AssetAmount::new(ASSET_Y, 4 * TEN_K),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((TEN_M + 90_405, TEN_M - 89_500))
+				);
+			});
+		}
+
+		#[test]
+		fn swaps_should_not_drain_the_reserve() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_constant_sum_x_y_pool();
+
+				// giving enough to take the whole reserve of `ASSET_Y` after the fee
+				assert_noop!(
+					Dex::swap_limit_take(
+						RuntimeOrigin::signed(ALICE),
+						AssetAmount::new(ASSET_X, 10_101_011),
+						0,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::SwapCannotBeSatisfied
+				);
+				assert_noop!(
+					Dex::swap_limit_give(
+						RuntimeOrigin::signed(ALICE),
+						AssetAmount::new(ASSET_Y, TEN_M),
+						2 * TEN_M,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::SwapCannotBeSatisfied
+				);
+			});
+		}
+	}
+
+	mod custom_curve_tests {
+		use frame_support::{assert_noop, assert_ok};
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, CUSTOM_CURVE_ID, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			BOB, EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, Curve, Error, Pools};
+
+		#[test]
+		fn create_custom_pool_should_price_swaps_by_the_resolved_curve() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				// given created assets, held by alice to provide and by bob to swap
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				mint_asset(ALICE, 2 * TEN_M, ASSET_X);
+				mint_asset(ALICE, 2 * TEN_M, ASSET_Y);
+				mint_asset(BOB, 10 * TEN_K + EXISTENTIAL_DEPOSIT, ASSET_X);
+
+				assert_ok!(Dex::create_custom_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					CUSTOM_CURVE_ID,
+					None
+				));
+				assert_eq!(
+					Pools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist").curve,
+					Curve::Custom(CUSTOM_CURVE_ID)
+				);
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+					FEE_TIER,
+					None
+				));
+				// the mock resolves the curve to the constant sum, minting the sum of the reserves
+				assert_account_has(ALICE, LP_TOKEN_ID, 2 * TEN_M);
+
+				// and swapping one for one, less the fee
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_X, 5 * TEN_K),
+					49_500,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				assert_account_has(BOB, ASSET_Y, 49_500);
+			});
+		}
+
+		#[test]
+		fn create_custom_pool_should_fail_for_an_unknown_curve() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+
+				assert_noop!(
+					Dex::create_custom_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Y,
+						FEE_TIER,
+						CUSTOM_CURVE_ID + 1,
+						None
+					),
+					Error::<Test>::UnknownCurve
+				);
+			});
+		}
+	}
+
+	mod weighted_pool_tests {
+		use frame_support::{assert_noop, assert_ok, BoundedVec};
+		use sp_runtime::Perbill;
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			ASSET_Z, BOB, LP_TOKEN_ID, TEN_K, TEN_M,
+		};
+		use crate::{AssetAmount, Error, Event, WeightedPools};
+
+		const HEAVY: Perbill = Perbill::from_percent(80);
+		const LIGHT: Perbill = Perbill::from_percent(20);
+		/// The invariant of a pool of `4 * TEN_M` of an asset weighing 80% and `TEN_M` of one
+		/// weighing 20%, `(4 * TEN_M)^0.8 * TEN_M^0.2`, rounded down.
+		const INVARIANT: u128 = 30_314_331;
+
+		fn weights(
+			assets: &[(u32, Perbill)],
+		) -> BoundedVec<(u32, Perbill), <Test as crate::Config>::MaxWeightedPoolAssets> {
+			BoundedVec::truncate_from(assets.to_vec())
+		}
+
+		fn amounts(
+			amounts: &[u128],
+		) -> BoundedVec<u128, <Test as crate::Config>::MaxWeightedPoolAssets> {
+			BoundedVec::truncate_from(amounts.to_vec())
+		}
+
+		fn reserves() -> Vec<u128> {
+			WeightedPools::<Test>::get(LP_TOKEN_ID)
+				.expect("pool should exist")
+				.assets
+				.iter()
+				.map(|asset| asset.reserve)
+				.collect()
+		}
+
+		fn setup_assets() {
+			// given created assets, held by alice to join and by bob to join and swap
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			create_asset(ASSET_Z);
+			for who in [ALICE, BOB] {
+				mint_asset(who, 5 * TEN_M, ASSET_X);
+				mint_asset(who, 5 * TEN_M, ASSET_Y);
+			}
+		}
+
+		/// Creates a pool of `ASSET_X` weighing 80% and `ASSET_Y` weighing 20%, and joins it on
+		/// behalf of alice with `4 * TEN_M` and `TEN_M` of them, at a price of one to one.
+		fn setup_x_y_pool() {
+			assert_ok!(Dex::create_weighted_pool(
+				RuntimeOrigin::signed(ALICE),
+				weights(&[(ASSET_X, HEAVY), (ASSET_Y, LIGHT)]),
+				FEE_TIER,
+				None
+			));
+			assert_ok!(Dex::join_weighted_pool(
+				RuntimeOrigin::signed(ALICE),
+				LP_TOKEN_ID,
+				amounts(&[4 * TEN_M, TEN_M]),
+				INVARIANT,
+				None
+			));
+		}
+
+		#[test]
+		fn create_weighted_pool_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+
+				// the assets can be given in any order
+				let assets = [
+					(ASSET_Z, Perbill::from_percent(25)),
+					(ASSET_X, Perbill::from_percent(50)),
+					(ASSET_Y, Perbill::from_percent(25)),
+				];
+				assert_ok!(Dex::create_weighted_pool(
+					RuntimeOrigin::signed(ALICE),
+					weights(&assets),
+					FEE_TIER,
+					None
+				));
+
+				// and should be kept in increasing order of their ids
+				System::assert_last_event(
+					Event::WeightedPoolCreated {
+						lp_token_id: LP_TOKEN_ID,
+						assets: weights(&[assets[1], assets[2], assets[0]]),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				assert_eq!(reserves(), vec![0, 0, 0]);
+			});
+		}
+
+		#[test]
+		fn create_weighted_pool_with_invalid_assets_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+
+				// a single asset, or an asset given twice
+				for assets in [
+					vec![(ASSET_X, Perbill::one())],
+					vec![
+						(ASSET_X, Perbill::from_percent(50)),
+						(ASSET_X, Perbill::from_percent(50)),
+					],
+				] {
+					assert_noop!(
+						Dex::create_weighted_pool(
+							RuntimeOrigin::signed(ALICE),
+							weights(&assets),
+							FEE_TIER,
+							None
+						),
+						Error::<Test>::InvalidWeightedPoolAssets
+					);
+				}
+			});
+		}
+
+		#[test]
+		fn create_weighted_pool_with_invalid_weights_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+
+				// a weight below 1%, or weights not adding up to 100%
+				for (weight_x, weight_y) in [
+					(Perbill::from_perthousand(995), Perbill::from_perthousand(5)),
+					(Perbill::from_percent(50), Perbill::from_percent(40)),
+					(Perbill::from_percent(60), Perbill::from_percent(50)),
+				] {
+					assert_noop!(
+						Dex::create_weighted_pool(
+							RuntimeOrigin::signed(ALICE),
+							weights(&[(ASSET_X, weight_x), (ASSET_Y, weight_y)]),
+							FEE_TIER,
+							None
+						),
+						Error::<Test>::InvalidWeights
+					);
+				}
+
+				// nor at a fee tier that isn't allowed
+				assert_noop!(
+					Dex::create_weighted_pool(
+						RuntimeOrigin::signed(ALICE),
+						weights(&[(ASSET_X, HEAVY), (ASSET_Y, LIGHT)]),
+						Perbill::from_percent(2),
+						None
+					),
+					Error::<Test>::FeeTierNotAllowed
+				);
+			});
+		}
+
+		#[test]
+		fn create_weighted_pool_fails_for_unknown_asset() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+
+				// an asset id that was never created
+				let unknown = ASSET_Z + 1;
+				assert_noop!(
+					Dex::create_weighted_pool(
+						RuntimeOrigin::signed(ALICE),
+						weights(&[(ASSET_X, HEAVY), (unknown, LIGHT)]),
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::AssetDoesNotExist
+				);
+				assert!(WeightedPools::<Test>::iter_keys().next().is_none());
+			});
+		}
+
+		#[test]
+		fn first_join_should_mint_the_invariant() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				assert_ok!(Dex::create_weighted_pool(
+					RuntimeOrigin::signed(ALICE),
+					weights(&[(ASSET_X, HEAVY), (ASSET_Y, LIGHT)]),
+					FEE_TIER,
+					None
+				));
+
+				// an amount for every asset, none of them zero, is needed
+				assert_noop!(
+					Dex::join_weighted_pool(
+						RuntimeOrigin::signed(ALICE),
+						LP_TOKEN_ID,
+						amounts(&[4 * TEN_M]),
+						0,
+						None
+					),
+					Error::<Test>::AmountsDontMatchPoolAssets
+				);
+				assert_noop!(
+					Dex::join_weighted_pool(
+						RuntimeOrigin::signed(ALICE),
+						LP_TOKEN_ID,
+						amounts(&[4 * TEN_M, 0]),
+						0,
+						None
+					),
+					Error::<Test>::InsufficientLiquidityProvided
+				);
+
+				assert_ok!(Dex::join_weighted_pool(
+					RuntimeOrigin::signed(ALICE),
+					LP_TOKEN_ID,
+					amounts(&[4 * TEN_M, TEN_M]),
+					INVARIANT,
+					None
+				));
+				assert_account_has(ALICE, LP_TOKEN_ID, INVARIANT);
+				assert_eq!(reserves(), vec![4 * TEN_M, TEN_M]);
+			});
+		}
+
+		#[test]
+		fn join_weighted_pool_should_take_amounts_at_the_ratio_of_the_reserves() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool();
+
+				// bob's `ASSET_X` is the limiting amount, a hundredth of its reserve
+				assert_ok!(Dex::join_weighted_pool(
+					RuntimeOrigin::signed(BOB),
+					LP_TOKEN_ID,
+					amounts(&[40 * TEN_K, 20 * TEN_K]),
+					0,
+					None
+				));
+				System::assert_last_event(
+					Event::WeightedPoolJoined {
+						who: BOB,
+						lp_token_id: LP_TOKEN_ID,
+						amounts: BoundedVec::truncate_from(vec![
+							AssetAmount::new(ASSET_X, 40 * TEN_K),
+							AssetAmount::new(ASSET_Y, 10 * TEN_K),
+						]),
+						lp_tokens: INVARIANT / 100,
+					}
+					.into(),
+				);
+				assert_account_has(BOB, LP_TOKEN_ID, INVARIANT / 100);
+				assert_eq!(reserves(), vec![4 * TEN_M + 40 * TEN_K, TEN_M + 10 * TEN_K]);
+			});
+		}
+
+		#[test]
+		fn exit_weighted_pool_should_return_a_share_of_each_reserve() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool();
+
+				// half the LP tokens, rounded down, for half of each reserve, rounded down
+				let lp_tokens = INVARIANT / 2;
+				assert_noop!(
+					Dex::exit_weighted_pool(
+						RuntimeOrigin::signed(ALICE),
+						LP_TOKEN_ID,
+						lp_tokens,
+						amounts(&[2 * TEN_M, 0]),
+						None
+					),
+					Error::<Test>::MinimumWithdrawalNotReached
+				);
+				assert_ok!(Dex::exit_weighted_pool(
+					RuntimeOrigin::signed(ALICE),
+					LP_TOKEN_ID,
+					lp_tokens,
+					amounts(&[2 * TEN_M - 1, TEN_M / 2 - 1]),
+					None
+				));
+				assert_account_has(ALICE, LP_TOKEN_ID, INVARIANT - lp_tokens);
+				assert_account_has(ALICE, ASSET_X, 3 * TEN_M - 1);
+				assert_account_has(ALICE, ASSET_Y, 9 * TEN_M / 2 - 1);
+				assert_eq!(reserves(), vec![2 * TEN_M + 1, TEN_M / 2 + 1]);
+			});
+		}
+
+		#[test]
+		fn join_and_exit_by_a_single_asset_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool();
+
+				// giving 0.25% of the `ASSET_X` reserve, weighing 80%, mints a bit less than 0.2%
+				// of the LP tokens, as the fifth of it not matching the reserves pays the fee
+				assert_noop!(
+					Dex::join_weighted_pool_single(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						AssetAmount::new(ASSET_X, 10 * TEN_K),
+						60_493,
+						None
+					),
+					Error::<Test>::MinimumLpTokensNotReached
+				);
+				assert_ok!(Dex::join_weighted_pool_single(
+					RuntimeOrigin::signed(BOB),
+					LP_TOKEN_ID,
+					AssetAmount::new(ASSET_X, 10 * TEN_K),
+					60_492,
+					None
+				));
+				assert_account_has(BOB, LP_TOKEN_ID, 60_492);
+				assert_eq!(reserves(), vec![4 * TEN_M + 10 * TEN_K, TEN_M]);
+
+				// and burning about 0.33% of the LP tokens for `ASSET_Y` alone, weighing 20%, takes
+				// about five times as large a share of its reserve, as if the share of `ASSET_X`
+				// was swapped for it
+				assert_noop!(
+					Dex::exit_weighted_pool_single(
+						RuntimeOrigin::signed(ALICE),
+						LP_TOKEN_ID,
+						10 * TEN_K,
+						ASSET_Z,
+						0,
+						None
+					),
+					Error::<Test>::AssetNotInPool
+				);
+				assert_ok!(Dex::exit_weighted_pool_single(
+					RuntimeOrigin::signed(ALICE),
+					LP_TOKEN_ID,
+					10 * TEN_K,
+					ASSET_Y,
+					162_220,
+					None
+				));
+				assert_account_has(ALICE, LP_TOKEN_ID, INVARIANT - 10 * TEN_K);
+				assert_account_has(ALICE, ASSET_Y, 4 * TEN_M + 162_220);
+				assert_eq!(reserves(), vec![4 * TEN_M + 10 * TEN_K, TEN_M - 162_220]);
+			});
+		}
+
+		#[test]
+		fn swap_weighted_limit_take_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool();
+
+				// the reserves and weights price the assets one to one, so giving `10 * TEN_K` of
+				// `ASSET_X` should take a bit less of `ASSET_Y`, after the fee and price impact
+				let give = AssetAmount::new(ASSET_X, 10 * TEN_K);
+				assert_noop!(
+					Dex::swap_weighted_limit_take(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						give.clone(),
+						ASSET_Y,
+						98_391,
+						None
+					),
+					Error::<Test>::MinimumOutputNotReached
+				);
+				assert_ok!(Dex::swap_weighted_limit_take(
+					RuntimeOrigin::signed(BOB),
+					LP_TOKEN_ID,
+					give.clone(),
+					ASSET_Y,
+					98_390,
+					None
+				));
+				System::assert_last_event(
+					Event::WeightedTokenSwapped {
+						who: BOB,
+						lp_token_id: LP_TOKEN_ID,
+						give,
+						take: AssetAmount::new(ASSET_Y, 98_390),
+					}
+					.into(),
+				);
+				assert_account_has(BOB, ASSET_Y, 5 * TEN_M + 98_390);
+				assert_eq!(reserves(), vec![4 * TEN_M + 10 * TEN_K, TEN_M - 98_390]);
+			});
+		}
+
+		#[test]
+		fn swap_weighted_limit_give_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool();
+
+				let take = AssetAmount::new(ASSET_Y, 10 * TEN_K);
+				assert_noop!(
+					Dex::swap_weighted_limit_give(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						take.clone(),
+						ASSET_X,
+						101_646,
+						None
+					),
+					Error::<Test>::MaximumInputExceeded
+				);
+				assert_ok!(Dex::swap_weighted_limit_give(
+					RuntimeOrigin::signed(BOB),
+					LP_TOKEN_ID,
+					take,
+					ASSET_X,
+					101_647,
+					None
+				));
+				assert_account_has(BOB, ASSET_X, 5 * TEN_M - 101_647);
+				assert_account_has(BOB, ASSET_Y, 5 * TEN_M + 10 * TEN_K);
+				assert_eq!(reserves(), vec![4 * TEN_M + 101_647, TEN_M - 10 * TEN_K]);
+			});
+		}
+
+		#[test]
+		fn invalid_weighted_swaps_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_assets();
+				setup_x_y_pool();
+
+				// more than 30% of the reserve given or taken
+				assert_noop!(
+					Dex::swap_weighted_limit_take(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						AssetAmount::new(ASSET_X, 12 * TEN_M / 10 + 1),
+						ASSET_Y,
+						0,
+						None
+					),
+					Error::<Test>::WeightedAmountTooLarge
+				);
+				assert_noop!(
+					Dex::swap_weighted_limit_give(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						AssetAmount::new(ASSET_Y, 3 * TEN_M / 10 + 1),
+						ASSET_X,
+						u128::MAX,
+						None
+					),
+					Error::<Test>::WeightedAmountTooLarge
+				);
+
+				// an asset the pool doesn't hold, or the same asset on both sides
+				assert_noop!(
+					Dex::swap_weighted_limit_take(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						AssetAmount::new(ASSET_Z, TEN_K),
+						ASSET_Y,
+						0,
+						None
+					),
+					Error::<Test>::AssetNotInPool
+				);
+				assert_noop!(
+					Dex::swap_weighted_limit_take(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID,
+						AssetAmount::new(ASSET_X, TEN_K),
+						ASSET_X,
+						0,
+						None
+					),
+					Error::<Test>::InvalidPair
+				);
+
+				// or a pool that doesn't exist
+				assert_noop!(
+					Dex::swap_weighted_limit_take(
+						RuntimeOrigin::signed(BOB),
+						LP_TOKEN_ID + 1,
+						AssetAmount::new(ASSET_X, TEN_K),
+						ASSET_Y,
+						0,
+						None
+					),
+					Error::<Test>::PoolDoesntExists
+				);
+			});
+		}
+	}
+
+	mod concentrated_liquidity_tests {
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::DispatchResult;
+
+		use crate::math::MIN_SQRT_PRICE;
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			ASSET_Z, BOB, EXISTENTIAL_DEPOSIT, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, ConcentratedPools, Error, Event, Positions};
+
+		/// The square root of a price of one in Q64.64, at tick 0.
+		const SQRT_PRICE_ONE: u128 = 1 << 64;
+		const TICK_SPACING: u32 = 10;
+		/// Liquidity of each of alice's positions.
+		const LIQUIDITY: u128 = 1_000_000_000;
+		/// Amount of each asset minted to alice.
+		const ALICE_BALANCE: u128 = 10 * TEN_M;
+
+		/// Returns the liquidity and tick of the pool of x and y.
+		fn pool_state() -> (u128, i32) {
+			let pool = ConcentratedPools::<Test>::get(X_Y_ID, FEE_TIER).expect("pool should exist");
+			(pool.liquidity, pool.tick)
+		}
+
+		fn open_position(who: u64, tick_lower: i32, tick_upper: i32) -> DispatchResult {
+			Dex::open_position(
+				RuntimeOrigin::signed(who),
+				X_Y_ID,
+				FEE_TIER,
+				tick_lower,
+				tick_upper,
+				LIQUIDITY,
+				ALICE_BALANCE,
+				ALICE_BALANCE,
+				None,
+			)
+		}
+
+		/// Creates a pool of x and y at a price of one, in which alice opens position 0 from tick
+		/// -600 to 600, containing the price, and position 1 from 600 to 1200, above it.
+		fn setup_concentrated_x_y_pool() {
+			// given created assets, held by alice to provide and by bob to swap
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, ALICE_BALANCE, ASSET_X);
+			mint_asset(ALICE, ALICE_BALANCE, ASSET_Y);
+			mint_asset(BOB, 4 * TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+			assert_ok!(Dex::create_concentrated_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				TICK_SPACING,
+				SQRT_PRICE_ONE,
+				None
+			));
+			assert_ok!(open_position(ALICE, -600, 600));
+			assert_ok!(open_position(ALICE, 600, 1_200));
+		}
+
+		/// Bob swaps 40M of y for x, raising the price past tick 600, then x for 20M of y,
+		/// lowering it back below.
+		fn swap_across_tick_600() {
+			assert_ok!(Dex::swap_concentrated_limit_take(
+				RuntimeOrigin::signed(BOB),
+				AssetAmount::new(ASSET_Y, 4 * TEN_M),
+				38_091_571,
+				X_Y_ID,
+				FEE_TIER,
+				None
+			));
+			assert_ok!(Dex::swap_concentrated_limit_give(
+				RuntimeOrigin::signed(BOB),
+				AssetAmount::new(ASSET_Y, 2 * TEN_M),
+				19_058_942,
+				X_Y_ID,
+				FEE_TIER,
+				None
+			));
+		}
+
+		#[test]
+		fn open_position_should_take_the_amounts_its_liquidity_is_worth() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				// position 0 contains the price, and takes both assets, position 1 is above it
+				// and only takes x
+				System::assert_last_event(
+					Event::PositionOpened {
+						who: ALICE,
+						position_id: 1,
+						fee_tier: FEE_TIER,
+						tick_lower: 600,
+						tick_upper: 1_200,
+						liquidity: LIQUIDITY,
+						provided: AssetAmountPair::new(X_Y_ID, 28_679_631, 0),
+					}
+					.into(),
+				);
+				assert_account_has(ALICE, ASSET_X, ALICE_BALANCE - 29_553_011 - 28_679_631);
+				assert_account_has(ALICE, ASSET_Y, ALICE_BALANCE - 29_553_011);
+
+				// and only position 0 is traded against
+				assert_eq!(pool_state(), (LIQUIDITY, 0));
+			});
+		}
+
+		#[test]
+		fn swaps_should_cross_ticks() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				// when bob gives y past tick 600, position 1 is traded against instead of 0
+				assert_ok!(Dex::swap_concentrated_limit_take(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_Y, 4 * TEN_M),
+					38_091_571,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				assert_account_has(BOB, ASSET_X, 38_091_571);
+				assert_eq!(pool_state(), (LIQUIDITY, 776));
+
+				// and when he takes y back below it, position 0 is again
+				assert_ok!(Dex::swap_concentrated_limit_give(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_Y, 2 * TEN_M),
+					19_058_942,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				System::assert_last_event(
+					Event::ConcentratedTokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 19_058_942),
+						take: AssetAmount::new(ASSET_Y, 2 * TEN_M),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				assert_account_has(BOB, ASSET_X, 38_091_571 - 19_058_942);
+				assert_account_has(BOB, ASSET_Y, 2 * TEN_M + EXISTENTIAL_DEPOSIT);
+				assert_eq!(pool_state(), (LIQUIDITY, 388));
+			});
+		}
+
+		#[test]
+		fn swaps_should_fail_beyond_the_pools_liquidity() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				// the pool holds less than 60M of x
+				assert_noop!(
+					Dex::swap_concentrated_limit_give(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_X, 6 * TEN_M),
+						u128::MAX,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::SwapCannotBeSatisfied
+				);
+				assert_noop!(
+					Dex::swap_concentrated_limit_take(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_Y, 1_000 * TEN_M),
+						0,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::SwapCannotBeSatisfied
+				);
+				assert_noop!(
+					Dex::swap_concentrated_limit_take(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_Y, 4 * TEN_M),
+						38_091_572,
+						X_Y_ID,
+						FEE_TIER,
+						None
+					),
+					Error::<Test>::MinimumOutputNotReached
+				);
+			});
+		}
+
+		#[test]
+		fn positions_should_earn_fees_within_their_range() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+				swap_across_tick_600();
+
+				// the fees of each swap are split by the liquidity traded against on either
+				// side of tick 600
+				assert_ok!(Dex::collect_position_fees(RuntimeOrigin::signed(ALICE), 0, None));
+				System::assert_last_event(
+					Event::PositionFeesCollected {
+						who: ALICE,
+						position_id: 0,
+						collected: AssetAmountPair::new(X_Y_ID, 104_341, 307_605),
+					}
+					.into(),
+				);
+				assert_ok!(Dex::collect_position_fees(RuntimeOrigin::signed(ALICE), 1, None));
+				System::assert_last_event(
+					Event::PositionFeesCollected {
+						who: ALICE,
+						position_id: 1,
+						collected: AssetAmountPair::new(X_Y_ID, 86_248, 92_394),
+					}
+					.into(),
+				);
+
+				// and fees are only collected once
+				assert_ok!(Dex::collect_position_fees(RuntimeOrigin::signed(ALICE), 0, None));
+				System::assert_last_event(
+					Event::PositionFeesCollected {
+						who: ALICE,
+						position_id: 0,
+						collected: AssetAmountPair::new(X_Y_ID, 0, 0),
+					}
+					.into(),
+				);
+			});
+		}
+
+		#[test]
+		fn decrease_position_should_pay_out_liquidity_and_keep_fees_until_collected() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+				swap_across_tick_600();
+				let alice_x = ALICE_BALANCE - 29_553_011 - 28_679_631;
+				let alice_y = ALICE_BALANCE - 29_553_011;
+
+				assert_noop!(
+					Dex::decrease_position(
+						RuntimeOrigin::signed(ALICE),
+						0,
+						LIQUIDITY,
+						10_329_789,
+						0,
+						None
+					),
+					Error::<Test>::MinimumWithdrawalNotReached
+				);
+				assert_ok!(Dex::decrease_position(
+					RuntimeOrigin::signed(ALICE),
+					0,
+					LIQUIDITY,
+					10_329_788,
+					49_153_008,
+					None
+				));
+				assert_account_has(ALICE, ASSET_X, alice_x + 10_329_788);
+				assert_account_has(ALICE, ASSET_Y, alice_y + 49_153_008);
+				assert_eq!(pool_state(), (0, 388));
+
+				// the position is kept with the fees it earned, and closed once they're collected
+				assert_ok!(Dex::collect_position_fees(RuntimeOrigin::signed(ALICE), 0, None));
+				assert_account_has(ALICE, ASSET_X, alice_x + 10_329_788 + 104_341);
+				assert_account_has(ALICE, ASSET_Y, alice_y + 49_153_008 + 307_605);
+				assert!(Positions::<Test>::get(0).is_none());
+
+				// and removing more liquidity than a position has fails
+				assert_noop!(
+					Dex::decrease_position(
+						RuntimeOrigin::signed(ALICE),
+						1,
+						LIQUIDITY + 1,
+						0,
+						0,
+						None
+					),
+					Error::<Test>::InsufficientPositionLiquidity
+				);
+			});
+		}
+
+		#[test]
+		fn only_the_owner_should_manage_a_position() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				assert_noop!(
+					Dex::decrease_position(RuntimeOrigin::signed(BOB), 0, LIQUIDITY, 0, 0, None),
+					Error::<Test>::NotPositionOwner
+				);
+
+				// when alice transfers position 0 to bob
+				assert_ok!(Dex::transfer_position(RuntimeOrigin::signed(ALICE), 0, BOB, None));
+				System::assert_last_event(
+					Event::PositionTransferred { position_id: 0, from: ALICE, to: BOB }.into(),
+				);
+
+				// then only bob can manage it
+				assert_noop!(
+					Dex::collect_position_fees(RuntimeOrigin::signed(ALICE), 0, None),
+					Error::<Test>::NotPositionOwner
+				);
+				assert_ok!(Dex::decrease_position(
+					RuntimeOrigin::signed(BOB),
+					0,
+					LIQUIDITY,
+					0,
+					0,
+					None
+				));
+				assert_account_has(BOB, ASSET_X, 29_553_010);
+				assert_account_has(BOB, ASSET_Y, 4 * TEN_M + EXISTENTIAL_DEPOSIT + 29_553_010);
+
+				// and the position is closed, as it earned no fees
+				assert_noop!(
+					Dex::transfer_position(RuntimeOrigin::signed(BOB), 0, ALICE, None),
+					Error::<Test>::PositionNotFound
+				);
+			});
+		}
+
+		#[test]
+		fn invalid_positions_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				for (tick_lower, tick_upper) in
+					[(-605, 600), (600, 600), (600, -600), (-443_640, 600), (600, 443_640)]
+				{
+					assert_noop!(
+						open_position(ALICE, tick_lower, tick_upper),
+						Error::<Test>::InvalidTickRange
+					);
+				}
+				for liquidity in [0, u128::MAX] {
+					assert_noop!(
+						Dex::open_position(
+							RuntimeOrigin::signed(ALICE),
+							X_Y_ID,
+							FEE_TIER,
+							-600,
+							600,
+							liquidity,
+							TEN_M,
+							TEN_M,
+							None
+						),
+						Error::<Test>::ZeroLiquidity
+					);
+				}
+				assert_noop!(
+					Dex::open_position(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						-600,
+						600,
+						LIQUIDITY,
+						29_553_010,
+						ALICE_BALANCE,
+						None
+					),
+					Error::<Test>::MaximumInputExceeded
+				);
+			});
+		}
+
+		#[test]
+		fn pools_should_have_at_most_max_concentrated_ticks() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				// given the 3 ticks of alice's positions, and 4 more, of the 8 allowed
+				assert_ok!(open_position(ALICE, 1_300, 1_400));
+				assert_ok!(open_position(ALICE, 1_500, 1_600));
+
+				// a position bounded by 2 new ticks can't be opened, but one by existing ticks can
+				assert_noop!(open_position(ALICE, 1_700, 1_800), Error::<Test>::TooManyTicks);
+				assert_ok!(open_position(ALICE, 1_400, 1_500));
+			});
+		}
+
+		#[test]
+		fn create_concentrated_pool_should_validate_its_parameters() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_concentrated_x_y_pool();
+
+				let create = |tick_spacing, sqrt_price| {
+					Dex::create_concentrated_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Y,
+						FEE_TIER,
+						tick_spacing,
+						sqrt_price,
+						None,
+					)
+				};
+				assert_noop!(create(0, SQRT_PRICE_ONE), Error::<Test>::InvalidTickSpacing);
+				assert_noop!(create(16_385, SQRT_PRICE_ONE), Error::<Test>::InvalidTickSpacing);
+				assert_noop!(
+					create(TICK_SPACING, MIN_SQRT_PRICE - 1),
+					Error::<Test>::InvalidSqrtPrice
+				);
+				assert_noop!(
+					create(TICK_SPACING, SQRT_PRICE_ONE),
+					Error::<Test>::PoolAlreadyExists
+				);
+			});
+		}
+
+		#[test]
+		fn create_concentrated_pool_fails_for_unknown_asset() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+
+				// z was never created
+				assert_noop!(
+					Dex::create_concentrated_pool(
+						RuntimeOrigin::signed(ALICE),
+						ASSET_X,
+						ASSET_Z,
+						FEE_TIER,
+						TICK_SPACING,
+						SQRT_PRICE_ONE,
+						None
+					),
+					Error::<Test>::AssetDoesNotExist
+				);
+				assert!(ConcentratedPools::<Test>::iter_keys().next().is_none());
+			});
+		}
+	}
+
+	mod order_book_tests {
+		use frame_support::{assert_noop, assert_ok};
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			ASSET_Z, BOB, CHARLIE, EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, Error, Event, OrderBookOf, OrderBooks};
+
+		/// Amount of y minted to charlie, who places the orders.
+		const CHARLIE_BALANCE: u128 = 2 * TEN_K + EXISTENTIAL_DEPOSIT;
+		/// Amount of x minted to bob, who fills them.
+		const BOB_BALANCE: u128 = 10 * TEN_K + EXISTENTIAL_DEPOSIT;
+
+		/// Creates a constant product pool of x and y with 10M of each provided by alice.
+		fn setup_x_y_pool() {
+			// given created assets, held by alice to provide, charlie to offer and bob to take
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_X);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_Y);
+			mint_asset(CHARLIE, CHARLIE_BALANCE, ASSET_Y);
+			mint_asset(BOB, BOB_BALANCE, ASSET_X);
+
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+				FEE_TIER,
+				None
+			));
+		}
+
+		/// Charlie offers `offer` of y for `want` of x.
+		fn offer_y_for_x(offer: u128, want: u128) {
+			assert_ok!(Dex::place_order(
+				RuntimeOrigin::signed(CHARLIE),
+				AssetAmount::new(ASSET_Y, offer),
+				AssetAmount::new(ASSET_X, want),
+				None
+			));
+		}
+
+		/// Returns the ids of the orders offering y, cheapest first.
+		fn book_of_y() -> Vec<u64> {
+			OrderBooks::<Test>::get(X_Y_ID, ASSET_Y).iter().map(|order| order.id).collect()
+		}
+
+		#[test]
+		fn place_order_should_escrow_the_offer_and_keep_the_book_sorted() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+
+				offer_y_for_x(5_000, 6_000);
+				offer_y_for_x(5_000, 5_000);
+				// at the same price as order 1, so behind it
+				offer_y_for_x(1_000, 1_000);
+				System::assert_last_event(
+					Event::OrderPlaced {
+						who: CHARLIE,
+						order_id: 2,
+						offer: AssetAmount::new(ASSET_Y, 1_000),
+						want: AssetAmount::new(ASSET_X, 1_000),
+					}
+					.into(),
+				);
+
+				assert_eq!(book_of_y(), vec![1, 2, 0]);
+				assert_eq!(OrderBookOf::<Test>::get(2), Some((X_Y_ID, ASSET_Y)));
+				assert!(OrderBooks::<Test>::get(X_Y_ID, ASSET_X).is_empty());
+				assert_account_has(CHARLIE, ASSET_Y, CHARLIE_BALANCE - 11_000);
+			});
+		}
+
+		#[test]
+		fn place_order_should_fail_for_invalid_orders() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				create_asset(ASSET_Z);
+
+				let place = |offer: AssetAmount<Test>, want: AssetAmount<Test>| {
+					Dex::place_order(RuntimeOrigin::signed(CHARLIE), offer, want, None)
+				};
+				assert_noop!(
+					place(AssetAmount::new(ASSET_Y, 0), AssetAmount::new(ASSET_X, 1_000)),
+					Error::<Test>::ZeroOrderAmount
+				);
+				assert_noop!(
+					place(AssetAmount::new(ASSET_Y, 1_000), AssetAmount::new(ASSET_X, 0)),
+					Error::<Test>::ZeroOrderAmount
+				);
+				assert_noop!(
+					place(AssetAmount::new(ASSET_Y, 1_000), AssetAmount::new(ASSET_Y, 1_000)),
+					Error::<Test>::InvalidPair
+				);
+				assert_noop!(
+					place(AssetAmount::new(ASSET_Y, 1_000), AssetAmount::new(ASSET_Z, 1_000)),
+					Error::<Test>::PoolDoesntExists
+				);
+
+				// the mock allows 4 orders offering an asset of a pair
+				for _ in 0..4 {
+					offer_y_for_x(1_000, 1_000);
+				}
+				assert_noop!(
+					place(AssetAmount::new(ASSET_Y, 1_000), AssetAmount::new(ASSET_X, 1_000)),
+					Error::<Test>::OrderBookFull
+				);
+			});
+		}
+
+		#[test]
+		fn place_order_below_min_order_amount_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+
+				// the mock requires orders to offer at least 1_000
+				assert_noop!(
+					Dex::place_order(
+						RuntimeOrigin::signed(CHARLIE),
+						AssetAmount::new(ASSET_Y, 999),
+						AssetAmount::new(ASSET_X, 1_000),
+						None
+					),
+					Error::<Test>::OrderBelowMinimum
+				);
+
+				// while exactly the minimum should pass
+				offer_y_for_x(1_000, 1_000);
+				assert_eq!(book_of_y(), vec![0]);
+			});
+		}
+
+		#[test]
+		fn fill_order_should_pay_the_maker_at_the_order_price_rounded_up() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				offer_y_for_x(5_000, 6_000);
+
+				// 1_001 y at 1.2 x each costs 1_201.2 x
+				assert_ok!(Dex::fill_order(RuntimeOrigin::signed(BOB), 0, 1_001, None));
+				System::assert_last_event(
+					Event::OrderFilled {
+						order_id: 0,
+						maker: CHARLIE,
+						taker: BOB,
+						filled: AssetAmount::new(ASSET_Y, 1_001),
+						paid: AssetAmount::new(ASSET_X, 1_202),
+					}
+					.into(),
+				);
+				assert_eq!(OrderBooks::<Test>::get(X_Y_ID, ASSET_Y)[0].remaining, 3_999);
+
+				assert_noop!(
+					Dex::fill_order(RuntimeOrigin::signed(BOB), 0, 0, None),
+					Error::<Test>::ZeroSwapAmountRequested
+				);
+				assert_noop!(
+					Dex::fill_order(RuntimeOrigin::signed(BOB), 0, 4_000, None),
+					Error::<Test>::InsufficientOrderAmount
+				);
+
+				// filling the rest, for 4_798.8 x, removes the order
+				assert_ok!(Dex::fill_order(RuntimeOrigin::signed(BOB), 0, 3_999, None));
+				assert!(book_of_y().is_empty());
+				assert_eq!(OrderBookOf::<Test>::get(0), None);
+				assert_noop!(
+					Dex::fill_order(RuntimeOrigin::signed(BOB), 0, 1, None),
+					Error::<Test>::OrderNotFound
+				);
+
+				assert_account_has(BOB, ASSET_Y, 5_000);
+				assert_account_has(BOB, ASSET_X, BOB_BALANCE - 6_001);
+				assert_account_has(CHARLIE, ASSET_X, 6_001);
+			});
+		}
+
+		#[test]
+		fn cancel_order_should_refund_what_is_left_of_the_offer() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				offer_y_for_x(5_000, 5_000);
+				assert_ok!(Dex::fill_order(RuntimeOrigin::signed(BOB), 0, 2_000, None));
+
+				assert_noop!(
+					Dex::cancel_order(RuntimeOrigin::signed(BOB), 0, None),
+					Error::<Test>::NotOrderOwner
+				);
+				assert_ok!(Dex::cancel_order(RuntimeOrigin::signed(CHARLIE), 0, None));
+				System::assert_last_event(
+					Event::OrderCancelled {
+						who: CHARLIE,
+						order_id: 0,
+						refunded: AssetAmount::new(ASSET_Y, 3_000),
+					}
+					.into(),
+				);
+
+				assert!(book_of_y().is_empty());
+				assert_eq!(OrderBookOf::<Test>::get(0), None);
+				assert_account_has(CHARLIE, ASSET_Y, CHARLIE_BALANCE - 2_000);
+				assert_noop!(
+					Dex::cancel_order(RuntimeOrigin::signed(CHARLIE), 0, None),
+					Error::<Test>::OrderNotFound
+				);
+			});
+		}
+
+		#[test]
+		fn swap_limit_take_should_fill_cheaper_orders_before_the_pool() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				// order 0 gives 5_000 y for 5_000 x, more than the pool's 4_947 after its fee,
+				// while order 1, at 1.2 x per y, gives less than the pool for the rest
+				offer_y_for_x(5_000, 5_000);
+				offer_y_for_x(5_000, 6_000);
+
+				let swap = |expect_min_take: u128| {
+					Dex::swap_limit_take(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_X, TEN_K),
+						expect_min_take,
+						X_Y_ID,
+						FEE_TIER,
+						None,
+					)
+				};
+				assert_noop!(swap(9_948), Error::<Test>::MinimumOutputNotReached);
+				assert_noop!(
+					Dex::swap_limit_take(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_Z, TEN_K),
+						0,
+						X_Y_ID,
+						FEE_TIER,
+						None,
+					),
+					Error::<Test>::AssetNotInPool
+				);
+				assert_ok!(swap(9_947));
+
+				System::assert_has_event(
+					Event::OrderFilled {
+						order_id: 0,
+						maker: CHARLIE,
+						taker: BOB,
+						filled: AssetAmount::new(ASSET_Y, 5_000),
+						paid: AssetAmount::new(ASSET_X, 5_000),
+					}
+					.into(),
+				);
+				// the swap event only covers the pool's part
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 5_000),
+						take: AssetAmount::new(ASSET_Y, 4_947),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+
+				assert_eq!(book_of_y(), vec![1]);
+				assert_eq!(OrderBookOf::<Test>::get(0), None);
+				assert_account_has(BOB, ASSET_Y, 9_947);
+				assert_account_has(CHARLIE, ASSET_X, 5_000);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((TEN_M + 5_000, TEN_M - 4_947))
+				);
+			});
+		}
+
+		#[test]
+		fn swap_limit_give_should_fill_cheaper_orders_before_the_pool() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				// order 0 asks 5_000 x for 5_000 y, less than the pool's 5_054 with its fee,
+				// while order 1, at 1.2 x per y, asks more than the pool for the rest
+				offer_y_for_x(5_000, 5_000);
+				offer_y_for_x(5_000, 6_000);
+
+				let swap = |expect_max_give: u128| {
+					Dex::swap_limit_give(
+						RuntimeOrigin::signed(BOB),
+						AssetAmount::new(ASSET_Y, TEN_K),
+						expect_max_give,
+						X_Y_ID,
+						FEE_TIER,
+						None,
+					)
+				};
+				assert_noop!(swap(10_053), Error::<Test>::MaximumInputExceeded);
+				assert_ok!(swap(10_054));
+
+				System::assert_has_event(
+					Event::OrderFilled {
+						order_id: 0,
+						maker: CHARLIE,
+						taker: BOB,
+						filled: AssetAmount::new(ASSET_Y, 5_000),
+						paid: AssetAmount::new(ASSET_X, 5_000),
+					}
+					.into(),
+				);
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 5_054),
+						take: AssetAmount::new(ASSET_Y, 5_000),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+
+				assert_eq!(book_of_y(), vec![1]);
+				assert_account_has(BOB, ASSET_Y, TEN_K);
+				assert_account_has(BOB, ASSET_X, BOB_BALANCE - 10_054);
+			});
+		}
+
+		#[test]
+		fn swaps_should_be_filled_by_orders_alone_when_they_suffice() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				offer_y_for_x(5_000, 5_000);
+				offer_y_for_x(5_000, 5_000);
+
+				assert_ok!(Dex::swap_limit_give(
+					RuntimeOrigin::signed(BOB),
+					AssetAmount::new(ASSET_Y, 7_000),
+					7_000,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				System::assert_last_event(
+					Event::OrderFilled {
+						order_id: 1,
+						maker: CHARLIE,
+						taker: BOB,
+						filled: AssetAmount::new(ASSET_Y, 2_000),
+						paid: AssetAmount::new(ASSET_X, 2_000),
+					}
+					.into(),
+				);
+
+				// the pool is left as it was
+				assert_eq!(book_of_y(), vec![1]);
+				assert_eq!(OrderBooks::<Test>::get(X_Y_ID, ASSET_Y)[0].remaining, 3_000);
+				assert_eq!(Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER), Some((TEN_M, TEN_M)));
+			});
+		}
+
+		#[test]
+		fn swap_exact_in_along_path_should_fill_cheaper_orders_on_each_hop() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				// as in swap_limit_take_should_fill_cheaper_orders_before_the_pool
+				offer_y_for_x(5_000, 5_000);
+				offer_y_for_x(5_000, 6_000);
+
+				let swap = |expect_min_take: u128| {
+					Dex::swap_exact_in_along_path(
+						RuntimeOrigin::signed(BOB),
+						vec![ASSET_X, ASSET_Y].try_into().expect("path should fit"),
+						TEN_K,
+						expect_min_take,
+						None,
+					)
+				};
+				assert_noop!(swap(9_948), Error::<Test>::MinimumOutputNotReached);
+				assert_ok!(swap(9_947));
+
+				// the dex account takes the order, bob only settles with it
+				System::assert_has_event(
+					Event::OrderFilled {
+						order_id: 0,
+						maker: CHARLIE,
+						taker: Dex::dex_account_id(),
+						filled: AssetAmount::new(ASSET_Y, 5_000),
+						paid: AssetAmount::new(ASSET_X, 5_000),
+					}
+					.into(),
+				);
+				System::assert_has_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 5_000),
+						take: AssetAmount::new(ASSET_Y, 4_947),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				System::assert_last_event(
+					Event::PathSwapped {
+						who: BOB,
+						path: vec![ASSET_X, ASSET_Y].try_into().expect("path should fit"),
+						give: AssetAmount::new(ASSET_X, TEN_K),
+						take: AssetAmount::new(ASSET_Y, 9_947),
+					}
+					.into(),
+				);
+
+				assert_eq!(book_of_y(), vec![1]);
+				assert_account_has(BOB, ASSET_X, BOB_BALANCE - TEN_K);
+				assert_account_has(BOB, ASSET_Y, 9_947);
+				assert_account_has(CHARLIE, ASSET_X, 5_000);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((TEN_M + 5_000, TEN_M - 4_947))
+				);
+			});
+		}
+
+		#[test]
+		fn swap_exact_out_along_path_should_fill_cheaper_orders_on_each_hop() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				// as in swap_limit_give_should_fill_cheaper_orders_before_the_pool
+				offer_y_for_x(5_000, 5_000);
+				offer_y_for_x(5_000, 6_000);
+
+				let swap = |expect_max_give: u128| {
+					Dex::swap_exact_out_along_path(
+						RuntimeOrigin::signed(BOB),
+						vec![ASSET_X, ASSET_Y].try_into().expect("path should fit"),
+						TEN_K,
+						expect_max_give,
+						None,
+					)
+				};
+				assert_noop!(swap(10_053), Error::<Test>::MaximumInputExceeded);
+				assert_ok!(swap(10_054));
+
+				System::assert_has_event(
+					Event::OrderFilled {
+						order_id: 0,
+						maker: CHARLIE,
+						taker: Dex::dex_account_id(),
+						filled: AssetAmount::new(ASSET_Y, 5_000),
+						paid: AssetAmount::new(ASSET_X, 5_000),
+					}
+					.into(),
+				);
+				System::assert_last_event(
+					Event::PathSwapped {
+						who: BOB,
+						path: vec![ASSET_X, ASSET_Y].try_into().expect("path should fit"),
+						give: AssetAmount::new(ASSET_X, 10_054),
+						take: AssetAmount::new(ASSET_Y, TEN_K),
+					}
+					.into(),
+				);
+
+				assert_eq!(book_of_y(), vec![1]);
+				assert_account_has(BOB, ASSET_Y, TEN_K);
+				assert_account_has(BOB, ASSET_X, BOB_BALANCE - 10_054);
+				assert_account_has(CHARLIE, ASSET_X, 5_000);
+			});
+		}
+	}
+
+	mod flash_swap_tests {
+		use codec::Compact;
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::{DispatchError, DispatchResult, Perbill};
+
+		use crate::mock::{Dex, RuntimeCall, RuntimeOrigin, System, Test, FEE_TIER, GOVERNANCE};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, init_test_ext, mint_asset, ALICE, ASSET_X, ASSET_Y,
+			BOB, CHARLIE, EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmountPair, DexParameters, Error, Event, FlashBorrower};
+
+		/// Amount of x minted to bob, who flash swaps.
+		const BOB_BALANCE: u128 = TEN_K + EXISTENTIAL_DEPOSIT;
+
+		/// Creates a constant product pool of x and y with 10M of each provided by alice.
+		fn setup_x_y_pool() {
+			// given created assets, held by alice to provide and by bob to repay
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_X);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_Y);
+			mint_asset(BOB, BOB_BALANCE, ASSET_X);
+
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				ASSET_X,
+				ASSET_Y,
+				FEE_TIER,
+				None
+			));
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+				FEE_TIER,
+				None
+			));
+		}
+
+		fn transfer_y_to_charlie(amount: u128) -> Box<RuntimeCall> {
+			Box::new(RuntimeCall::Assets(pallet_assets::Call::transfer {
+				id: Compact(ASSET_Y),
+				target: CHARLIE,
+				amount,
+			}))
+		}
+
+		/// Bob flash swaps 1_000 y, which he gives to charlie in the call, repaying `repay_x`.
+		fn flash_swap_y_for_x(repay_x: u128) -> DispatchResult {
+			Dex::flash_swap(
+				RuntimeOrigin::signed(BOB),
+				AssetAmountPair::new(X_Y_ID, 0, 1_000),
+				AssetAmountPair::new(X_Y_ID, repay_x, 0),
+				FEE_TIER,
+				transfer_y_to_charlie(1_000),
+				None,
+			)
+			.map(|_| ())
+			.map_err(|error| error.error)
+		}
+
+		/// Gives charlie what is taken of y.
+		struct GiveToCharlie;
+
+		impl FlashBorrower<Test> for GiveToCharlie {
+			fn on_flash_swap(who: &u64, taken: &AssetAmountPair<Test>) -> DispatchResult {
+				pallet_assets::Pallet::<Test>::transfer(
+					RuntimeOrigin::signed(*who),
+					Compact(ASSET_Y),
+					CHARLIE,
+					taken.amount_y.balance,
+				)
+			}
+		}
+
+		/// Fails whatever is taken.
+		struct Defaulter;
+
+		impl FlashBorrower<Test> for Defaulter {
+			fn on_flash_swap(_: &u64, _: &AssetAmountPair<Test>) -> DispatchResult {
+				Err(DispatchError::Other("defaulted"))
+			}
+		}
+
+		#[test]
+		fn flash_swap_should_transfer_the_output_before_taking_the_repayment() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+
+				// the pool's price of 1_000 y without its fee is 1_001 x, and the flash fee on
+				// 1_006 x is 6 x
+				assert_noop!(flash_swap_y_for_x(1_006), Error::<Test>::FlashSwapNotRepaid);
+				assert_ok!(flash_swap_y_for_x(1_007));
+				System::assert_last_event(
+					Event::FlashSwapped {
+						who: BOB,
+						taken: AssetAmountPair::new(X_Y_ID, 0, 1_000),
+						repaid: AssetAmountPair::new(X_Y_ID, 1_007, 0),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+
+				// bob had no y to give charlie but what he took
+				assert_account_has(CHARLIE, ASSET_Y, 1_000);
+				assert_account_has(BOB, ASSET_X, BOB_BALANCE - 1_007);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((TEN_M + 1_007, TEN_M - 1_000))
+				);
+			});
+		}
+
+		#[test]
+		fn repaying_the_asset_taken_should_cost_the_flash_fee() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				mint_asset(BOB, TEN_K, ASSET_Y);
+
+				let flash_swap = |repay_y: u128| {
+					Dex::flash_swap(
+						RuntimeOrigin::signed(BOB),
+						AssetAmountPair::new(X_Y_ID, 0, 1_000),
+						AssetAmountPair::new(X_Y_ID, 0, repay_y),
+						FEE_TIER,
+						Box::new(RuntimeCall::System(frame_system::Call::remark {
+							remark: vec![],
+						})),
+						None,
+					)
+				};
+				// 5.025 y of 1_005 y is a flash fee of 6 y
+				assert_noop!(flash_swap(1_005), Error::<Test>::FlashSwapNotRepaid);
+				assert_ok!(flash_swap(1_006));
+
+				assert_account_has(BOB, ASSET_Y, TEN_K - 6);
+				assert_eq!(Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER), Some((TEN_M, TEN_M + 6)));
+			});
+		}
+
+		#[test]
+		fn flash_swap_should_be_reverted_when_the_call_fails_or_changes_the_pool() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+
+				let flash_swap = |call: RuntimeCall| {
+					Dex::flash_swap(
+						RuntimeOrigin::signed(BOB),
+						AssetAmountPair::new(X_Y_ID, 0, 1_000),
+						AssetAmountPair::new(X_Y_ID, 2_000, 0),
+						FEE_TIER,
+						Box::new(call),
+						None,
+					)
+				};
+				// bob isn't `AdminOrigin`
+				assert_noop!(
+					flash_swap(RuntimeCall::Dex(crate::Call::set_flash_fee {
+						flash_fee: Perbill::zero()
+					})),
+					DispatchError::BadOrigin
+				);
+				assert_noop!(
+					flash_swap(RuntimeCall::Dex(crate::Call::swap_limit_take {
+						give: crate::AssetAmount::new(ASSET_X, 1_000),
+						expect_min_take: 0,
+						pool_id: X_Y_ID,
+						fee_tier: FEE_TIER,
+						deadline: None,
+					})),
+					Error::<Test>::PoolChangedDuringFlashSwap
+				);
+			});
+		}
+
+		#[test]
+		fn flash_swap_should_fail_for_invalid_amounts() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+
+				let flash_swap = |take: AssetAmountPair<Test>, repay: AssetAmountPair<Test>| {
+					Dex::flash_swap(
+						RuntimeOrigin::signed(BOB),
+						take,
+						repay,
+						FEE_TIER,
+						Box::new(RuntimeCall::System(frame_system::Call::remark {
+							remark: vec![],
+						})),
+						None,
+					)
+				};
+				assert_noop!(
+					flash_swap(
+						AssetAmountPair::new(X_Y_ID, 0, 0),
+						AssetAmountPair::new(X_Y_ID, 1, 0)
+					),
+					Error::<Test>::ZeroSwapAmountRequested
+				);
+				assert_noop!(
+					flash_swap(
+						AssetAmountPair::new(X_Y_ID, 0, 1_000),
+						AssetAmountPair::new(crate::tests::tests::Y_Z_ID, 2_000, 0)
+					),
+					Error::<Test>::InvalidPair
+				);
+				assert_noop!(
+					flash_swap(
+						AssetAmountPair::new(X_Y_ID, 0, TEN_M),
+						AssetAmountPair::new(X_Y_ID, TEN_K, 0)
+					),
+					Error::<Test>::SwapCannotBeSatisfied
+				);
+			});
+		}
+
+		#[test]
+		fn flash_swap_with_should_call_the_borrower_of_another_pallet() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool();
+				let take = AssetAmountPair::new(X_Y_ID, 0, 1_000);
+				let repay = AssetAmountPair::new(X_Y_ID, 1_007, 0);
+
+				assert_noop!(
+					Dex::flash_swap_with::<Defaulter>(BOB, take.clone(), repay.clone(), FEE_TIER),
+					DispatchError::Other("defaulted")
+				);
+				assert_ok!(Dex::flash_swap_with::<GiveToCharlie>(BOB, take, repay, FEE_TIER));
+
+				assert_account_has(CHARLIE, ASSET_Y, 1_000);
+				assert_account_has(BOB, ASSET_X, BOB_BALANCE - 1_007);
+			});
+		}
+
+		#[test]
+		fn set_flash_fee_should_work() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+
+				assert_noop!(
+					Dex::set_flash_fee(RuntimeOrigin::signed(ALICE), Perbill::from_percent(1)),
+					DispatchError::BadOrigin
+				);
+				assert_noop!(
+					Dex::set_flash_fee(RuntimeOrigin::root(), Perbill::one()),
+					Error::<Test>::InvalidFlashFee
+				);
+
+				assert_ok!(Dex::set_flash_fee(
+					RuntimeOrigin::signed(GOVERNANCE),
+					Perbill::from_percent(1)
+				));
+				System::assert_last_event(
+					Event::FlashFeeSet { flash_fee: Perbill::from_percent(1) }.into(),
+				);
+				assert_eq!(DexParameters::<Test>::get().flash_fee, Perbill::from_percent(1));
+			});
+		}
+	}
+
+	mod zap_tests {
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::Perbill;
+
+		use crate::mock::{Dex, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, get_account_balance, init_test_ext, mint_asset,
+			ALICE, ASSET_X, ASSET_Y, ASSET_Z, BOB, EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, TEN_K, TEN_M,
+			X_Y_ID,
+		};
+		use crate::{AssetAmount, AssetAmountPair, Error, Event};
+
+		/// Amount of x minted to bob, who zaps in.
+		const BOB_BALANCE: u128 = TEN_K + EXISTENTIAL_DEPOSIT;
+
+		/// Creates a pool of x and y, a StableSwap one if `stable`, with 10M of each provided by
+		/// alice.
+		fn setup_x_y_pool(stable: bool) {
+			// given created assets, held by alice to provide and by bob to zap in
+			create_asset(ASSET_X);
+			create_asset(ASSET_Y);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_X);
+			mint_asset(ALICE, 2 * TEN_M, ASSET_Y);
+			mint_asset(BOB, BOB_BALANCE, ASSET_X);
+
+			if stable {
+				assert_ok!(Dex::create_stable_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					100,
+					None
+				));
+			} else {
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					None
+				));
+			}
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				AssetAmountPair::new(X_Y_ID, TEN_M, TEN_M),
+				FEE_TIER,
+				None
+			));
+		}
+
+		#[test]
+		fn zap_in_should_swap_the_share_matching_the_reserves_and_provide_both_assets() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(false);
+
+				assert_ok!(Dex::zap_in(
+					RuntimeOrigin::signed(BOB),
+					X_Y_ID,
+					FEE_TIER,
+					AssetAmount::new(ASSET_X, TEN_K),
+					4_970,
+					None
+				));
+
+				// 5_023 x is swapped for 4_969 y, which is provided with 4_973 x
+				System::assert_has_event(
+					Event::TokenSwapped {
+						who: BOB,
+						give: AssetAmount::new(ASSET_X, 5_023),
+						take: AssetAmount::new(ASSET_Y, 4_969),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				System::assert_last_event(
+					Event::LiquidityProvided {
+						who: BOB,
+						provided: AssetAmountPair::new(X_Y_ID, 4_973, 4_969),
+						lp_tokens: 4_970,
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+
+				// only the rounding of the provision is left to bob
+				assert_account_has(BOB, ASSET_X, EXISTENTIAL_DEPOSIT + 4);
+				assert_account_has(BOB, ASSET_Y, 0);
+				assert_account_has(BOB, LP_TOKEN_ID, 4_970);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((TEN_M + TEN_K - 4, TEN_M))
+				);
+			});
+		}
+
+		#[test]
+		fn zap_in_should_search_the_share_to_swap_in_a_stable_swap_pool() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(true);
+
+				assert_ok!(Dex::zap_in(
+					RuntimeOrigin::signed(BOB),
+					X_Y_ID,
+					FEE_TIER,
+					AssetAmount::new(ASSET_X, TEN_K),
+					0,
+					None
+				));
+
+				// all of the y taken is provided, with all of the x but its rounding
+				assert_account_has(BOB, ASSET_Y, 0);
+				assert!(get_account_balance(BOB, ASSET_X) - EXISTENTIAL_DEPOSIT < 10);
+				assert!(get_account_balance(BOB, LP_TOKEN_ID) > 0);
+			});
+		}
+
+		#[test]
+		fn zap_in_should_fail_for_invalid_amounts_or_pools() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(false);
+
+				let zap_in = |asset_id, amount, min_lp_tokens| {
+					Dex::zap_in(
+						RuntimeOrigin::signed(BOB),
+						X_Y_ID,
+						FEE_TIER,
+						AssetAmount::new(asset_id, amount),
+						min_lp_tokens,
+						None,
+					)
+				};
+				assert_noop!(zap_in(ASSET_Z, TEN_K, 0), Error::<Test>::AssetNotInPool);
+				assert_noop!(zap_in(ASSET_X, 1, 0), Error::<Test>::InsufficientLiquidityProvided);
+				assert_noop!(
+					zap_in(ASSET_X, TEN_K, 4_971),
+					Error::<Test>::MinimumLpTokensNotReached
+				);
+				assert_noop!(
+					Dex::zap_in(
+						RuntimeOrigin::signed(BOB),
+						X_Y_ID,
+						Perbill::from_percent(2),
+						AssetAmount::new(ASSET_X, TEN_K),
+						0,
+						None,
+					),
+					Error::<Test>::PoolDoesntExists
+				);
+			});
+		}
+
+		#[test]
+		fn zap_in_should_fail_for_a_pool_without_liquidity() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				mint_asset(BOB, BOB_BALANCE, ASSET_X);
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					None
+				));
+
+				assert_noop!(
+					Dex::zap_in(
+						RuntimeOrigin::signed(BOB),
+						X_Y_ID,
+						FEE_TIER,
+						AssetAmount::new(ASSET_X, TEN_K),
+						0,
+						None
+					),
+					Error::<Test>::InsufficientLiquidityProvided
+				);
+			});
+		}
+
+		#[test]
+		fn zap_out_should_burn_lp_tokens_for_a_single_asset() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(false);
+
+				assert_ok!(Dex::zap_out(
+					RuntimeOrigin::signed(ALICE),
+					X_Y_ID,
+					FEE_TIER,
+					1_000_000,
+					ASSET_X,
+					1_891_891,
+					None
+				));
+
+				// the 1M y the LP tokens are worth is swapped for 891_891 x
+				System::assert_has_event(
+					Event::LiquidityRemoved {
+						who: ALICE,
+						removed: AssetAmountPair::new(X_Y_ID, 1_000_000, 1_000_000),
+						lp_tokens: 1_000_000,
+						reserves: AssetAmountPair::new(
+							X_Y_ID,
+							TEN_M - 1_000_000,
+							TEN_M - 1_000_000,
+						),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+				System::assert_last_event(
+					Event::TokenSwapped {
+						who: ALICE,
+						give: AssetAmount::new(ASSET_Y, 1_000_000),
+						take: AssetAmount::new(ASSET_X, 891_891),
+						fee_tier: FEE_TIER,
+					}
+					.into(),
+				);
+
+				assert_account_has(ALICE, ASSET_X, TEN_M + 1_891_891);
+				assert_account_has(ALICE, ASSET_Y, TEN_M);
+				assert_account_has(ALICE, LP_TOKEN_ID, TEN_M - 1_000_000);
+				assert_eq!(
+					Dex::get_reserves(ASSET_X, ASSET_Y, FEE_TIER),
+					Some((TEN_M - 1_891_891, TEN_M))
+				);
+			});
+		}
+
+		#[test]
+		fn zap_out_should_fail_for_invalid_amounts() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_x_y_pool(false);
+
+				let zap_out = |lp_tokens, asset_id, min_amount| {
+					Dex::zap_out(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						lp_tokens,
+						asset_id,
+						min_amount,
+						None,
+					)
+				};
+				assert_noop!(zap_out(1_000_000, ASSET_Z, 0), Error::<Test>::AssetNotInPool);
+				assert_noop!(zap_out(0, ASSET_X, 0), Error::<Test>::InsufficientLiquidityProvided);
+				assert_noop!(
+					zap_out(1_000_000, ASSET_X, 1_891_892),
+					Error::<Test>::MinimumWithdrawalNotReached
+				);
+				// alice only holds `TEN_M` LP tokens
+				assert!(zap_out(TEN_M + 1, ASSET_X, 0).is_err());
+			});
+		}
+	}
+
+	mod transaction_payment_tests {
+		use frame_support::dispatch::{DispatchClass, DispatchInfo, Pays, PostDispatchInfo};
+		use frame_support::weights::Weight;
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::traits::SignedExtension;
+		use sp_runtime::transaction_validity::InvalidTransaction;
+
+		use crate::mock::{Balances, Dex, RuntimeCall, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, create_asset_amount_pair, init_test_ext, mint_asset,
+			ADMIN, ALICE, ASSET_X, ASSET_Y, BOB, EXISTENTIAL_DEPOSIT, NATIVE, TEN_B, TEN_K, TEN_M,
+		};
+		use crate::{AssetAmount, AssetIdPair, ChargeDexTxPayment};
+
+		/// Length of the transactions charged for.
+		const LEN: usize = 10;
+		/// Fee of a transaction of `LEN` bytes, the mock's 500 base fee and 500 weight fee plus a
+		/// fee of 1 per byte.
+		const FEE: u128 = 1_010;
+		/// Amount of x swapped for `FEE` in a 10M/10M pool at 1%.
+		const FEE_IN_X: u128 = 1_021;
+		/// Account holding none of the native currency.
+		const PAYER: u64 = 6;
+
+		fn remark_call() -> RuntimeCall {
+			RuntimeCall::System(frame_system::Call::remark { remark: vec![] })
+		}
+
+		fn dispatch_info() -> DispatchInfo {
+			DispatchInfo {
+				weight: Weight::from_parts(1_000, 0),
+				class: DispatchClass::Normal,
+				pays_fee: Pays::Yes,
+			}
+		}
+
+		/// Creates a pool of the native currency and x with 10M of each provided by alice, and
+		/// gives `x_balance` of x to bob.
+		fn setup_native_x_pool(x_balance: u128) {
+			create_asset(ASSET_X);
+			mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+			mint_asset(BOB, x_balance, ASSET_X);
+			assert_ok!(Dex::create_pool(
+				RuntimeOrigin::signed(ALICE),
+				NATIVE,
+				ASSET_X,
+				FEE_TIER,
+				None
+			));
+			assert_ok!(Dex::provide_liquidity(
+				RuntimeOrigin::signed(ALICE),
+				create_asset_amount_pair(TEN_M, NATIVE, ASSET_X),
+				FEE_TIER,
+				None
+			));
+		}
+
+		#[test]
+		fn fee_should_be_paid_in_an_asset_swapped_for_native() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_native_x_pool(TEN_K);
+
+				let pre = ChargeDexTxPayment::<Test>::new(0, Some(ASSET_X))
+					.pre_dispatch(&BOB, &remark_call(), &dispatch_info(), LEN)
+					.expect("fee should be paid in x");
+
+				// bob's native balance should be untouched, the fee was swapped for
+				assert_eq!(Balances::free_balance(BOB), TEN_B);
+				assert_account_has(BOB, ASSET_X, TEN_K - FEE_IN_X);
+				assert_eq!(
+					Dex::get_reserves(NATIVE, ASSET_X, FEE_TIER),
+					Some((TEN_M - FEE, TEN_M + FEE_IN_X))
+				);
+
+				// nothing is refunded when the whole fee is charged
+				let post_info = PostDispatchInfo { actual_weight: None, pays_fee: Pays::Yes };
+				assert_ok!(ChargeDexTxPayment::<Test>::post_dispatch(
+					Some(pre),
+					&dispatch_info(),
+					&post_info,
+					LEN,
+					&Ok(())
+				));
+				assert_eq!(Balances::free_balance(BOB), TEN_B);
+				assert_account_has(BOB, ASSET_X, TEN_K - FEE_IN_X);
+			});
+		}
+
+		#[test]
+		fn refund_should_be_swapped_back_to_the_asset() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_native_x_pool(TEN_K);
+
+				let pre = ChargeDexTxPayment::<Test>::new(0, Some(ASSET_X))
+					.pre_dispatch(&BOB, &remark_call(), &dispatch_info(), LEN)
+					.expect("fee should be paid in x");
+
+				// the whole fee is refunded when the call turns out not to pay it
+				let refund_in_x =
+					Dex::quote_exact_in(NATIVE, ASSET_X, FEE).expect("refund should be quoted");
+				let post_info = PostDispatchInfo { actual_weight: None, pays_fee: Pays::No };
+				assert_ok!(ChargeDexTxPayment::<Test>::post_dispatch(
+					Some(pre),
+					&dispatch_info(),
+					&post_info,
+					LEN,
+					&Ok(())
+				));
+				assert_eq!(Balances::free_balance(BOB), TEN_B);
+				assert_account_has(BOB, ASSET_X, TEN_K - FEE_IN_X + refund_in_x);
+			});
+		}
+
+		#[test]
+		fn refund_should_be_left_in_native_if_the_price_moved_against_it() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_native_x_pool(TEN_K);
+
+				let pre = ChargeDexTxPayment::<Test>::new(0, Some(ASSET_X))
+					.pre_dispatch(&BOB, &remark_call(), &dispatch_info(), LEN)
+					.expect("fee should be paid in x");
+
+				// when x gets more than twice as dear in native before the refund
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(ALICE),
+					AssetAmount::new(NATIVE, TEN_M / 2),
+					0,
+					AssetIdPair::new(NATIVE, ASSET_X).expect("id pair should be valid"),
+					FEE_TIER,
+					None
+				));
+
+				// the refund should be kept in native rather than swapped back for less than half
+				let post_info = PostDispatchInfo { actual_weight: None, pays_fee: Pays::No };
+				assert_ok!(ChargeDexTxPayment::<Test>::post_dispatch(
+					Some(pre),
+					&dispatch_info(),
+					&post_info,
+					LEN,
+					&Ok(())
+				));
+				assert_eq!(Balances::free_balance(BOB), TEN_B + FEE);
+				assert_account_has(BOB, ASSET_X, TEN_K - FEE_IN_X);
+			});
+		}
+
+		#[test]
+		fn fee_should_be_paid_in_native_without_an_asset() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_native_x_pool(TEN_K);
+
+				for asset_id in [None, Some(NATIVE)] {
+					let balance = Balances::free_balance(BOB);
+					assert_ok!(ChargeDexTxPayment::<Test>::new(0, asset_id).pre_dispatch(
+						&BOB,
+						&remark_call(),
+						&dispatch_info(),
+						LEN
+					));
+					assert_eq!(Balances::free_balance(BOB), balance - FEE);
+				}
+
+				// no swap took place
+				assert_account_has(BOB, ASSET_X, TEN_K);
+				assert_eq!(Dex::get_reserves(NATIVE, ASSET_X, FEE_TIER), Some((TEN_M, TEN_M)));
+			});
+		}
+
+		#[test]
+		fn fee_in_an_asset_should_fail_without_a_pool_or_enough_of_it() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				// bob would be left with less than the minimum balance of x
+				setup_native_x_pool(FEE_IN_X + EXISTENTIAL_DEPOSIT - 1);
+				create_asset(ASSET_Y);
+				mint_asset(BOB, TEN_K, ASSET_Y);
+
+				let charge = |asset_id| ChargeDexTxPayment::<Test>::new(0, Some(asset_id));
+				for asset_id in [ASSET_X, ASSET_Y] {
+					assert_noop!(
+						charge(asset_id).validate(&BOB, &remark_call(), &dispatch_info(), LEN),
+						InvalidTransaction::Payment
+					);
+					assert_noop!(
+						charge(asset_id)
+							.pre_dispatch(&BOB, &remark_call(), &dispatch_info(), LEN)
+							.map(|_| ()),
+						InvalidTransaction::Payment
+					);
+				}
+
+				// while enough x should pass
+				assert_ok!(pallet_assets::Pallet::<Test>::mint(
+					RuntimeOrigin::signed(ADMIN),
+					codec::Compact(ASSET_X),
+					BOB,
+					1
+				));
+				assert_ok!(charge(ASSET_X).validate(&BOB, &remark_call(), &dispatch_info(), LEN));
+				charge(ASSET_X)
+					.pre_dispatch(&BOB, &remark_call(), &dispatch_info(), LEN)
+					.expect("fee should be paid in x");
+				assert_account_has(BOB, ASSET_X, EXISTENTIAL_DEPOSIT);
+			});
+		}
+
+		#[test]
+		fn fee_in_an_asset_should_keep_a_payer_without_native_alive() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				setup_native_x_pool(0);
+
+				// given a payer holding only x, which it can as x is made sufficient
+				assert_ok!(pallet_assets::Pallet::<Test>::force_asset_status(
+					RuntimeOrigin::root(),
+					codec::Compact(ASSET_X),
+					ADMIN,
+					ADMIN,
+					ADMIN,
+					ADMIN,
+					EXISTENTIAL_DEPOSIT,
+					true,
+					false
+				));
+				let amount_in = Dex::quote_exact_out(ASSET_X, NATIVE, FEE + EXISTENTIAL_DEPOSIT)
+					.expect("fee and existential deposit should be quoted");
+				mint_asset(PAYER, amount_in - 1, ASSET_X);
+				assert_eq!(Balances::free_balance(PAYER), 0);
+
+				// the fee alone shouldn't be enough, as the payer would be reaped paying it
+				let charge = || ChargeDexTxPayment::<Test>::new(0, Some(ASSET_X));
+				assert_noop!(
+					charge().validate(&PAYER, &remark_call(), &dispatch_info(), LEN),
+					InvalidTransaction::Payment
+				);
+
+				// while enough x for the existential deposit as well should pass
+				assert_ok!(pallet_assets::Pallet::<Test>::mint(
+					RuntimeOrigin::signed(ADMIN),
+					codec::Compact(ASSET_X),
+					PAYER,
+					TEN_K
+				));
+				assert_ok!(charge().validate(&PAYER, &remark_call(), &dispatch_info(), LEN));
+				let pre = charge()
+					.pre_dispatch(&PAYER, &remark_call(), &dispatch_info(), LEN)
+					.expect("fee should be paid in x");
+
+				// the payer should be left with the existential deposit after paying the fee
+				assert_eq!(Balances::free_balance(PAYER), EXISTENTIAL_DEPOSIT);
+				assert_account_has(PAYER, ASSET_X, TEN_K - 1);
+				assert_eq!(
+					Dex::get_reserves(NATIVE, ASSET_X, FEE_TIER),
+					Some((TEN_M - FEE - EXISTENTIAL_DEPOSIT, TEN_M + amount_in))
+				);
+
+				let post_info = PostDispatchInfo { actual_weight: None, pays_fee: Pays::Yes };
+				assert_ok!(ChargeDexTxPayment::<Test>::post_dispatch(
+					Some(pre),
+					&dispatch_info(),
+					&post_info,
+					LEN,
+					&Ok(())
+				));
+				assert_eq!(Balances::free_balance(PAYER), EXISTENTIAL_DEPOSIT);
+			});
+		}
+	}
+
+	mod deadline_tests {
+		use frame_support::dispatch::DispatchInfo;
+		use frame_support::{assert_noop, assert_ok};
+		use sp_runtime::traits::SignedExtension;
+		use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
+
+		use crate::mock::{Dex, RuntimeCall, RuntimeOrigin, System, Test, FEE_TIER};
+		use crate::tests::tests::{
+			create_asset, create_asset_amount_pair, init_test_ext, mint_asset, ALICE, ASSET_X,
+			ASSET_Y, BOB, EXISTENTIAL_DEPOSIT, TEN_K, TEN_M, X_Y_ID,
+		};
+		use crate::{AssetAmount, CheckDexDeadline, Error};
+
+		fn swap_call(deadline: Option<u64>) -> RuntimeCall {
+			RuntimeCall::Dex(crate::Call::swap_limit_take {
+				give: AssetAmount::new(ASSET_X, TEN_K),
+				expect_min_take: 0,
+				pool_id: X_Y_ID,
+				fee_tier: FEE_TIER,
+				deadline,
+			})
+		}
+
+		fn validate(call: &RuntimeCall) -> Result<u64, TransactionValidityError> {
+			CheckDexDeadline::<Test>::new()
+				.validate(&ALICE, call, &DispatchInfo::default(), 0)
+				.map(|valid| valid.longevity)
+		}
+
+		#[test]
+		fn calls_should_pass_until_their_deadline() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(5);
+
+				// given created assets
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+				mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+
+				// calls with a deadline of the current block or later should pass
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					Some(5)
+				));
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+					FEE_TIER,
+					Some(6)
+				));
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(ALICE),
+					AssetAmount::new(ASSET_X, TEN_K),
+					0,
+					X_Y_ID,
+					FEE_TIER,
+					Some(5)
+				));
+			});
+		}
+
+		#[test]
+		fn calls_past_their_deadline_should_fail() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(5);
+
+				// given a pool with liquidity
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
+				mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					None
+				));
+				assert_ok!(Dex::provide_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					create_asset_amount_pair(TEN_K, ASSET_X, ASSET_Y),
+					FEE_TIER,
+					None
+				));
+
+				// calls with a deadline before the current block should fail
+				assert_noop!(
+					Dex::swap_limit_take(
+						RuntimeOrigin::signed(ALICE),
+						AssetAmount::new(ASSET_X, TEN_K),
+						0,
+						X_Y_ID,
+						FEE_TIER,
+						Some(4)
+					),
+					Error::<Test>::DeadlineExpired
+				);
+				assert_noop!(
+					Dex::add_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						TEN_K,
+						TEN_K,
+						0,
+						0,
+						Some(4)
+					),
+					Error::<Test>::DeadlineExpired
+				);
+				assert_noop!(
+					Dex::remove_liquidity(
+						RuntimeOrigin::signed(ALICE),
+						X_Y_ID,
+						FEE_TIER,
+						TEN_K / 2,
+						0,
+						0,
+						None,
+						Some(4)
+					),
+					Error::<Test>::DeadlineExpired
+				);
+
+				// as should calls that only move what the caller owns
+				assert_noop!(
+					Dex::cancel_order(RuntimeOrigin::signed(ALICE), 0, Some(4)),
+					Error::<Test>::DeadlineExpired
+				);
+				assert_noop!(
+					Dex::transfer_position(RuntimeOrigin::signed(ALICE), 0, BOB, Some(4)),
+					Error::<Test>::DeadlineExpired
+				);
+			});
+		}
+
+		#[test]
+		fn extension_should_reject_expired_calls() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(5);
+
+				// calls past their deadline should be stale
+				assert_eq!(validate(&swap_call(Some(4))), Err(InvalidTransaction::Stale.into()));
+
+				// calls before their deadline should only live until it
+				assert_eq!(validate(&swap_call(Some(5))), Ok(1));
+				assert_eq!(validate(&swap_call(Some(7))), Ok(3));
+				let cancel_call = |deadline| {
+					RuntimeCall::Dex(crate::Call::cancel_order { order_id: 0, deadline })
+				};
+				assert_eq!(validate(&cancel_call(Some(4))), Err(InvalidTransaction::Stale.into()));
+				assert_eq!(validate(&cancel_call(Some(7))), Ok(3));
+
+				// and calls without a deadline, or of other pallets, should be left alone
+				assert_eq!(validate(&swap_call(None)), Ok(u64::MAX));
+				let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+				assert_eq!(validate(&remark), Ok(u64::MAX));
+			});
+		}
+	}
+
+	mod migration_tests {
+		use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+		use sp_runtime::Perbill;
+
+		use crate::migration::{v0, MigrateToV1};
+		use crate::mock::{Dex, System, Test, Timestamp, FEE_TIER};
+		use crate::tests::tests::{
+			create_asset_amount_pair, init_test_ext, ASSET_X, ASSET_Y, LP_TOKEN_ID, TEN_M, X_Y_ID,
+		};
+		use crate::{Curve, FeeTiers, LpTokenToPair, Pools};
+
+		frame_support::parameter_types! {
+			pub LegacyFeeTier: Perbill = Perbill::from_perthousand(3);
+		}
+
+		#[test]
+		fn migration_should_key_pools_by_the_legacy_fee_tier() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				Timestamp::set_timestamp(10_000);
+
+				// given a pool stored before pools had fee tiers
+				StorageVersion::new(0).put::<Dex>();
+				v0::Pools::<Test>::insert(
+					X_Y_ID,
+					v0::LiquidityPool {
+						asset_amounts: create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y),
+						total_liquidity: TEN_M,
+						lp_token_id: LP_TOKEN_ID,
+					},
+				);
+
+				MigrateToV1::<Test, LegacyFeeTier>::on_runtime_upgrade();
+
+				// the pool should be kept at the legacy fee tier, which is allowed from then on
+				let pool = Pools::<Test>::get(X_Y_ID, LegacyFeeTier::get())
+					.expect("pool should be migrated");
+				assert_eq!(pool.asset_amounts, create_asset_amount_pair(TEN_M, ASSET_X, ASSET_Y));
+				assert_eq!(pool.total_liquidity, TEN_M);
+				assert_eq!(pool.lp_token_id, LP_TOKEN_ID);
+				assert_eq!(pool.fee_tier, LegacyFeeTier::get());
+				assert_eq!(pool.curve, Curve::ConstantProduct);
+				assert_eq!(pool.last_timestamp, 10);
+				assert_eq!(
+					LpTokenToPair::<Test>::get(LP_TOKEN_ID),
+					Some((X_Y_ID, LegacyFeeTier::get()))
+				);
+				assert_eq!(FeeTiers::<Test>::get().to_vec(), vec![LegacyFeeTier::get(), FEE_TIER]);
+				assert_eq!(Dex::on_chain_storage_version(), StorageVersion::new(1));
+
+				// and migrating again should change nothing
+				MigrateToV1::<Test, LegacyFeeTier>::on_runtime_upgrade();
+				assert_eq!(Pools::<Test>::iter().count(), 1);
+				assert_eq!(Pools::<Test>::get(X_Y_ID, LegacyFeeTier::get()), Some(pool));
+			});
+		}
+	}
+
+	mod math_tests {
+		use frame_support::assert_ok;
+		use num_bigint::BigUint;
+		use proptest::prelude::*;
+		use sp_core::U256;
+		use sp_runtime::{ArithmeticError, FixedPointNumber, FixedU128, PerThing, Perbill};
+
+		use crate::math::{self, Rounding, SwapStep};
+		use crate::mock::{Dex, RuntimeOrigin, System, FEE_TIER};
+		use crate::tests::tests::{
+			assert_account_has, create_asset, create_asset_amount_pair, init_test_ext, mint_asset,
+			ALICE, ASSET_X, ASSET_Y, BOB, CHARLIE, EXISTENTIAL_DEPOSIT, LP_TOKEN_ID, X_Y_ID,
+		};
+		use crate::{AssetAmount, ConstantProduct, ConstantSum, PricingCurve};
+
+		/// A whole token of an asset with 18 decimals.
+		const ONE_TOKEN: u128 = 1_000_000_000_000_000_000;
+		/// A reserve of a million tokens, the square of which overflows `u128`.
+		const RESERVE: u128 = 1_000_000 * ONE_TOKEN;
+
+		/// Returns the result the pallet's math is expected to return for a reference result,
+		/// which fails if it doesn't fit `u128`.
+		fn expected(value: BigUint) -> Result<u128, ArithmeticError> {
+			u128::try_from(value).map_err(|_| ArithmeticError::Overflow)
+		}
+
+		fn div_ceil(numerator: BigUint, denominator: &BigUint) -> BigUint {
+			(numerator + denominator - 1u32) / denominator
+		}
+
+		/// `a * b / c` over arbitrary precision integers.
+		fn reference_mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> BigUint {
+			let numerator = BigUint::from(a) * b;
+			match rounding {
+				Rounding::Down => numerator / c,
+				Rounding::Up => div_ceil(numerator, &BigUint::from(c)),
+			}
+		}
+
+		/// The constant product formula over arbitrary precision integers, with the fee rounded
+		/// up.
+		fn reference_amount_out(
+			amount_in: u128,
+			reserve_in: u128,
+			reserve_out: u128,
+			fee: Perbill,
+		) -> Result<u128, ArithmeticError> {
+			let parts = BigUint::from(Perbill::ACCURACY);
+			let fee = div_ceil(BigUint::from(amount_in) * fee.deconstruct(), &parts);
+			let amount_in_less_fee = BigUint::from(amount_in) - fee;
+			let denominator = BigUint::from(reserve_in) + &amount_in_less_fee;
+			if denominator == BigUint::from(0u32) {
+				return Err(ArithmeticError::DivisionByZero);
+			}
+			expected(BigUint::from(reserve_out) * amount_in_less_fee / denominator)
+		}
+
+		/// The amount to give for `amount_out`, `reserve_in * amount_out / ((reserve_out -
+		/// amount_out) * (1 - fee))`, over arbitrary precision integers, rounded up.
+		fn reference_amount_in(
+			amount_out: u128,
+			reserve_in: u128,
+			reserve_out: u128,
+			fee: Perbill,
+		) -> Result<u128, ArithmeticError> {
+			let reserve_out_left =
+				reserve_out.checked_sub(amount_out).ok_or(ArithmeticError::Underflow)?;
+			let denominator = BigUint::from(reserve_out_left) * fee.left_from_one().deconstruct();
+			if denominator == BigUint::from(0u32) {
+				return Err(ArithmeticError::DivisionByZero);
+			}
+			let numerator = BigUint::from(reserve_in) * amount_out * Perbill::ACCURACY;
+			expected(div_ceil(numerator, &denominator))
+		}
+
+		/// Whether the weighted product of a pool of two assets weighing `weights`, `x^w_x * y^w_y`,
+		/// is no lower with reserves `after` than `before`, over arbitrary precision integers. The
+		/// weights are whole percents, which the comparison raises both products to the power of
+		/// 100 to get rid of.
+		fn weighted_product_kept(
+			before: (u128, u128),
+			after: (u128, u128),
+			(weight_x, weight_y): (u32, u32),
+		) -> bool {
+			let product = |(x, y): (u128, u128)| {
+				BigUint::from(x).pow(weight_x) * BigUint::from(y).pow(weight_y)
+			};
+			product(after) >= product(before)
+		}
+
+		/// The exact protocol fee, `total * (root_k - root_k_last) * share / (root_k * (1 - share)
+		/// + root_k_last * share)`, over arbitrary precision integers, rounded down.
+		fn exact_protocol_fee(
+			total: u128,
+			root_k: u128,
+			root_k_last: u128,
+			share: Perbill,
+		) -> BigUint {
+			let parts = share.deconstruct();
+			let numerator = BigUint::from(total) * (root_k - root_k_last) * parts;
+			let denominator = BigUint::from(root_k) * (Perbill::ACCURACY - parts)
+				+ BigUint::from(root_k_last) * parts;
+			numerator / denominator
+		}
+
+		fn fee() -> impl Strategy<Value = Perbill> {
+			(0..=Perbill::ACCURACY).prop_map(Perbill::from_parts)
+		}
+
+		proptest! {
+			#[test]
+			fn mul_div_should_match_reference(a: u128, b: u128, c in 1..=u128::MAX) {
+				for rounding in [Rounding::Down, Rounding::Up] {
+					prop_assert_eq!(
+						math::mul_div(a, b, c, rounding),
+						expected(reference_mul_div(a, b, c, rounding))
+					);
+				}
+			}
+
+			#[test]
+			fn sqrt_of_product_should_match_reference(a: u128, b: u128) {
+				prop_assert_eq!(
+					math::sqrt_of_product(a, b),
+					expected((BigUint::from(a) * b).sqrt())
+				);
 			}
 
 			#[test]
-			fn swap_limit_give_should_work() {
-				init_test_ext().execute_with(|| {
-					System::set_block_number(1);
-					let reserve_x = TEN_M;
-					let reserve_y = TEN_M;
-					let liquidity = TEN_M;
-					let take = TEN_K;
+			fn amount_out_should_match_reference(
+				amount_in: u128,
+				reserve_in: u128,
+				reserve_out: u128,
+				fee in fee(),
+			) {
+				prop_assert_eq!(
+					math::get_amount_out(amount_in, reserve_in, reserve_out, fee),
+					reference_amount_out(amount_in, reserve_in, reserve_out, fee)
+				);
+			}
 
-					// given created assets and pool
-					create_asset(ASSET_X);
-					create_asset(ASSET_Y);
-					create_pool(X_Y_ID, reserve_x, reserve_y, liquidity);
+			#[test]
+			fn amount_out_should_never_decrease_k(
+				amount_in: u128,
+				reserve_in in 1..=u128::MAX,
+				reserve_out: u128,
+				fee in fee(),
+			) {
+				let amount_out = math::get_amount_out(amount_in, reserve_in, reserve_out, fee)
+					.expect("a non-empty pool can always be quoted");
+				prop_assert!(amount_out <= reserve_out);
+				prop_assert!(
+					(BigUint::from(reserve_in) + amount_in) * (reserve_out - amount_out)
+						>= BigUint::from(reserve_in) * reserve_out
+				);
+			}
 
-					// and assets minted to Alice
-					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
-					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+			#[test]
+			fn amount_in_should_match_reference(
+				amount_out: u128,
+				reserve_in: u128,
+				reserve_out: u128,
+				fee in fee(),
+			) {
+				prop_assert_eq!(
+					math::get_amount_in(amount_out, reserve_in, reserve_out, fee),
+					reference_amount_in(amount_out, reserve_in, reserve_out, fee)
+				);
+			}
 
-					// and assets minted to dex account
-					mint_asset(Dex::dex_account_id(), reserve_x + EXISTENTIAL_DEPOSIT, ASSET_X);
-					mint_asset(Dex::dex_account_id(), reserve_y + EXISTENTIAL_DEPOSIT, ASSET_Y);
+			#[test]
+			fn amount_in_should_never_decrease_k(
+				amount_out: u128,
+				reserve_in: u128,
+				reserve_out: u128,
+				fee in fee(),
+			) {
+				if let Ok(amount_in) = math::get_amount_in(amount_out, reserve_in, reserve_out, fee) {
+					// what is left of the amount given after the fee, scaled by the fee's accuracy
+					let amount_in_less_fee =
+						BigUint::from(amount_in) * fee.left_from_one().deconstruct();
+					let reserve_in_scaled = BigUint::from(reserve_in) * Perbill::ACCURACY;
+					prop_assert!(
+						(&reserve_in_scaled + amount_in_less_fee) * (reserve_out - amount_out)
+							>= reserve_in_scaled * reserve_out
+					);
+				}
+			}
 
-					let expected_max_give_amount =
-						calculate_expected_give_amount(take, reserve_x, reserve_y);
+			#[test]
+			fn stable_swap_amount_out_should_never_decrease_d(
+				amount_in in 0..RESERVE,
+				reserve_in in 1..RESERVE,
+				reserve_out in 1..RESERVE,
+				amplification in 1..=1_000_000u32,
+			) {
+				let d = math::stable_swap_d(reserve_in, reserve_out, amplification)
+					.expect("a non-empty pool has an invariant");
+				let amount_out = math::stable_swap_amount_out(
+					amount_in,
+					reserve_in,
+					reserve_out,
+					Perbill::zero(),
+					amplification,
+				)
+				.expect("a non-empty pool can always be quoted");
+				prop_assert!(amount_out < reserve_out);
+				prop_assert!(
+					math::stable_swap_d(reserve_in + amount_in, reserve_out - amount_out, amplification)
+						.expect("a non-empty pool has an invariant")
+						>= d
+				);
+			}
 
-					// swap should work
-					let take_amount = AssetAmount::<Test>::new(ASSET_Y, take);
-					assert_ok!(Dex::swap_limit_give(
-						RuntimeOrigin::signed(ALICE),
-						take_amount,
-						expected_max_give_amount,
-						X_Y_ID
-					));
+			#[test]
+			fn stable_swap_amount_in_should_never_decrease_d(
+				amount_out in 1..RESERVE,
+				reserve_in in 1..RESERVE,
+				reserve_out in 1..RESERVE,
+				amplification in 1..=1_000_000u32,
+			) {
+				prop_assume!(amount_out < reserve_out);
+				let d = math::stable_swap_d(reserve_in, reserve_out, amplification)
+					.expect("a non-empty pool has an invariant");
+				let amount_in = math::stable_swap_amount_in(
+					amount_out,
+					reserve_in,
+					reserve_out,
+					Perbill::zero(),
+					amplification,
+				)
+				.expect("a swap leaving some of the reserve can always be quoted");
+				prop_assert!(
+					math::stable_swap_d(reserve_in + amount_in, reserve_out - amount_out, amplification)
+						.expect("a non-empty pool has an invariant")
+						>= d
+				);
+			}
 
-					// and token issuance event should be emitted
-					let event = System::events().last().unwrap().clone().event;
-					if let RuntimeEvent::Dex(Event::TokenSwapped { who, give, take }) = event {
-						assert_eq!(who, ALICE);
-						assert!(give.balance <= expected_max_give_amount);
-						assert_eq!(take.balance, take_amount.balance);
-					} else {
-						panic!("Expected TokenSwapped event");
-					}
+			#[test]
+			fn weighted_amount_out_should_never_decrease_the_invariant(
+				amount_in in 1..RESERVE,
+				reserve_in in 1..RESERVE,
+				reserve_out in 1..RESERVE,
+				weight_in in 1..100u32,
+				fee in fee(),
+			) {
+				// at most 30% of the reserve, as the pallet allows
+				prop_assume!(amount_in <= reserve_in / 10 * 3);
+				let weight_out = 100 - weight_in;
+				let amount_out = math::weighted_amount_out(
+					amount_in,
+					reserve_in,
+					Perbill::from_percent(weight_in),
+					reserve_out,
+					Perbill::from_percent(weight_out),
+					fee,
+				)
+				.expect("a swap of at most 30% of the reserve can always be quoted");
+				prop_assert!(amount_out < reserve_out);
+				prop_assert!(weighted_product_kept(
+					(reserve_in, reserve_out),
+					(reserve_in + amount_in, reserve_out - amount_out),
+					(weight_in, weight_out),
+				));
+			}
 
-					// and alice should have exactly expected amount of asset y increased
-					assert_account_has(ALICE, ASSET_Y, TEN_M + EXISTENTIAL_DEPOSIT + take);
+			#[test]
+			fn weighted_amount_in_should_never_decrease_the_invariant(
+				amount_out in 1..RESERVE,
+				reserve_in in 1..RESERVE,
+				reserve_out in 1..RESERVE,
+				weight_in in 1..100u32,
+				fee in fee(),
+			) {
+				prop_assume!(amount_out <= reserve_out / 10 * 3);
+				let weight_out = 100 - weight_in;
+				let amount_in = match math::weighted_amount_in(
+					amount_out,
+					reserve_in,
+					Perbill::from_percent(weight_in),
+					reserve_out,
+					Perbill::from_percent(weight_out),
+					fee,
+				) {
+					Ok(amount_in) => amount_in,
+					// the steepest weights can price the swap above `u128::MAX`
+					Err(error) => {
+						prop_assert_eq!(error, ArithmeticError::Overflow);
+						return Ok(());
+					},
+				};
+				prop_assert!(weighted_product_kept(
+					(reserve_in, reserve_out),
+					(reserve_in + amount_in, reserve_out - amount_out),
+					(weight_in, weight_out),
+				));
+			}
 
-					// and no more than expected_max_give_amount of asset x decreased
-					assert!(
-						get_account_balance(ALICE, ASSET_X)
-							> EXISTENTIAL_DEPOSIT + (TEN_M - expected_max_give_amount)
+			#[test]
+			fn protocol_fee_should_not_exceed_exact_share(
+				total: u128,
+				root_k: u128,
+				root_k_last in 1..=u128::MAX,
+				share in fee(),
+			) {
+				let lp_tokens = math::protocol_fee(total, root_k, root_k_last, share);
+				if share.is_zero() || root_k <= root_k_last {
+					prop_assert_eq!(lp_tokens, Ok(0));
+				} else if let Ok(lp_tokens) = lp_tokens {
+					prop_assert!(
+						BigUint::from(lp_tokens)
+							<= exact_protocol_fee(total, root_k, root_k_last, share)
 					);
-				});
+				}
 			}
 
 			#[test]
-			fn swapping_giving_zero_amount_should_fail() {
-				init_test_ext().execute_with(|| {
-					System::set_block_number(1);
+			fn tick_at_sqrt_price_should_invert_sqrt_price_at_tick(
+				tick in math::MIN_TICK..math::MAX_TICK,
+			) {
+				let sqrt_price = math::sqrt_price_at_tick(tick).expect("the tick is in range");
+				prop_assert_eq!(math::tick_at_sqrt_price(sqrt_price), Ok(tick));
+				// and every square root of a price below that of the next tick is at the tick
+				let next_sqrt_price =
+					math::sqrt_price_at_tick(tick + 1).expect("the tick is in range");
+				prop_assert_eq!(math::tick_at_sqrt_price(next_sqrt_price - 1), Ok(tick));
+			}
 
-					assert_noop!(
-						Dex::swap_limit_take(
-							RuntimeOrigin::signed(ALICE),
-							AssetAmount::<Test>::new(ASSET_X, 0u128),
-							0u128,
-							X_Y_ID
+			#[test]
+			fn amount_deltas_should_match_reference(
+				sqrt_price_a in math::MIN_SQRT_PRICE..=math::MAX_SQRT_PRICE,
+				sqrt_price_b in math::MIN_SQRT_PRICE..=math::MAX_SQRT_PRICE,
+				liquidity: u64,
+			) {
+				let liquidity = u128::from(liquidity);
+				let difference = BigUint::from(sqrt_price_a.abs_diff(sqrt_price_b)) * liquidity;
+				let x_denominator = BigUint::from(sqrt_price_a) * sqrt_price_b;
+				let y_denominator = BigUint::from(1u8) << 64;
+				let x = &difference << 64;
+				for rounding in [Rounding::Down, Rounding::Up] {
+					let (expected_x, expected_y) = match rounding {
+						Rounding::Down => (&x / &x_denominator, &difference / &y_denominator),
+						Rounding::Up => (
+							div_ceil(x.clone(), &x_denominator),
+							div_ceil(difference.clone(), &y_denominator),
 						),
-						Error::<Test>::ZeroSwapAmountRequested
+					};
+					prop_assert_eq!(
+						math::amount_x_delta(sqrt_price_a, sqrt_price_b, liquidity, rounding),
+						expected(expected_x)
 					);
-				});
+					prop_assert_eq!(
+						math::amount_y_delta(sqrt_price_a, sqrt_price_b, liquidity, rounding),
+						expected(expected_y)
+					);
+				}
 			}
+		}
 
-			#[test]
-			fn unsatisfiable_swap_should_fail() {
-				init_test_ext().execute_with(|| {
-					System::set_block_number(1);
+		#[test]
+		fn stable_swap_d_of_equal_reserves_should_be_their_total() {
+			for amplification in [1, 100, 1_000_000] {
+				assert_eq!(math::stable_swap_d(RESERVE, RESERVE, amplification), Ok(2 * RESERVE));
+			}
+		}
 
-					// given created assets and pool with 10k
-					create_asset(ASSET_X);
-					create_asset(ASSET_Y);
-					create_balanced_pool(X_Y_ID, TEN_K, TEN_K);
+		#[test]
+		fn stable_swap_price_should_be_one_at_the_peg() {
+			assert_eq!(math::stable_swap_price(RESERVE, RESERVE, 100), Ok(FixedU128::one()));
+		}
 
-					// and assets minted to Alice, value 10m
-					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_X);
-					mint_asset(ALICE, TEN_M + EXISTENTIAL_DEPOSIT, ASSET_Y);
+		#[test]
+		fn stable_swap_price_should_tend_to_the_constant_product_price() {
+			// the more amplified, the closer to the peg the price of an unbalanced pool stays
+			let prices: Vec<_> = [1_000_000, 100, 1]
+				.into_iter()
+				.map(|amplification| {
+					math::stable_swap_price(2 * RESERVE, RESERVE, amplification)
+						.expect("the price of a non-empty pool fits")
+				})
+				.collect();
+			assert!(prices.windows(2).all(|pair| pair[0] < pair[1]));
+			assert!(prices[2] < FixedU128::from_u32(2));
+		}
 
-					// and assets minted to dex account, value 10k
-					mint_asset(Dex::dex_account_id(), TEN_K + EXISTENTIAL_DEPOSIT, ASSET_X);
-					mint_asset(Dex::dex_account_id(), TEN_K + EXISTENTIAL_DEPOSIT, ASSET_Y);
+		#[test]
+		fn pow_should_be_rounded_in_the_requested_direction() {
+			let one = U256::from(ONE_TOKEN);
+			let half = Perbill::from_percent(50);
+
+			// the square root of four is two, approximated and rounded around it
+			let root =
+				|rounding| math::pow(U256::from(4), U256::one(), half, Perbill::one(), rounding);
+			assert!(root(Rounding::Down).expect("the power fits") <= 2 * one);
+			assert!(root(Rounding::Up).expect("the power fits") >= 2 * one);
+			assert!(
+				root(Rounding::Up).expect("the power fits") - 2 * one < one / 1_000_000_000_000
+			);
+
+			// an exponent of one is exact, and of zero is one
+			assert_eq!(
+				math::pow(U256::from(3), U256::from(4), half, half, Rounding::Down),
+				Ok(one * 3 / 4)
+			);
+			assert_eq!(
+				math::pow(U256::from(3), U256::from(4), Perbill::zero(), half, Rounding::Up),
+				Ok(one)
+			);
+		}
 
-					// swap should fail with excessive input amount when asking to swap 10m, and expecting 1m-10k
-					let give = AssetAmount::<Test>::new(ASSET_X, TEN_M);
-					assert_noop!(
-						Dex::swap_limit_take(
-							RuntimeOrigin::signed(ALICE),
-							give,
-							TEN_M - TEN_K,
-							X_Y_ID
-						),
-						Error::<Test>::MinimumOutputNotReached
-					);
-				});
+		#[test]
+		fn weighted_invariant_of_equal_reserves_should_be_the_reserve() {
+			// rounded down by the error bound of the power
+			for weights in [[50, 50], [80, 20], [1, 99]] {
+				let invariant = math::weighted_invariant(
+					weights.map(|weight| (RESERVE, Perbill::from_percent(weight))),
+				)
+				.expect("the invariant of a non-empty pool fits");
+				assert!(
+					invariant <= RESERVE && RESERVE - invariant <= RESERVE / 10_000_000_000_000
+				);
 			}
 		}
-		mod get_asset_price_tests {
-			use frame_support::assert_ok;
-			use sp_runtime::FixedU128;
 
-			use crate::mock::{Dex, RuntimeOrigin, System};
-			use crate::tests::tests::{
-				create_asset, create_pool, init_test_ext, ALICE, ASSET_X, ASSET_Y, TEN_M, X_Y_ID,
-			};
-			use crate::Event::AssetPrice;
+		#[test]
+		fn weighted_swaps_of_equal_weights_should_match_the_constant_product() {
+			let half = Perbill::from_percent(50);
+			assert_eq!(
+				math::weighted_amount_out(
+					100_000u128,
+					10_000_000,
+					half,
+					10_000_000,
+					half,
+					FEE_TIER
+				),
+				math::get_amount_out(100_000u128, 10_000_000, 10_000_000, FEE_TIER)
+			);
+		}
 
-			#[test]
-			fn get_price_of_should_work() {
-				let x_vs_y = 2;
-				let price_of_x_in_y = FixedU128::from_rational(x_vs_y, 1);
-				init_test_ext().execute_with(|| {
-					System::set_block_number(1);
+		#[test]
+		fn constant_sum_should_swap_one_for_one_less_the_fee() {
+			assert_eq!(math::constant_sum_amount_out(100_000u128, FEE_TIER), Ok(99_000));
+			assert_eq!(math::constant_sum_amount_in(99_000u128, FEE_TIER), Ok(100_000));
+			// rounded up
+			assert_eq!(math::constant_sum_amount_in(99_001u128, FEE_TIER), Ok(100_002));
+			assert_eq!(
+				math::constant_sum_amount_in(1u128, Perbill::one()),
+				Err(ArithmeticError::DivisionByZero)
+			);
+		}
 
-					// given created assets and pool
-					create_asset(ASSET_X);
-					create_asset(ASSET_Y);
-					create_pool(X_Y_ID, TEN_M * x_vs_y, TEN_M, TEN_M);
+		#[test]
+		fn sqrt_prices_of_ticks_should_be_bounded() {
+			assert_eq!(math::sqrt_price_at_tick(0), Ok(1 << 64));
+			// a tick is a change of the price of 0.01%, so about 0.005% of its square root
+			assert_eq!(math::sqrt_price_at_tick(1), Ok(18_447_666_387_855_959_851));
+			assert_eq!(math::sqrt_price_at_tick(-1), Ok(18_445_821_805_675_392_312));
+			assert_eq!(math::sqrt_price_at_tick(math::MIN_TICK), Ok(math::MIN_SQRT_PRICE));
+			assert_eq!(math::sqrt_price_at_tick(math::MAX_TICK), Ok(math::MAX_SQRT_PRICE));
+
+			assert_eq!(
+				math::sqrt_price_at_tick(math::MIN_TICK - 1),
+				Err(ArithmeticError::Overflow)
+			);
+			assert_eq!(
+				math::sqrt_price_at_tick(math::MAX_TICK + 1),
+				Err(ArithmeticError::Overflow)
+			);
+			assert_eq!(
+				math::tick_at_sqrt_price(math::MIN_SQRT_PRICE - 1),
+				Err(ArithmeticError::Overflow)
+			);
+			assert_eq!(
+				math::tick_at_sqrt_price(math::MAX_SQRT_PRICE + 1),
+				Err(ArithmeticError::Overflow)
+			);
+		}
 
-					// get price should work
-					assert_ok!(Dex::get_asset_price(RuntimeOrigin::signed(ALICE), X_Y_ID, ASSET_X));
+		#[test]
+		fn swap_steps_should_stop_at_the_target_or_when_the_amount_is_swapped() {
+			let sqrt_price = 1 << 64;
+			let liquidity = 1_000_000_000;
+			let above = math::sqrt_price_at_tick(600).expect("the tick is in range");
+			let below = math::sqrt_price_at_tick(-600).expect("the tick is in range");
+
+			// 40M of y, less the fee, is more than it takes to reach the target
+			assert_eq!(
+				math::swap_step(sqrt_price, above, liquidity, 40_000_000, FEE_TIER, true),
+				Ok(SwapStep {
+					sqrt_price: above,
+					amount_in: 30_452_989,
+					amount_out: 29_553_010,
+					fee: 307_606,
+				})
+			);
+			// 10M isn't, and all of it is given, less the fee
+			assert_eq!(
+				math::swap_step(sqrt_price, above, liquidity, 10_000_000, FEE_TIER, true),
+				Ok(SwapStep {
+					sqrt_price: 18_629_366_840_039_276_176,
+					amount_in: 9_900_000,
+					amount_out: 9_802_950,
+					fee: 100_000,
+				})
+			);
+			// and taking 10M of y gives x for it, rounded up
+			assert_eq!(
+				math::swap_step(sqrt_price, below, liquidity, 10_000_000, FEE_TIER, false),
+				Ok(SwapStep {
+					sqrt_price: 18_262_276_632_972_456_099,
+					amount_in: 10_101_011,
+					amount_out: 10_000_000,
+					fee: 102_031,
+				})
+			);
+		}
 
-					// and token asset price event should be emitted
-					System::assert_last_event(AssetPrice { price: price_of_x_in_y }.into());
-				});
+		#[test]
+		fn fees_earned_should_be_rounded_down() {
+			assert_eq!(math::fees_earned(math::fee_growth(1_000, 3), 3), Ok(999));
+			assert_eq!(math::fee_growth(1_000, 0), 0);
+		}
+
+		#[test]
+		fn pricing_curves_should_mint_the_invariant_then_by_share() {
+			// the first provision mints the invariant of the curve
+			assert_eq!(
+				ConstantProduct.lp_tokens_to_mint((RESERVE, 4 * RESERVE), (0, 0), 0),
+				Ok(2 * RESERVE)
+			);
+			assert_eq!(
+				ConstantSum.lp_tokens_to_mint((RESERVE, 4 * RESERVE), (0, 0), 0),
+				Ok(5 * RESERVE)
+			);
+
+			// and later ones the lower share of a reserve added, whatever the curve
+			for curve in [&ConstantProduct as &dyn PricingCurve<u128>, &ConstantSum] {
+				assert_eq!(
+					curve.lp_tokens_to_mint(
+						(RESERVE / 2, RESERVE),
+						(RESERVE, 4 * RESERVE),
+						2 * RESERVE
+					),
+					Ok(RESERVE / 2)
+				);
+				assert_eq!(
+					curve.amounts_for_lp_tokens(RESERVE, (RESERVE, 4 * RESERVE), 2 * RESERVE),
+					Ok((RESERVE / 2, 2 * RESERVE))
+				);
 			}
 		}
+
+		#[test]
+		fn mul_div_by_zero_fails() {
+			assert_eq!(
+				math::mul_div(1u128, 1, 0, Rounding::Down),
+				Err(ArithmeticError::DivisionByZero)
+			);
+		}
+
+		#[test]
+		fn mul_div_should_round_in_the_requested_direction() {
+			assert_eq!(math::mul_div(10u128, 10, 3, Rounding::Down), Ok(33));
+			assert_eq!(math::mul_div(10u128, 10, 3, Rounding::Up), Ok(34));
+			assert_eq!(math::mul_div(10u128, 9, 3, Rounding::Up), Ok(30));
+		}
+
+		#[test]
+		fn pools_of_18_decimal_assets_should_not_overflow() {
+			init_test_ext().execute_with(|| {
+				System::set_block_number(1);
+				create_asset(ASSET_X);
+				create_asset(ASSET_Y);
+				assert_ok!(Dex::create_pool(
+					RuntimeOrigin::signed(ALICE),
+					ASSET_X,
+					ASSET_Y,
+					FEE_TIER,
+					None
+				));
+
+				// given alice and bob provide a million tokens of each asset, whose product
+				// overflows u128
+				for who in [ALICE, BOB] {
+					mint_asset(who, RESERVE + EXISTENTIAL_DEPOSIT, ASSET_X);
+					mint_asset(who, RESERVE + EXISTENTIAL_DEPOSIT, ASSET_Y);
+					assert_ok!(Dex::provide_liquidity(
+						RuntimeOrigin::signed(who),
+						create_asset_amount_pair(RESERVE, ASSET_X, ASSET_Y),
+						FEE_TIER,
+						None
+					));
+					assert_account_has(who, LP_TOKEN_ID, RESERVE);
+				}
+
+				// when charlie swaps a thousand tokens of x
+				let give = 1_000 * ONE_TOKEN;
+				let expected_take =
+					reference_amount_out(give, 2 * RESERVE, 2 * RESERVE, Perbill::from_percent(1))
+						.expect("the pool is not empty");
+				mint_asset(CHARLIE, give + EXISTENTIAL_DEPOSIT, ASSET_X);
+				assert_ok!(Dex::swap_limit_take(
+					RuntimeOrigin::signed(CHARLIE),
+					AssetAmount::new(ASSET_X, give),
+					expected_take,
+					X_Y_ID,
+					FEE_TIER,
+					None
+				));
+				assert_account_has(CHARLIE, ASSET_Y, expected_take);
+
+				// then alice should be able to withdraw her half of the pool
+				assert_ok!(Dex::remove_liquidity(
+					RuntimeOrigin::signed(ALICE),
+					X_Y_ID,
+					FEE_TIER,
+					RESERVE,
+					0,
+					0,
+					None,
+					None
+				));
+				assert_account_has(ALICE, ASSET_X, (2 * RESERVE + give) / 2 + EXISTENTIAL_DEPOSIT);
+				assert_account_has(
+					ALICE,
+					ASSET_Y,
+					(2 * RESERVE - expected_take) / 2 + EXISTENTIAL_DEPOSIT,
+				);
+			});
+		}
 	}
 
 	mod integration_tests {
@@ -626,7 +6501,8 @@ mod tests {
 					RuntimeOrigin::signed(ALICE),
 					ASSET_X,
 					ASSET_Y,
-					LP_TOKEN_ID
+					FEE_TIER,
+					None
 				));
 
 				// Alice Provides liquidity of 10m
@@ -636,7 +6512,8 @@ mod tests {
 				assert_ok!(Dex::provide_liquidity(
 					RuntimeOrigin::signed(ALICE),
 					provision,
-					LP_TOKEN_ID
+					FEE_TIER,
+					None
 				));
 
 				// Bob Swaps 10k
@@ -648,7 +6525,9 @@ mod tests {
 					RuntimeOrigin::signed(BOB),
 					give_amount,
 					expected_taken_amount,
-					X_Y_ID
+					X_Y_ID,
+					FEE_TIER,
+					None
 				));
 
 				let precision_loss = 1;
@@ -674,7 +6553,12 @@ mod tests {
 
 				// Bob gets asset price
 				let (expected_x_reserve, expected_y_reserve) = (10_010_000u128, 9_990_110u128);
-				assert_ok!(Dex::get_asset_price(RuntimeOrigin::signed(BOB), X_Y_ID, ASSET_X));
+				assert_ok!(Dex::get_asset_price(
+					RuntimeOrigin::signed(BOB),
+					X_Y_ID,
+					FEE_TIER,
+					ASSET_X
+				));
 				System::assert_last_event(
 					AssetPrice {
 						price: AssetBalancePairToRatioConverter::convert((
@@ -687,7 +6571,7 @@ mod tests {
 
 				// Get pool and check reserves have changed
 				assert_eq!(
-					Pools::<Test>::get(&X_Y_ID).expect("pool should exist"),
+					Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist"),
 					create_pool(X_Y_ID, expected_x_reserve, expected_y_reserve, liquidity)
 				);
 
@@ -698,7 +6582,12 @@ mod tests {
 				assert_ok!(Dex::remove_liquidity(
 					RuntimeOrigin::signed(ALICE),
 					X_Y_ID,
-					liquidity - 1
+					FEE_TIER,
+					liquidity - 1,
+					0,
+					0,
+					None,
+					None
 				)
 				.into());
 				let total_alice_balance =
@@ -729,7 +6618,8 @@ mod tests {
 					RuntimeOrigin::signed(ALICE),
 					ASSET_X,
 					ASSET_Y,
-					LP_TOKEN_ID
+					FEE_TIER,
+					None
 				));
 
 				// Alice Provides liquidity of 10m
@@ -739,7 +6629,8 @@ mod tests {
 				assert_ok!(Dex::provide_liquidity(
 					RuntimeOrigin::signed(ALICE),
 					provision,
-					LP_TOKEN_ID
+					FEE_TIER,
+					None
 				));
 
 				// Charlie Provides liquidity of 10k
@@ -749,7 +6640,8 @@ mod tests {
 				assert_ok!(Dex::provide_liquidity(
 					RuntimeOrigin::signed(CHARLIE),
 					provision,
-					LP_TOKEN_ID
+					FEE_TIER,
+					None
 				));
 
 				// Bob Swaps 10k
@@ -761,7 +6653,9 @@ mod tests {
 					RuntimeOrigin::signed(BOB),
 					give_amount,
 					expected_taken_amount,
-					X_Y_ID
+					X_Y_ID,
+					FEE_TIER,
+					None
 				));
 
 				// Check bob x tokens have been sent and y tokens received
@@ -784,7 +6678,12 @@ mod tests {
 
 				// Bob gets asset price
 				let (expected_x_reserve, expected_y_reserve) = (10_020_000u128, 10_000_110u128);
-				assert_ok!(Dex::get_asset_price(RuntimeOrigin::signed(BOB), X_Y_ID, ASSET_X));
+				assert_ok!(Dex::get_asset_price(
+					RuntimeOrigin::signed(BOB),
+					X_Y_ID,
+					FEE_TIER,
+					ASSET_X
+				));
 				System::assert_last_event(
 					AssetPrice {
 						price: AssetBalancePairToRatioConverter::convert((
@@ -797,7 +6696,7 @@ mod tests {
 
 				// Alice gets pool and check reserves have changed
 				assert_eq!(
-					Pools::<Test>::get(&X_Y_ID).expect("pool should exist"),
+					Pools::<Test>::get(&X_Y_ID, FEE_TIER).expect("pool should exist"),
 					create_pool(
 						X_Y_ID,
 						expected_x_reserve,
@@ -846,7 +6745,11 @@ mod tests {
 	fn create_empty_pool(asset_x_id: u32, asset_y_id: u32) {
 		let id_pair: AssetIdPair<Test> =
 			AssetIdPair::new(asset_x_id, asset_y_id).expect("id pair should be valid");
-		Pools::insert(id_pair.clone(), LiquidityPool::empty_from_pair(id_pair, LP_TOKEN_ID));
+		Pools::insert(
+			id_pair.clone(),
+			FEE_TIER,
+			LiquidityPool::empty_from_pair(id_pair, LP_TOKEN_ID, FEE_TIER, Curve::ConstantProduct),
+		);
 	}
 
 	fn create_balanced_pool(id_pair: AssetIdPair<Test>, balance: u128, liquidity: u128) {
@@ -861,14 +6764,19 @@ mod tests {
 	) -> LiquidityPool<Test> {
 		let id_pair: AssetIdPair<Test> = AssetIdPair::new(id_pair.asset_x_id, id_pair.asset_y_id)
 			.expect("id pair should be valid");
-		let mut pool = LiquidityPool::empty_from_pair(id_pair.clone(), LP_TOKEN_ID);
+		let mut pool = LiquidityPool::empty_from_pair(
+			id_pair.clone(),
+			LP_TOKEN_ID,
+			FEE_TIER,
+			Curve::ConstantProduct,
+		);
 		let mut provision = AssetAmountPair::empty(id_pair.clone());
 		provision.amount_x.balance = balance_x;
 		provision.amount_y.balance = balance_y;
 		pool.asset_amounts = provision;
 		pool.total_liquidity = liquidity;
-		Pools::insert(id_pair.clone(), pool.clone());
-		assert!(Pools::get(&id_pair).is_some());
+		Pools::insert(id_pair.clone(), FEE_TIER, pool.clone());
+		assert!(Pools::get(&id_pair, FEE_TIER).is_some());
 		pool
 	}
 
@@ -896,9 +6804,8 @@ mod tests {
 	}
 
 	fn calculate_expected_taken_amount(give: u128, reserve_x: u128, reserve_y: u128) -> u128 {
-		let fee_pct = <Test as Config>::FeePct::get(); // Swap fee percentage
 		let amount_in_with_fee =
-			give.ensure_sub(fee_pct * give).expect("Bad taken amount calculation");
+			give.ensure_sub(FEE_TIER.mul_ceil(give)).expect("Bad taken amount calculation");
 		let numerator =
 			reserve_y.ensure_mul(amount_in_with_fee).expect("Bad taken amount calculation");
 		let denominator = reserve_x
@@ -909,23 +6816,6 @@ mod tests {
 		take
 	}
 
-	fn calculate_expected_give_amount(take: u128, reserve_x: u128, reserve_y: u128) -> u128 {
-		let fee_pct = <Test as Config>::FeePct::get(); // Swap fee percentage
-		let new_reserve_y = reserve_y.ensure_add(take).expect("Bad give amount calculation");
-		let new_reserve_x = reserve_x
-			.ensure_mul(new_reserve_y)
-			.expect("Bad give amount calculation")
-			.ensure_div(reserve_y)
-			.expect("Bad give amount calculation");
-		let raw_give_amount_x =
-			new_reserve_x.ensure_sub(reserve_x).expect("Bad give amount calculation");
-		let give = raw_give_amount_x
-			.ensure_add(fee_pct * raw_give_amount_x)
-			.expect("Bad give amount calculation");
-
-		give
-	}
-
 	fn init_test_ext() -> TestExternalities {
 		new_test_ext(vec![
 			(Dex::dex_account_id(), TEN_B),
@@ -933,6 +6823,7 @@ mod tests {
 			(ALICE, TEN_B),
 			(BOB, TEN_B),
 			(CHARLIE, TEN_B),
+			(PROTOCOL_FEE_RECIPIENT, TEN_B),
 		])
 	}
 }
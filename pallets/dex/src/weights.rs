@@ -0,0 +1,843 @@
+//! Weights for pallet_dex
+//!
+//! The values below are estimated from the storage accesses of each call and the weights of the
+//! equivalent `pallet_assets` operations, they should be regenerated on reference hardware with:
+//!
+//! ```shell
+//! cargo build --release --features runtime-benchmarks
+//! ./target/release/node-template benchmark pallet \
+//! 	--chain dev \
+//! 	--wasm-execution=compiled \
+//! 	--pallet pallet_dex \
+//! 	--extrinsic '*' \
+//! 	--steps 50 \
+//! 	--repeat 20 \
+//! 	--output pallets/dex/src/weights.rs \
+//! 	--template frame-weight-template.hbs
+//! ```
+//!
+//! where `frame-weight-template.hbs` is the weight template from Substrate's `.maintain` folder.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_dex.
+pub trait WeightInfo {
+	fn create_pool() -> Weight;
+	fn provide_liquidity() -> Weight;
+	fn remove_liquidity() -> Weight;
+	fn swap_limit_take() -> Weight;
+	fn swap_limit_give() -> Weight;
+	fn get_asset_price() -> Weight;
+	fn swap_exact_in_along_path(n: u32, ) -> Weight;
+	fn swap_exact_out_along_path(n: u32, ) -> Weight;
+	fn set_protocol_fee() -> Weight;
+	fn add_liquidity() -> Weight;
+	fn set_fee_tier() -> Weight;
+	fn set_protocol_share() -> Weight;
+	fn set_min_liquidity() -> Weight;
+	fn set_lp_token_dust() -> Weight;
+	fn create_stable_pool() -> Weight;
+	fn ramp_amplification() -> Weight;
+	fn create_weighted_pool() -> Weight;
+	fn join_weighted_pool() -> Weight;
+	fn join_weighted_pool_single() -> Weight;
+	fn exit_weighted_pool() -> Weight;
+	fn exit_weighted_pool_single() -> Weight;
+	fn swap_weighted_limit_take() -> Weight;
+	fn swap_weighted_limit_give() -> Weight;
+	fn create_constant_sum_pool() -> Weight;
+	fn create_concentrated_pool() -> Weight;
+	fn open_position() -> Weight;
+	fn decrease_position() -> Weight;
+	fn collect_position_fees() -> Weight;
+	fn transfer_position() -> Weight;
+	fn swap_concentrated_limit_take() -> Weight;
+	fn swap_concentrated_limit_give() -> Weight;
+	fn place_order() -> Weight;
+	fn cancel_order() -> Weight;
+	fn fill_order() -> Weight;
+	fn flash_swap() -> Weight;
+	fn set_flash_fee() -> Weight;
+	fn zap_in() -> Weight;
+	fn zap_out() -> Weight;
+}
+
+/// Weights for pallet_dex using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex LpTokenToPair (r:0 w:1)
+	fn create_pool() -> Weight {
+		Weight::from_parts(43_000_000, 3_675)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn provide_liquidity() -> Weight {
+		Weight::from_parts(116_000_000, 24_127)
+			.saturating_add(T::DbWeight::get().reads(15_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn remove_liquidity() -> Weight {
+		Weight::from_parts(118_000_000, 24_127)
+			.saturating_add(T::DbWeight::get().reads(15_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:36 w:36)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:32)
+	fn swap_limit_take() -> Weight {
+		Weight::from_parts(1_412_000_000, 101_331)
+			.saturating_add(T::DbWeight::get().reads(43_u64))
+			.saturating_add(T::DbWeight::get().writes(74_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:36 w:36)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:32)
+	fn swap_limit_give() -> Weight {
+		Weight::from_parts(1_418_000_000, 101_331)
+			.saturating_add(T::DbWeight::get().reads(43_u64))
+			.saturating_add(T::DbWeight::get().writes(74_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:0)
+	fn get_asset_price() -> Weight {
+		Weight::from_parts(13_000_000, 3_566)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+	}
+	/// Storage: Dex Pools (r:3 w:3)
+	/// Storage: Dex Observations (r:3 w:3)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:3 w:3)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:100 w:100)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:96)
+	/// The range of component `n` is `[2, 4]`.
+	fn swap_exact_in_along_path(n: u32, ) -> Weight {
+		Weight::from_parts(60_000_000, 9_794)
+			.saturating_add(Weight::from_parts(676_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().reads((35_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+			.saturating_add(T::DbWeight::get().writes((67_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 50_110).saturating_mul(n.into()))
+	}
+	/// Storage: Dex Pools (r:3 w:3)
+	/// Storage: Dex Observations (r:3 w:3)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:3 w:3)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:100 w:100)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:96)
+	/// The range of component `n` is `[2, 4]`.
+	fn swap_exact_out_along_path(n: u32, ) -> Weight {
+		Weight::from_parts(60_000_000, 9_794)
+			.saturating_add(Weight::from_parts(676_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().reads((35_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+			.saturating_add(T::DbWeight::get().writes((67_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 50_110).saturating_mul(n.into()))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn set_protocol_fee() -> Weight {
+		Weight::from_parts(43_000_000, 9_229)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn add_liquidity() -> Weight {
+		Weight::from_parts(119_000_000, 24_127)
+			.saturating_add(T::DbWeight::get().reads(15_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:1)
+	fn set_fee_tier() -> Weight {
+		Weight::from_parts(9_000_000, 1_507)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_protocol_share() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_min_liquidity() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_lp_token_dust() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex LpTokenToPair (r:0 w:1)
+	fn create_stable_pool() -> Weight {
+		Weight::from_parts(43_000_000, 3_675)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn ramp_amplification() -> Weight {
+		Weight::from_parts(61_000_000, 9_229)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex WeightedPools (r:0 w:1)
+	fn create_weighted_pool() -> Weight {
+		Weight::from_parts(46_000_000, 3_675)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Assets Asset (r:9 w:9)
+	/// Storage: Assets Account (r:17 w:17)
+	/// Storage: System Account (r:2 w:2)
+	fn join_weighted_pool() -> Weight {
+		Weight::from_parts(398_000_000, 47_281)
+			.saturating_add(T::DbWeight::get().reads(30_u64))
+			.saturating_add(T::DbWeight::get().writes(29_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Storage: System Account (r:1 w:1)
+	fn join_weighted_pool_single() -> Weight {
+		Weight::from_parts(104_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:9 w:9)
+	/// Storage: Assets Account (r:17 w:17)
+	/// Storage: System Account (r:1 w:1)
+	fn exit_weighted_pool() -> Weight {
+		Weight::from_parts(402_000_000, 47_281)
+			.saturating_add(T::DbWeight::get().reads(28_u64))
+			.saturating_add(T::DbWeight::get().writes(28_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Storage: System Account (r:1 w:1)
+	fn exit_weighted_pool_single() -> Weight {
+		Weight::from_parts(108_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_weighted_limit_take() -> Weight {
+		Weight::from_parts(91_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_weighted_limit_give() -> Weight {
+		Weight::from_parts(92_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex LpTokenToPair (r:0 w:1)
+	fn create_constant_sum_pool() -> Weight {
+		Weight::from_parts(42_000_000, 3_675)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	fn create_concentrated_pool() -> Weight {
+		Weight::from_parts(24_000_000, 5_943)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex NextPositionId (r:1 w:1)
+	/// Storage: Dex Positions (r:0 w:1)
+	fn open_position() -> Weight {
+		Weight::from_parts(96_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(10_u64))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn decrease_position() -> Weight {
+		Weight::from_parts(98_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Storage: Dex ConcentratedPools (r:1 w:0)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn collect_position_fees() -> Weight {
+		Weight::from_parts(81_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	fn transfer_position() -> Weight {
+		Weight::from_parts(14_000_000, 3_622)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_concentrated_limit_take() -> Weight {
+		Weight::from_parts(1_164_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_concentrated_limit_give() -> Weight {
+		Weight::from_parts(1_187_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:0)
+	/// Storage: Dex NextOrderId (r:1 w:1)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:1)
+	fn place_order() -> Weight {
+		Weight::from_parts(61_000_000, 7_345)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	/// Storage: Dex OrderBookOf (r:1 w:1)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Storage: System Account (r:1 w:1)
+	fn cancel_order() -> Weight {
+		Weight::from_parts(55_000_000, 7_345)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: Dex OrderBookOf (r:1 w:1)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn fill_order() -> Weight {
+		Weight::from_parts(79_000_000, 11_934)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	fn flash_swap() -> Weight {
+		Weight::from_parts(97_000_000, 15_929)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_flash_fee() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn zap_in() -> Weight {
+		Weight::from_parts(207_000_000, 24_127)
+			.saturating_add(T::DbWeight::get().reads(15_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn zap_out() -> Weight {
+		Weight::from_parts(141_000_000, 24_127)
+			.saturating_add(T::DbWeight::get().reads(15_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex LpTokenToPair (r:0 w:1)
+	fn create_pool() -> Weight {
+		Weight::from_parts(43_000_000, 3_675)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn provide_liquidity() -> Weight {
+		Weight::from_parts(116_000_000, 24_127)
+			.saturating_add(RocksDbWeight::get().reads(15_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn remove_liquidity() -> Weight {
+		Weight::from_parts(118_000_000, 24_127)
+			.saturating_add(RocksDbWeight::get().reads(15_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:36 w:36)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:32)
+	fn swap_limit_take() -> Weight {
+		Weight::from_parts(1_412_000_000, 101_331)
+			.saturating_add(RocksDbWeight::get().reads(43_u64))
+			.saturating_add(RocksDbWeight::get().writes(74_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:36 w:36)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:32)
+	fn swap_limit_give() -> Weight {
+		Weight::from_parts(1_418_000_000, 101_331)
+			.saturating_add(RocksDbWeight::get().reads(43_u64))
+			.saturating_add(RocksDbWeight::get().writes(74_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:0)
+	fn get_asset_price() -> Weight {
+		Weight::from_parts(13_000_000, 3_566)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+	}
+	/// Storage: Dex Pools (r:3 w:3)
+	/// Storage: Dex Observations (r:3 w:3)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:3 w:3)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:100 w:100)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:96)
+	/// The range of component `n` is `[2, 4]`.
+	fn swap_exact_in_along_path(n: u32, ) -> Weight {
+		Weight::from_parts(60_000_000, 9_794)
+			.saturating_add(Weight::from_parts(676_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().reads((35_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+			.saturating_add(RocksDbWeight::get().writes((67_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 50_110).saturating_mul(n.into()))
+	}
+	/// Storage: Dex Pools (r:3 w:3)
+	/// Storage: Dex Observations (r:3 w:3)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex OrderBooks (r:3 w:3)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:100 w:100)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:96)
+	/// The range of component `n` is `[2, 4]`.
+	fn swap_exact_out_along_path(n: u32, ) -> Weight {
+		Weight::from_parts(60_000_000, 9_794)
+			.saturating_add(Weight::from_parts(676_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().reads((35_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+			.saturating_add(RocksDbWeight::get().writes((67_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 50_110).saturating_mul(n.into()))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn set_protocol_fee() -> Weight {
+		Weight::from_parts(43_000_000, 9_229)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn add_liquidity() -> Weight {
+		Weight::from_parts(119_000_000, 24_127)
+			.saturating_add(RocksDbWeight::get().reads(15_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:1)
+	fn set_fee_tier() -> Weight {
+		Weight::from_parts(9_000_000, 1_507)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_protocol_share() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_min_liquidity() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_lp_token_dust() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex LpTokenToPair (r:0 w:1)
+	fn create_stable_pool() -> Weight {
+		Weight::from_parts(43_000_000, 3_675)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn ramp_amplification() -> Weight {
+		Weight::from_parts(61_000_000, 9_229)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex WeightedPools (r:0 w:1)
+	fn create_weighted_pool() -> Weight {
+		Weight::from_parts(46_000_000, 3_675)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Assets Asset (r:9 w:9)
+	/// Storage: Assets Account (r:17 w:17)
+	/// Storage: System Account (r:2 w:2)
+	fn join_weighted_pool() -> Weight {
+		Weight::from_parts(398_000_000, 47_281)
+			.saturating_add(RocksDbWeight::get().reads(30_u64))
+			.saturating_add(RocksDbWeight::get().writes(29_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Storage: System Account (r:1 w:1)
+	fn join_weighted_pool_single() -> Weight {
+		Weight::from_parts(104_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:9 w:9)
+	/// Storage: Assets Account (r:17 w:17)
+	/// Storage: System Account (r:1 w:1)
+	fn exit_weighted_pool() -> Weight {
+		Weight::from_parts(402_000_000, 47_281)
+			.saturating_add(RocksDbWeight::get().reads(28_u64))
+			.saturating_add(RocksDbWeight::get().writes(28_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Storage: System Account (r:1 w:1)
+	fn exit_weighted_pool_single() -> Weight {
+		Weight::from_parts(108_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_weighted_limit_take() -> Weight {
+		Weight::from_parts(91_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex WeightedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_weighted_limit_give() -> Weight {
+		Weight::from_parts(92_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex NextLpTokenId (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Dex LpTokenToPair (r:0 w:1)
+	fn create_constant_sum_pool() -> Weight {
+		Weight::from_parts(42_000_000, 3_675)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: Dex FeeTiers (r:1 w:0)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	fn create_concentrated_pool() -> Weight {
+		Weight::from_parts(24_000_000, 5_943)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex NextPositionId (r:1 w:1)
+	/// Storage: Dex Positions (r:0 w:1)
+	fn open_position() -> Weight {
+		Weight::from_parts(96_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(10_u64))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn decrease_position() -> Weight {
+		Weight::from_parts(98_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Storage: Dex ConcentratedPools (r:1 w:0)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn collect_position_fees() -> Weight {
+		Weight::from_parts(81_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	fn transfer_position() -> Weight {
+		Weight::from_parts(14_000_000, 3_622)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_concentrated_limit_take() -> Weight {
+		Weight::from_parts(1_164_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn swap_concentrated_limit_give() -> Weight {
+		Weight::from_parts(1_187_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:0)
+	/// Storage: Dex NextOrderId (r:1 w:1)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex OrderBookOf (r:0 w:1)
+	fn place_order() -> Weight {
+		Weight::from_parts(61_000_000, 7_345)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	/// Storage: Dex OrderBookOf (r:1 w:1)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Storage: System Account (r:1 w:1)
+	fn cancel_order() -> Weight {
+		Weight::from_parts(55_000_000, 7_345)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: Dex OrderBookOf (r:1 w:1)
+	/// Storage: Dex OrderBooks (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	fn fill_order() -> Weight {
+		Weight::from_parts(79_000_000, 11_934)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: System Account (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	fn flash_swap() -> Weight {
+		Weight::from_parts(97_000_000, 15_929)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
+	}
+	/// Storage: Dex DexParameters (r:1 w:1)
+	fn set_flash_fee() -> Weight {
+		Weight::from_parts(8_000_000, 1_527)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn zap_in() -> Weight {
+		Weight::from_parts(207_000_000, 24_127)
+			.saturating_add(RocksDbWeight::get().reads(15_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+	/// Storage: Dex Pools (r:1 w:1)
+	/// Storage: Dex DexParameters (r:1 w:0)
+	/// Storage: Dex Observations (r:1 w:1)
+	/// Storage: Timestamp Now (r:1 w:0)
+	/// Storage: Assets Asset (r:3 w:3)
+	/// Storage: Assets Account (r:6 w:6)
+	/// Storage: System Account (r:2 w:2)
+	fn zap_out() -> Weight {
+		Weight::from_parts(141_000_000, 24_127)
+			.saturating_add(RocksDbWeight::get().reads(15_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+}
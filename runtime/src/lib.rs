@@ -8,16 +8,17 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use codec::{Decode, Encode};
 use frame_support::traits::AsEnsureOriginWithArg;
+use frame_support::PalletId;
 use frame_system::{EnsureRoot, EnsureSigned};
 use pallet_grandpa::AuthorityId as GrandpaId;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{crypto::KeyTypeId, Get, OpaqueMetadata};
+use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, Convert, IdentifyAccount, NumberFor, One,
-		Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, Convert,
+		IdentifyAccount, NumberFor, One, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, FixedU128, MultiSignature,
@@ -64,6 +65,9 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset in `pallet_assets`, also used to identify assets in the DEX.
+pub type AssetId = u32;
+
 /// Index of a transaction in the chain.
 pub type Nonce = u32;
 
@@ -104,12 +108,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	//   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
-	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
-	//   the compatible custom types.
-	spec_version: 100,
+	// This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+	//   the compatible custom types, and is bumped whenever the runtime's logic changes.
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// Bumped whenever the encoding of calls or of the signed extensions changes.
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -281,8 +286,8 @@ parameter_types! {
 impl pallet_assets::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Balance = u128;
-	type AssetId = u32;
-	type AssetIdParameter = codec::Compact<u32>;
+	type AssetId = AssetId;
+	type AssetIdParameter = codec::Compact<AssetId>;
 	type Currency = Balances;
 	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
 	type ForceOrigin = EnsureRoot<AccountId>;
@@ -312,23 +317,46 @@ impl Convert<AuraId, AccountId> for AuthorityToAccount {
 	}
 }
 
+parameter_types! {
+	pub DexProtocolFeeShare: Perbill = Perbill::from_rational(1u32, 6u32);
+	pub DexMaxProtocolFeeShare: Perbill = Perbill::from_percent(50);
+	pub DexFlashFee: Perbill = Perbill::from_perthousand(3);
+	pub DexProtocolFeeRecipient: AccountId = PalletId(*b"py/dxfee").into_account_truncating();
+	/// The fee every pool was charged before pools had fee tiers, to migrate them to.
+	pub DexLegacyFeeTier: Perbill = Perbill::from_percent(1);
+}
+
 /// Configure the pallet-dex in pallets/dex.
 impl pallet_dex::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
 	type NativeBalance = Balances;
 	type Fungibles = Assets;
-	type DexAssetId = u32;
+	type DexAssetId = AssetId;
+	type NativeAssetId = ConstU32<0>;
+	type AdminOrigin = EnsureRoot<AccountId>;
 	type LpTokenDust = ConstU128<1>;
-	type FeePct = Fee1Percent;
+	type MinimumLiquidity = ConstU128<1_000>;
+	type FlashFee = DexFlashFee;
+	type MaxFeeTiers = ConstU32<4>;
+	type ProtocolFeeShare = DexProtocolFeeShare;
+	type MaxProtocolFeeShare = DexMaxProtocolFeeShare;
+	type ProtocolFeeRecipient = DexProtocolFeeRecipient;
 	type AssetBalancePairToRatioConverter = AssetBalancePairToRatioConverter;
-}
-
-pub struct Fee1Percent;
-
-impl Get<Perbill> for Fee1Percent {
-	fn get() -> Perbill {
-		Perbill::from_rational(1u32, 100u32)
-	}
+	type MaxPathLength = ConstU32<4>;
+	type Time = Timestamp;
+	type MaxObservations = ConstU32<24>;
+	type MaxAmplification = ConstU32<1_000_000>;
+	type MinAmplificationRampDuration = ConstU64<86_400>;
+	type Curves = ();
+	type MaxWeightedPoolAssets = ConstU32<8>;
+	type MaxConcentratedTicks = ConstU32<64>;
+	type MaxOrdersPerBook = ConstU32<32>;
+	type MinOrderAmount = ConstU128<10_000>;
+	type LpTokenIdStart = ConstU32<1_000>;
+	type WeightInfo = pallet_dex::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
 }
 
 pub struct AssetBalancePairToRatioConverter;
@@ -371,7 +399,8 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_dex::ChargeDexTxPayment<Runtime>,
+	pallet_dex::CheckDexDeadline<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -379,6 +408,9 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Migrations to run on the next runtime upgrade.
+pub type Migrations = (pallet_dex::migration::MigrateToV1<Runtime, DexLegacyFeeTier>,);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -386,6 +418,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -400,9 +433,8 @@ mod benches {
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
 		[pallet_sudo, Sudo]
+		[pallet_assets, Assets]
 		[pallet_dex, Dex]
-		[pallet_dpos, Dpos]
-		[pallet_voting, Voting]
 	);
 }
 
@@ -574,6 +606,34 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_dex::DexApi<Block, AssetId, Balance> for Runtime {
+		fn quote_exact_in(asset_in: AssetId, asset_out: AssetId, amount_in: Balance) -> Option<Balance> {
+			Dex::quote_exact_in(asset_in, asset_out, amount_in)
+		}
+		fn quote_exact_out(asset_in: AssetId, asset_out: AssetId, amount_out: Balance) -> Option<Balance> {
+			Dex::quote_exact_out(asset_in, asset_out, amount_out)
+		}
+		fn get_reserves(asset_a: AssetId, asset_b: AssetId, fee_tier: Perbill) -> Option<(Balance, Balance)> {
+			Dex::get_reserves(asset_a, asset_b, fee_tier)
+		}
+		fn list_pools() -> Vec<pallet_dex::PoolInfo<AssetId, Balance>> {
+			Dex::list_pools()
+		}
+		fn lp_token_of(asset_a: AssetId, asset_b: AssetId, fee_tier: Perbill) -> Option<AssetId> {
+			Dex::lp_token_of(asset_a, asset_b, fee_tier)
+		}
+
+		fn get_twap(
+			asset_id: AssetId,
+			other_asset_id: AssetId,
+			fee_tier: Perbill,
+			from: u64,
+			to: u64,
+		) -> Option<FixedU128> {
+			Dex::get_twap(asset_id, other_asset_id, fee_tier, from, to)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (